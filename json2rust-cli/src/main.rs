@@ -1,6 +1,6 @@
 use clap::{Arg, Command};
 use json2rust_lib::*;
-use std::fs;
+use std::fs::{self, File};
 use std::io::{self, Read};
 
 #[tokio::main]
@@ -18,6 +18,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .action(clap::ArgAction::Append)
                 .required(false),
         )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Input format; guessed from the file extension when omitted, JSON for stdin")
+                .value_parser(["json", "jsonl", "json5", "yaml", "toml", "csv", "msgpack", "cbor", "jsonschema"]),
+        )
+        .arg(
+            Arg::new("ndjson")
+                .long("ndjson")
+                .help("Treat input as JSON Lines, one record per line (same as --format jsonl)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-samples")
+                .long("max-samples")
+                .value_name("N")
+                .help("Analyze a random sample of at most N JSON Lines records; the rest are still checked to be valid JSON")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .help("Analyze on a thread pool: every input, and the elements of a JSON array or the records of JSON Lines input, which are read into memory rather than streamed. Results merge in input order into one schema before generating code")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("openapi")
+                .long("openapi")
+                .value_name("FILE")
+                .help("Generate one type per components/schemas entry of an OpenAPI 3 document (YAML or JSON)")
+                .conflicts_with("input"),
+        )
+        .arg(
+            Arg::new("operations")
+                .long("operations")
+                .help("With --openapi, also generate request/response types named after each operationId")
+                .requires("openapi")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("har")
+                .long("har")
+                .value_name("FILE")
+                .help("Generate request/response types per endpoint from the JSON bodies of a HAR capture")
+                .conflicts_with_all(["input", "openapi"]),
+        )
         .arg(
             Arg::new("existing")
                 .short('e')
@@ -34,6 +81,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Output file (stdout if not specified)")
                 .required(false),
         )
+        .arg(
+            Arg::new("target")
+                .long("target")
+                .value_name("TARGET")
+                .help("Write the generated types as Rust code, a JSON Schema (2020-12) document or TypeScript declarations")
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    EmitterRegistry::with_builtins().names().into_iter().map(str::to_string),
+                ))
+                .default_value("rust"),
+        )
         .arg(
             Arg::new("struct-name")
                 .short('n')
@@ -51,6 +108,83 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_parser(["optional", "enum", "hybrid"])
                 .default_value("optional"),
         )
+        .arg(
+            Arg::new("string-formats")
+                .long("string-formats")
+                .value_name("FORMATS")
+                .help("Comma-separated string formats to emit as typed fields (datetime, date, uuid, url, ip, base64, or all)")
+                .value_parser(["datetime", "date", "uuid", "url", "ip", "base64", "all"])
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("string-enums")
+                .long("string-enums")
+                .help("Generate unit enums for string fields with a small set of distinct values")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("enum-unknown-variant")
+                .long("enum-unknown-variant")
+                .help("Add a #[serde(other)] Unknown variant to generated string enums")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("serde-default")
+                .long("serde-default")
+                .help("Add #[serde(default)] to fields missing from some samples")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("double-option")
+                .long("double-option")
+                .help("Emit optional fields as Option<Option<T>> to tell absent from null (needs serde_with)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("struct-naming")
+                .long("struct-naming")
+                .value_name("STRATEGY")
+                .help("Name given to structurally identical structs once merged")
+                .value_parser(["first-seen", "shortest", "common-suffix"])
+                .default_value("first-seen"),
+        )
+        .arg(
+            Arg::new("no-dedupe")
+                .long("no-dedupe")
+                .help("Keep structurally identical structs separate")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("keyword-style")
+                .long("keyword-style")
+                .value_name("STYLE")
+                .help("How keys that are Rust keywords become field names (r#type or type_)")
+                .value_parser(["raw", "suffix"])
+                .default_value("raw"),
+        )
+        .arg(
+            Arg::new("map-path")
+                .long("map-path")
+                .value_name("PATH")
+                .help("JSON path to always treat as a map, e.g. users or teams[].members (repeatable)")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("singular")
+                .long("singular")
+                .value_name("PLURAL=SINGULAR")
+                .help("Singular to use when naming elements of arrays called PLURAL, e.g. data=record (repeatable)")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("map-type")
+                .long("map-type")
+                .value_name("TYPE")
+                .help("Collection type for map-like objects")
+                .value_parser(["hashmap", "btreemap"])
+                .default_value("hashmap"),
+        )
         .arg(
             Arg::new("show-intermediate")
                 .long("show-intermediate")
@@ -59,16 +193,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .get_matches();
 
-    let input_files: Vec<String> = if let Some(input_files) = matches.get_many::<String>("input") {
-        input_files.cloned().collect()
-    } else {
-        // Read from stdin
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        // Write to temp file for processing
-        let temp_file = "tmp/stdin_input.json";
-        fs::write(temp_file, buffer)?;
-        vec![temp_file.to_string()]
+    // Each input is a file path, or None for stdin
+    let inputs: Vec<Option<&String>> = match matches.get_many::<String>("input") {
+        Some(input_files) => input_files.map(Some).collect(),
+        None => vec![None],
+    };
+    let format: Option<InputFormat> = match matches.get_one::<String>("format") {
+        Some(format) => Some(format.as_str().into()),
+        None => matches.get_flag("ndjson").then_some(InputFormat::JsonLines),
     };
 
     let (mut existing_structs, mut current_code) = if let Some(existing_file) = matches.get_one::<String>("existing") {
@@ -82,15 +214,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let struct_name = matches.get_one::<String>("struct-name").unwrap();
     let merge_strategy = matches.get_one::<String>("merge-strategy").unwrap().as_str().into();
     let show_intermediate = matches.get_flag("show-intermediate");
+    let target = matches.get_one::<String>("target").unwrap();
+    
+    let mut config = GenerationConfig::default();
+    for format in matches.get_many::<String>("string-formats").into_iter().flatten() {
+        if format == "all" {
+            config = GenerationConfig::with_all_string_formats();
+            break;
+        }
+        config.string_formats.push(format.parse()?);
+    }
+    config.string_enums = matches.get_flag("string-enums");
+    config.enum_unknown_variant = matches.get_flag("enum-unknown-variant");
+    config.map_type = matches.get_one::<String>("map-type").unwrap().as_str().into();
+    config.serde_default = matches.get_flag("serde-default");
+    config.double_option = matches.get_flag("double-option");
+    config.dedupe_structs = !matches.get_flag("no-dedupe");
+    config.struct_naming = matches.get_one::<String>("struct-naming").unwrap().as_str().into();
+    config.keyword_style = matches.get_one::<String>("keyword-style").unwrap().as_str().into();
+    for pair in matches.get_many::<String>("singular").into_iter().flatten() {
+        let (plural, singular) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("Expected PLURAL=SINGULAR, got '{}'", pair))?;
+        config.singular_overrides.insert(plural.to_lowercase(), singular.to_string());
+    }
+    
+    let analysis_options = AnalysisOptions {
+        map_paths: matches.get_many::<String>("map-path").into_iter().flatten().cloned().collect(),
+        max_samples: matches.get_one::<usize>("max-samples").copied(),
+        ..AnalysisOptions::default()
+    };
     
+    // API descriptions and captures hold several root types rather than samples of one
+    let imported_roots = if let Some(spec_file) = matches.get_one::<String>("openapi") {
+        eprintln!("📝 Importing OpenAPI document {}", spec_file);
+        let options = OpenApiOptions { operations: matches.get_flag("operations") };
+        Some(import_openapi(&fs::read_to_string(spec_file)?, &options)?)
+    } else if let Some(har_file) = matches.get_one::<String>("har") {
+        eprintln!("📝 Importing HAR capture {}", har_file);
+        Some(import_har(&fs::read_to_string(har_file)?, &analysis_options)?)
+    } else {
+        None
+    };
+    if let Some(schemas) = imported_roots {
+        let generated_types = generate_rust_types_for_schemas(&schemas, &existing_structs, &merge_strategy, &config)?;
+        let generated_code = generate_code_with_types_and_preservation_and_config(&generated_types, current_code.as_deref(), &merge_strategy, None, &config)?;
+        write_output(matches.get_one::<String>("output"), target, generated_code, &generated_types, struct_name)?;
+        return Ok(());
+    }
+
+    if matches.get_flag("parallel") {
+        eprintln!("📝 Analyzing {} inputs in parallel", inputs.len());
+        let json_schema = merge_parallel(&inputs, struct_name, |input| analyze_input(*input, format, struct_name, &analysis_options, true))?;
+        let generated_types = generate_rust_types_with_config(&json_schema, &existing_structs, &merge_strategy, &config)?;
+        let generated_code = generate_code_with_types_and_preservation_and_config(&generated_types, current_code.as_deref(), &merge_strategy, Some(&json_schema), &config)?;
+        write_output(matches.get_one::<String>("output"), target, generated_code, &generated_types, struct_name)?;
+        return Ok(());
+    }
+
     // Process each input file sequentially
-    for (step, input_file) in input_files.iter().enumerate() {
-        eprintln!("📝 Step {}: Processing {}", step + 1, input_file);
+    for (step, input) in inputs.iter().enumerate() {
+        eprintln!("📝 Step {}: Processing {}", step + 1, input.map_or("<stdin>", |file| file.as_str()));
         
-        let input_json = fs::read_to_string(input_file)?;
-        let json_schema = analyze_json(&input_json, struct_name)?;
-        let generated_types = generate_rust_types_with_strategy(&json_schema, &existing_structs, &merge_strategy)?;
-        let generated_code = generate_code_with_types_and_preservation_and_schema(&generated_types, current_code.as_deref(), &merge_strategy, Some(&json_schema))?;
+        let json_schema = analyze_input(*input, format, struct_name, &analysis_options, false)?;
+        let generated_types = generate_rust_types_with_config(&json_schema, &existing_structs, &merge_strategy, &config)?;
+        let generated_code = generate_code_with_types_and_preservation_and_config(&generated_types, current_code.as_deref(), &merge_strategy, Some(&json_schema), &config)?;
         
         if show_intermediate {
             eprintln!("🔄 Intermediate result after step {}:", step + 1);
@@ -104,14 +292,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         existing_structs = parse_existing_structs(&generated_code)?;
         
         // Final output
-        if step == input_files.len() - 1 {
-            if let Some(output_file) = matches.get_one::<String>("output") {
-                fs::write(output_file, generated_code)?;
-            } else {
-                println!("{}", generated_code);
-            }
+        if step == inputs.len() - 1 {
+            write_output(matches.get_one::<String>("output"), target, generated_code, &generated_types, struct_name)?;
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Writes the result of the run: the Rust source, or the generated types as written by the
+/// emitter registered under `target`.
+fn write_output(
+    output_file: Option<&String>,
+    target: &str,
+    rust_code: String,
+    types: &GeneratedTypes,
+    root_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = EmitterRegistry::with_builtins();
+    let emitter = registry.get(target).ok_or_else(|| format!("Unknown target '{}'", target))?;
+    // The Rust source also keeps whatever the existing file held besides the generated types
+    let output = if emitter.name() == RustEmitter.name() { rust_code } else { emitter.emit(types, root_name)? };
+    match output_file {
+        Some(output_file) => fs::write(output_file, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// Analyzes one input file, or stdin when `path` is None, in `format` or else the format its
+/// extension suggests. JSON and JSON Lines are streamed instead of read into memory, unless
+/// `parallel` splits their records across the thread pool.
+fn analyze_input(
+    path: Option<&String>,
+    format: Option<InputFormat>,
+    struct_name: &str,
+    options: &AnalysisOptions,
+    parallel: bool,
+) -> Result<JsonSchema, Json2RustError> {
+    let format = format
+        .or_else(|| path.and_then(|file| InputFormat::from_path(file)))
+        .unwrap_or(InputFormat::Json);
+    let reader: Box<dyn Read> = match path {
+        Some(file) => Box::new(File::open(file)?),
+        None => Box::new(io::stdin().lock()),
+    };
+    match parallel {
+        true => analyze_reader_parallel(reader, format, struct_name, options),
+        false => analyze_reader(reader, format, struct_name, options),
+    }
+}
@@ -10,7 +10,7 @@ fn test_simple_json_object() {
     write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
     
     let output = Command::new("cargo")
-        .args(&["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Person"])
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Person"])
         .output()
         .expect("Failed to execute command");
     
@@ -18,7 +18,7 @@ fn test_simple_json_object() {
     
     assert!(stdout.contains("pub struct Person"));
     assert!(stdout.contains("pub name: String"));
-    assert!(stdout.contains("pub age: f64"));
+    assert!(stdout.contains("pub age: u32"));
     assert!(stdout.contains("pub active: bool"));
     assert!(stdout.contains("Serialize, Deserialize"));
 }
@@ -31,7 +31,7 @@ fn test_json_array() {
     write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
     
     let output = Command::new("cargo")
-        .args(&["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Users"])
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Users"])
         .output()
         .expect("Failed to execute command");
     
@@ -40,10 +40,445 @@ fn test_json_array() {
     assert!(stdout.contains("pub struct Users"));
     assert!(stdout.contains("pub items: Vec<User>"));
     assert!(stdout.contains("pub struct User"));
-    assert!(stdout.contains("pub id: f64"));
+    assert!(stdout.contains("pub id: u32"));
     assert!(stdout.contains("pub name: String"));
 }
 
+#[test]
+fn test_numeric_type_inference() {
+    let json_input = r#"[{"id": 1, "offset": -3, "created_ms": 1700000000000, "score": 1}, {"id": 2, "offset": 4, "created_ms": 1700000000001, "score": 2.5}]"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Events"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub id: u32"));
+    assert!(stdout.contains("pub offset: i64"));
+    assert!(stdout.contains("pub created_ms: i64"));
+    assert!(stdout.contains("pub score: f64"));
+}
+
+#[test]
+fn test_string_formats() {
+    let json_input = r#"{"id": "550e8400-e29b-41d4-a716-446655440000", "created_at": "2024-03-01T12:30:00Z", "homepage": "https://example.com"}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Account", "--string-formats", "uuid,datetime"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub id: uuid::Uuid"));
+    assert!(stdout.contains("pub created_at: chrono::DateTime<chrono::Utc>"));
+    assert!(stdout.contains("pub homepage: String"));
+}
+
+#[test]
+fn test_tagged_event_stream() {
+    let json_input = r#"[{"type": "login", "user": "alice"}, {"type": "purchase", "user": "bob", "amount": 9.99}, {"type": "login", "user": "carol"}]"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Events"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("#[serde(tag = \"type\")]"));
+    assert!(stdout.contains("pub enum Event"));
+    assert!(stdout.contains("Purchase {"));
+    assert!(stdout.contains("amount: f64"));
+    assert!(stdout.contains("pub items: Vec<Event>"));
+}
+
+#[test]
+fn test_missing_and_null_fields() {
+    let json_input = r#"[{"id": 1, "email": null}, {"id": 2, "email": "b@example.com", "nickname": "b"}]"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Users", "--serde-default"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub id: u32"));
+    assert!(stdout.contains("pub email: Option<String>"));
+    assert!(stdout.contains("#[serde(default)]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub nickname: Option<String>"));
+}
+
+#[test]
+fn test_recursive_tree() {
+    let json_input = r#"{"path": "/", "entries": [{"path": "/etc", "entries": [{"path": "/etc/hosts"}]}, {"path": "/tmp", "entries": []}]}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Dir"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub entries: Option<Vec<Dir>>"));
+    assert_eq!(stdout.matches("pub struct").count(), 1);
+}
+
+#[test]
+fn test_map_path_override() {
+    let json_input = r#"{"labels": {"env": "prod", "team": "core"}}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Resource", "--map-path", "labels", "--map-type", "btreemap"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub labels: std::collections::BTreeMap<String, String>"));
+    assert!(!stdout.contains("pub struct Labels"));
+}
+
+#[test]
+fn test_identical_structs_deduplicated() {
+    let json_input = r#"{"billing_address": {"street": "1 Main", "city": "Oslo"}, "shipping_address": {"street": "2 Side", "city": "Bergen"}}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Order", "--struct-naming", "common-suffix"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub struct Address"));
+    assert!(stdout.contains("pub billing_address: Address"));
+    assert!(stdout.contains("pub shipping_address: Address"));
+    assert!(!stdout.contains("pub struct BillingAddress"));
+}
+
+#[test]
+fn test_keyword_and_symbol_keys() {
+    let json_input = r#"{"type": "user", "$ref": "/defs/a", "1st": true, "userId": 1, "user_id": 2}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Doc", "--keyword-style", "suffix"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("#[serde(rename = \"type\")]"));
+    assert!(stdout.contains("pub type_: String"));
+    assert!(stdout.contains("#[serde(rename = \"$ref\")]"));
+    assert!(stdout.contains("pub ref_: String"));
+    assert!(stdout.contains("pub _1st: bool"));
+    assert!(stdout.contains("pub user_id_2: u32"));
+    assert!(stdout.contains("pub user_id: u32"));
+}
+
+#[test]
+fn test_array_element_names_are_singular() {
+    let json_input = r#"{"addresses": [{"city": "Oslo"}], "children": [{"age": 3}], "data": [{"v": 1}], "metrics": [{"n": 1}]}"#;
+    
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Report", "--singular", "data=sample", "--no-dedupe"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub addresses: Vec<Address>"));
+    assert!(stdout.contains("pub children: Vec<Child>"));
+    assert!(stdout.contains("pub data: Vec<Sample>"));
+    assert!(stdout.contains("pub metrics: Vec<Metric>"));
+}
+
+#[test]
+fn test_ndjson_input() {
+    let json_lines = "{\"id\": 1, \"msg\": \"start\"}\n{\"id\": 2, \"msg\": \"fail\", \"error\": \"disk full\"}\n{\"id\": 3, \"msg\": \"done\"}\n";
+    
+    let mut temp_file = tempfile::Builder::new().suffix(".ndjson").tempfile().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_lines).expect("Failed to write to temp file");
+    
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "LogEntry", "--max-samples", "100"])
+        .output()
+        .expect("Failed to execute command");
+    
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    
+    assert!(stdout.contains("pub struct LogEntry"));
+    assert!(stdout.contains("pub id: u32"));
+    assert!(stdout.contains("pub error: Option<String>"));
+    assert!(!stdout.contains("Vec<"));
+}
+
+#[test]
+fn test_parallel_inputs_merge_into_one_schema() {
+    let mut first = NamedTempFile::new().expect("Failed to create temp file");
+    write!(first, r#"{{"id": 1, "name": "a"}}"#).expect("Failed to write to temp file");
+    let mut second = NamedTempFile::new().expect("Failed to create temp file");
+    write!(second, r#"{{"id": 2, "email": "b@example.com"}}"#).expect("Failed to write to temp file");
+    let mut third = tempfile::Builder::new().suffix(".jsonl").tempfile().expect("Failed to create temp file");
+    write!(third, "{{\"id\": 3, \"name\": \"c\"}}\n{{\"id\": 4, \"name\": \"d\"}}\n").expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args([
+            "run", "--bin", "json2rust", "--", "--parallel", "-n", "User",
+            "-i", first.path().to_str().unwrap(),
+            "-i", second.path().to_str().unwrap(),
+            "-i", third.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("pub struct User"));
+    assert!(stdout.contains("pub id: u32"));
+    assert!(stdout.contains("pub name: Option<String>"));
+    assert!(stdout.contains("pub email: Option<String>"));
+    assert!(!stdout.contains("enum"));
+}
+
+#[test]
+fn test_non_json_input_formats() {
+    let run = |suffix: &str, contents: &str, extra: &[&str]| {
+        let mut temp_file = tempfile::Builder::new().suffix(suffix).tempfile().expect("Failed to create temp file");
+        write!(temp_file, "{}", contents).expect("Failed to write to temp file");
+        let mut args = vec!["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Config"];
+        args.extend_from_slice(extra);
+        let output = Command::new("cargo").args(&args).output().expect("Failed to execute command");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).expect("Invalid UTF-8")
+    };
+
+    let yaml = run(".yaml", "name: app\nreplicas: 3\nports:\n  - 80\n  - 443\n", &[]);
+    assert!(yaml.contains("pub name: String"));
+    assert!(yaml.contains("pub replicas: u32"));
+    assert!(yaml.contains("pub ports: Vec<u32>"));
+
+    let toml = run(".toml", "title = \"release\"\npublished = 1979-05-27T07:32:00Z\n", &[]);
+    assert!(toml.contains("pub published: toml::value::Datetime"));
+
+    let csv = run(".txt", "id,price,sku\n1,9.99,0042\n2,15,17\n", &["--format", "csv"]);
+    assert!(csv.contains("Vec<ConfigItem>"));
+    assert!(csv.contains("pub price: f64"));
+    assert!(csv.contains("pub sku: String"));
+
+    let json5 = run(".jsonc", "{\n  // comment\n  \"debug\": true,\n}\n", &[]);
+    assert!(json5.contains("pub debug: bool"));
+}
+
+#[test]
+fn test_binary_input_formats() {
+    // MessagePack for {"id": 1, "blob": <bin 01 02>, "labels": {1: "a"}}
+    let msgpack: &[u8] = &[
+        0x83, 0xa2, b'i', b'd', 0x01, 0xa4, b'b', b'l', b'o', b'b', 0xc4, 0x02, 0x01, 0x02,
+        0xa6, b'l', b'a', b'b', b'e', b'l', b's', 0x81, 0x01, 0xa1, b'a',
+    ];
+    let mut temp_file = tempfile::Builder::new().suffix(".bin").tempfile().expect("Failed to create temp file");
+    temp_file.write_all(msgpack).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Packet", "--format", "msgpack"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.contains("pub id: u8"));
+    assert!(stdout.contains("#[serde(with = \"serde_bytes\")]\n    pub blob: Vec<u8>"));
+    assert!(stdout.contains("pub labels: std::collections::HashMap<i64, String>"));
+}
+
+#[test]
+fn test_json_schema_input() {
+    let schema = r##"{
+        "type": "object",
+        "required": ["id", "owner"],
+        "properties": {
+            "id": {"type": "integer", "format": "int32"},
+            "owner": {"$ref": "#/$defs/Person"},
+            "tags": {"type": "array", "items": {"type": "string"}}
+        },
+        "$defs": {"Person": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}}
+    }"##;
+    let mut temp_file = tempfile::Builder::new().suffix(".schema.json").tempfile().expect("Failed to create temp file");
+    write!(temp_file, "{}", schema).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Project"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.contains("pub id: i32"));
+    assert!(stdout.contains("pub owner: Person"));
+    assert!(stdout.contains("pub struct Person"));
+    assert!(stdout.contains("pub tags: Option<Vec<String>>"));
+}
+
+#[test]
+fn test_openapi_input_preserves_existing() {
+    let spec = r##"
+openapi: 3.1.0
+info: {title: Shop, version: "1"}
+paths:
+  /orders:
+    post:
+      operationId: placeOrder
+      requestBody:
+        content:
+          application/json:
+            schema: {type: object, required: [sku], properties: {sku: {type: string}}}
+      responses:
+        "201":
+          description: Placed
+          content:
+            application/json:
+              schema: {$ref: "#/components/schemas/Order"}
+components:
+  schemas:
+    Order:
+      type: object
+      required: [id, customer]
+      properties:
+        id: {type: integer, format: int32}
+        customer: {$ref: "#/components/schemas/Customer"}
+    Customer:
+      type: object
+      properties: {name: {type: string}}
+"##;
+    let existing = "use serde::{Deserialize, Serialize};\n\n#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Customer {\n    pub name: Option<String>,\n    pub loyalty_tier: Option<String>,\n}\n";
+    let mut spec_file = tempfile::Builder::new().suffix(".yaml").tempfile().expect("Failed to create temp file");
+    write!(spec_file, "{}", spec).expect("Failed to write to temp file");
+    let mut existing_file = tempfile::Builder::new().suffix(".rs").tempfile().expect("Failed to create temp file");
+    write!(existing_file, "{}", existing).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args([
+            "run", "--bin", "json2rust", "--",
+            "--openapi", spec_file.path().to_str().unwrap(),
+            "--operations",
+            "-e", existing_file.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.contains("pub loyalty_tier: Option<String>"));
+    assert!(stdout.contains("pub struct Order"));
+    assert!(stdout.contains("pub id: i32"));
+    assert!(stdout.contains("pub customer: Customer"));
+    assert!(stdout.contains("pub struct PlaceOrderRequest"));
+    assert!(!stdout.contains("PlaceOrderResponse"));
+}
+
+#[test]
+fn test_har_input() {
+    let har = r#"{"log": {"version": "1.2", "entries": [
+        {"request": {"method": "GET", "url": "https://api.example.com/users/17"},
+         "response": {"status": 200, "content": {"mimeType": "application/json", "text": "{\"id\": 17, \"name\": \"Ann\"}"}}},
+        {"request": {"method": "GET", "url": "https://api.example.com/users/42?fields=all"},
+         "response": {"status": 200, "content": {"mimeType": "application/json", "text": "{\"id\": 42, \"name\": \"Bo\", \"admin\": true}"}}}
+    ]}}"#;
+    let mut temp_file = tempfile::Builder::new().suffix(".har").tempfile().expect("Failed to create temp file");
+    write!(temp_file, "{}", har).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "--har", temp_file.path().to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.contains("pub struct GetUsersByIdResponse"));
+    assert!(stdout.contains("pub admin: Option<bool>"));
+}
+
+#[test]
+fn test_json_schema_target() {
+    let json_input = r#"[{"id": 1, "name": "Ann", "email": "ann@example.com"}, {"id": 2, "name": "Bo"}]"#;
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "People", "--target", "json-schema"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON Schema output");
+    assert_eq!(schema["title"], "People");
+    assert_eq!(schema["properties"]["items"]["items"]["$ref"], "#/$defs/Person");
+    assert_eq!(schema["$defs"]["Person"]["required"], serde_json::json!(["id", "name"]));
+}
+
+#[test]
+fn test_typescript_target() {
+    let json_input = r#"{"id": 1, "display-name": "Ann", "address": {"city": "Oslo"}, "nickname": null}"#;
+    let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+    write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Profile", "--target", "ts"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert!(stdout.contains("export interface Profile {"));
+    assert!(stdout.contains("  \"display-name\": string;"));
+    assert!(stdout.contains("  address: Address;"));
+    assert!(stdout.contains("export interface Address {"));
+    assert!(!stdout.contains("pub struct"));
+}
+
+#[test]
+fn test_unknown_target_lists_registered_emitters() {
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "json2rust", "--", "-n", "Root", "--target", "protobuf"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[possible values: rust, json-schema, ts]"), "{}", stderr);
+}
+
 #[test]
 fn test_nested_json() {
     let json_input = r#"{"user": {"name": "John", "profile": {"age": 30}}, "posts": [{"title": "Hello", "id": 1}]}"#;
@@ -52,7 +487,7 @@ fn test_nested_json() {
     write!(temp_file, "{}", json_input).expect("Failed to write to temp file");
     
     let output = Command::new("cargo")
-        .args(&["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Root"])
+        .args(["run", "--bin", "json2rust", "--", "-i", temp_file.path().to_str().unwrap(), "-n", "Root"])
         .output()
         .expect("Failed to execute command");
     
@@ -60,11 +495,11 @@ fn test_nested_json() {
     
     assert!(stdout.contains("pub struct Root"));
     assert!(stdout.contains("pub user: User"));
-    assert!(stdout.contains("pub posts: Vec<PostsItem>"));
+    assert!(stdout.contains("pub posts: Vec<Post>"));
     assert!(stdout.contains("pub struct User"));
     assert!(stdout.contains("pub profile: Profile"));
     assert!(stdout.contains("pub struct Profile"));
-    assert!(stdout.contains("pub struct PostsItem"));
+    assert!(stdout.contains("pub struct Post "));
 }
 
 #[test]
@@ -84,7 +519,7 @@ fn test_with_existing_struct() {
     write!(existing_file, "{}", existing_struct).expect("Failed to write to temp file");
     
     let output = Command::new("cargo")
-        .args(&[
+        .args([
             "run", "--bin", "json2rust", "--", 
             "-i", json_file.path().to_str().unwrap(),
             "-e", existing_file.path().to_str().unwrap(),
@@ -101,12 +536,59 @@ fn test_with_existing_struct() {
     assert!(stdout.contains("pub email: Option<String>"));
 }
 
+/// Builds `code` in a scratch crate next to the test binaries. serde is already in the local
+/// registry once the workspace has built, so this works offline.
+fn assert_compiles(code: &str) {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile-check");
+    std::fs::create_dir_all(dir.join("src")).expect("Failed to create scratch crate");
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"compile-check\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nserde_json = \"1\"\n\n[workspace]\n",
+    )
+    .expect("Failed to write manifest");
+    std::fs::write(dir.join("src/lib.rs"), format!("#![allow(dead_code)]\n{}", code)).expect("Failed to write source");
+
+    let output = Command::new("cargo")
+        .args(["check", "--offline", "--quiet"])
+        .current_dir(&dir)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}\n{}", code, String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_camel_case_inputs_in_sequence() {
+    let first_input = r#"{"firstName": "Ann", "lastName": "Lee"}"#;
+    let second_input = r#"{"firstName": "Bo", "lastName": "Ng", "userAge": 3}"#;
+    let mut first = NamedTempFile::new().expect("Failed to create temp file");
+    write!(first, "{}", first_input).expect("Failed to write to temp file");
+    let mut second = NamedTempFile::new().expect("Failed to create temp file");
+    write!(second, "{}", second_input).expect("Failed to write to temp file");
+
+    let output = Command::new("cargo")
+        .args([
+            "run", "--bin", "json2rust", "--",
+            "-i", first.path().to_str().unwrap(),
+            "-i", second.path().to_str().unwrap(),
+            "-n", "User",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).expect("Invalid UTF-8");
+    assert_eq!(stdout.matches("#[derive(").count(), 1, "{}", stdout);
+    assert_eq!(stdout.matches("#[serde(rename_all = \"camelCase\")]").count(), 1, "{}", stdout);
+    assert!(stdout.contains("pub user_age: Option<u32>"));
+    assert_compiles(&stdout);
+}
+
 #[test]
 fn test_stdin_input() {
     let json_input = r#"{"message": "Hello World"}"#;
     
     let mut child = Command::new("cargo")
-        .args(&["run", "--bin", "json2rust", "--", "-n", "Message"])
+        .args(["run", "--bin", "json2rust", "--", "-n", "Message"])
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
         .spawn()
@@ -53,7 +53,7 @@ impl ApiEvolution {
             }
             
             // Expand the requested type and add those shapes
-            let existing_shapes = self.shape_expander.expand_type(&requested_type, self.verbose)?;
+            let existing_shapes = self.shape_expander.expand_type(requested_type, self.verbose)?;
             if self.verbose {
                 println!("\n📈 Expanded requested type into {} shapes:", existing_shapes.len());
                 for (i, shape) in existing_shapes.iter().enumerate() {
@@ -79,7 +79,7 @@ impl ApiEvolution {
                 // Smart handling based on type
                 match &base_type.kind {
                     TypeKind::Struct { .. } => {
-                        let existing_shapes = self.shape_expander.expand_type(&base_type, self.verbose)?;
+                        let existing_shapes = self.shape_expander.expand_type(base_type, self.verbose)?;
                         if self.verbose {
                             println!("\n📈 Expanded existing struct into {} shapes:", existing_shapes.len());
                             for (i, shape) in existing_shapes.iter().enumerate() {
@@ -162,8 +162,13 @@ impl ApiEvolution {
             serde_json::Value::Null => "Option<()>".to_string(),
             serde_json::Value::Bool(_) => "bool".to_string(),
             serde_json::Value::Number(n) => {
-                if n.is_i64() {
-                    "i64".to_string()
+                // Same narrowing as json2rust-lib's analyzer for a single sample
+                if let Some(i) = n.as_i64() {
+                    if (0..=u32::MAX as i64).contains(&i) {
+                        "u32".to_string()
+                    } else {
+                        "i64".to_string()
+                    }
                 } else if n.is_u64() {
                     "u64".to_string()
                 } else {
@@ -253,7 +258,7 @@ impl ApiEvolution {
         }
         
         // Sort by score (highest first)
-        variant_scores.sort_by(|a, b| b.1.cmp(&a.1));
+        variant_scores.sort_by_key(|entry| std::cmp::Reverse(entry.1));
         
         if let Some((best_variant, best_score)) = variant_scores.first() {
             if self.verbose {
@@ -272,7 +277,7 @@ impl ApiEvolution {
                     
                     // For each variant shape, try to merge with JSON shape
                     for (i, variant_shape) in variant_shapes.iter().enumerate() {
-                        let merged_shape = self.merge_shapes(&json_shape, &variant_shape);
+                        let merged_shape = self.merge_shapes(json_shape, variant_shape);
                         if self.verbose {
                             println!("    🔗 Merged shape {} with JSON:", i + 1);
                             self.print_shape(&merged_shape, "      ");
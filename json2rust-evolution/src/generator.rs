@@ -1,6 +1,10 @@
 use crate::shape::ShapeField;
 use crate::optimizer::ShapeVariant;
 use anyhow::Result;
+use json2rust_lib::{
+    Emitter, EnumRepresentation, GeneratedTypes, RustEmitter, RustEnum, RustEnumVariant, RustField, RustStruct,
+    type_ref_from_rust,
+};
 
 #[derive(Debug)]
 pub enum EvolutionResult {
@@ -51,47 +55,39 @@ impl EvolutionResult {
         }
     }
     
-    pub fn generate_rust_code(&self) -> Result<String> {
+    /// The evolved types in the shared IR, for any `json2rust_lib::Emitter` to write out.
+    pub fn generated_types(&self) -> GeneratedTypes {
+        let mut types = GeneratedTypes { structs: Vec::new(), enums: Vec::new() };
         match self {
             Self::SimpleStruct { name, fields } => {
-                self.generate_struct_code(name, fields)
+                types.structs.push(rust_struct(name, fields.iter().map(rust_field).collect()));
             }
             Self::ComplexEnum { name, common_fields, variants } => {
-                self.generate_complex_enum_code(name, common_fields, variants)
+                Self::complex_enum_types(name, common_fields, variants, &mut types);
             }
-            Self::StructWithExtendedEnum { struct_name, struct_fields, enum_name, new_enum_variants } => {
-                self.generate_struct_with_extended_enum_code(struct_name, struct_fields, enum_name, new_enum_variants)
+            // For surgical replacement only the struct is generated;
+            // the enum extension is handled separately in main.rs
+            Self::StructWithExtendedEnum { struct_name, struct_fields, .. } => {
+                types.structs.push(rust_struct(struct_name, struct_fields.iter().map(rust_field).collect()));
             }
         }
+        types
     }
     
-    fn generate_struct_code(&self, name: &str, fields: &[ShapeField]) -> Result<String> {
-        let mut code = String::new();
-        
-        code.push_str(&format!("#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\n"));
-        code.push_str(&format!("pub struct {} {{\n", name));
-        
-        for field in fields {
-            let field_type = if field.is_required {
-                field.field_type.clone()
-            } else if field.field_type.starts_with("Option<") || field.field_type.starts_with("Option <") {
-                // Field is already optional, don't double-wrap
-                field.field_type.clone()
-            } else {
-                format!("Option<{}>", field.field_type)
-            };
-            
-            code.push_str(&format!("    pub {}: {},\n", field.name, field_type));
+    /// Rust source for the evolved types, structs before enums.
+    pub fn generate_rust_code(&self) -> Result<String> {
+        let types = self.generated_types();
+        let mut items = Vec::new();
+        for rust_struct in &types.structs {
+            items.push(RustEmitter.emit_struct(rust_struct, &types)?);
         }
-        
-        code.push_str("}\n");
-        
-        Ok(code)
+        for rust_enum in &types.enums {
+            items.push(RustEmitter.emit_enum(rust_enum, &types)?);
+        }
+        Ok(items.join("\n"))
     }
     
-    fn generate_complex_enum_code(&self, name: &str, common_fields: &[ShapeField], variants: &[ShapeVariant]) -> Result<String> {
-        let mut code = String::new();
-        
+    fn complex_enum_types(name: &str, common_fields: &[ShapeField], variants: &[ShapeVariant], types: &mut GeneratedTypes) {
         // Check if this is a simple case: common fields + one variant with fields + one empty variant
         // This should become a struct with an Option<SubStruct> field instead of an enum
         if variants.len() == 2 {
@@ -99,37 +95,20 @@ impl EvolutionResult {
             let non_empty_variants: Vec<_> = variants.iter().filter(|v| !v.fields.is_empty()).collect();
             
             if has_empty_variant && non_empty_variants.len() == 1 && !common_fields.is_empty() {
-                // Generate struct with Option<SubStruct> pattern
+                // Generate the extra struct for non-common fields
                 let extra_variant = non_empty_variants[0];
                 let extra_struct_name = format!("{}Extra", name);
+                types.structs.push(rust_struct(&extra_struct_name, extra_variant.fields.iter().map(rust_field).collect()));
                 
-                // Generate the extra struct for non-common fields
-                code.push_str(&self.generate_struct_code(&extra_struct_name, &extra_variant.fields)?);
-                code.push_str("\n");
-                
-                // Generate the main struct with common fields + optional extra
-                code.push_str(&format!("#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\n"));
-                code.push_str(&format!("pub struct {} {{\n", name));
-                
-                // Add common fields
-                for field in common_fields {
-                    let field_type = if field.is_required {
-                        field.field_type.clone()
-                    } else if field.field_type.starts_with("Option<") || field.field_type.starts_with("Option <") {
-                        field.field_type.clone()
-                    } else {
-                        format!("Option<{}>", field.field_type)
-                    };
-                    code.push_str(&format!("    pub {}: {},\n", field.name, field_type));
-                }
-                
-                // Add the optional extra fields as a single Option<SubStruct>
-                code.push_str(&format!("    #[serde(flatten)]\n"));
-                code.push_str(&format!("    pub extra: Option<{}>,\n", extra_struct_name));
-                
-                code.push_str("}\n");
-                
-                return Ok(code);
+                // The main struct has the common fields plus the optional extra fields
+                // as a single Option<SubStruct>
+                let mut fields: Vec<RustField> = common_fields.iter().map(rust_field).collect();
+                fields.push(RustField {
+                    serde_flatten: true,
+                    ..plain_field("extra", format!("Option<{}>", extra_struct_name))
+                });
+                types.structs.push(rust_struct(name, fields));
+                return;
             }
         }
         
@@ -137,54 +116,59 @@ impl EvolutionResult {
         if !common_fields.is_empty() {
             // Generate a base struct with common fields
             let base_name = format!("{}Base", name);
-            code.push_str(&self.generate_struct_code(&base_name, common_fields)?);
-            code.push_str("\n");
+            types.structs.push(rust_struct(&base_name, common_fields.iter().map(rust_field).collect()));
         }
         
-        // Generate the enum
-        code.push_str(&format!("#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\n"));
-        code.push_str(&format!("#[serde(untagged)]\n"));
-        code.push_str(&format!("pub enum {} {{\n", name));
-        
-        for variant in variants {
-            code.push_str(&format!("    {} {{\n", variant.name));
-            
-            // Include common fields if any
-            if !common_fields.is_empty() {
-                code.push_str(&format!("        #[serde(flatten)]\n"));
-                code.push_str(&format!("        base: {}Base,\n", name));
-            }
-            
-            // Add variant-specific fields
-            for field in &variant.fields {
-                let field_type = if field.is_required {
-                    field.field_type.clone()
-                } else if field.field_type.starts_with("Option<") || field.field_type.starts_with("Option <") {
-                    // Field is already optional, don't double-wrap
-                    field.field_type.clone()
-                } else {
-                    format!("Option<{}>", field.field_type)
-                };
-                code.push_str(&format!("        {}: {},\n", field.name, field_type));
-            }
-            
-            code.push_str("    },\n");
-        }
-        
-        code.push_str("}\n");
-        
-        Ok(code)
+        let variants = variants
+            .iter()
+            .map(|variant| {
+                let mut fields = Vec::new();
+                // Include common fields if any
+                if !common_fields.is_empty() {
+                    fields.push(RustField { serde_flatten: true, ..plain_field("base", format!("{}Base", name)) });
+                }
+                fields.extend(variant.fields.iter().map(rust_field));
+                RustEnumVariant { name: variant.name.clone(), fields, serde_rename: None, serde_other: false, newtype: None, newtype_ty: None }
+            })
+            .collect();
+        types.enums.push(RustEnum {
+            name: name.to_string(),
+            variants,
+            derives: derives(),
+            representation: EnumRepresentation::Untagged,
+            docs: Vec::new(),
+        });
     }
-    
-    fn generate_struct_with_extended_enum_code(
-        &self, 
-        struct_name: &str, 
-        struct_fields: &[ShapeField], 
-        _enum_name: &str, 
-        _new_enum_variants: &[ShapeVariant]
-    ) -> Result<String> {
-        // For surgical replacement, only generate the struct code
-        // The enum extension is handled separately in main.rs
-        self.generate_struct_code(struct_name, struct_fields)
+}
+
+fn derives() -> Vec<String> {
+    ["Debug", "Clone", "serde::Serialize", "serde::Deserialize"].map(String::from).to_vec()
+}
+
+fn rust_struct(name: &str, fields: Vec<RustField>) -> RustStruct {
+    RustStruct { name: name.to_string(), fields, derives: derives(), is_optional: false, docs: Vec::new() }
+}
+
+/// A shape field with its optionality spelled out in the type, as evolved types are written.
+fn rust_field(field: &ShapeField) -> RustField {
+    let field_type = if field.is_required || field.field_type.starts_with("Option<") || field.field_type.starts_with("Option <") {
+        // Field is already optional, don't double-wrap
+        field.field_type.clone()
+    } else {
+        format!("Option<{}>", field.field_type)
+    };
+    plain_field(&field.name, field_type)
+}
+
+fn plain_field(name: &str, type_name: String) -> RustField {
+    RustField {
+        name: name.to_string(),
+        ty: type_ref_from_rust(&type_name),
+        type_name,
+        is_optional: false,
+        serde_rename: None,
+        serde_flatten: false,
+        serde_with: None,
+        serde_default: false,
     }
 }
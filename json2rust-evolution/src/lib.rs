@@ -250,4 +250,33 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_complex_enum_emits_through_shared_ir() {
+        use crate::generator::EvolutionResult;
+        use crate::optimizer::ShapeVariant;
+        use crate::shape::ShapeField;
+        use json2rust_lib::{Emitter, TypeScriptEmitter};
+
+        let field = |name: &str, field_type: &str, is_required| ShapeField {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            is_required,
+        };
+        let result = EvolutionResult::complex_enum(
+            "Event",
+            vec![field("id", "i64", true)],
+            vec![
+                ShapeVariant { name: "Click".to_string(), fields: vec![field("x", "i32", true)] },
+                ShapeVariant { name: "Key".to_string(), fields: vec![field("code", "String", false)] },
+            ],
+        );
+
+        let code = result.generate_rust_code().unwrap();
+        assert!(code.starts_with("#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\npub struct EventBase {\n    pub id: i64,\n}\n\n"), "{}", code);
+        assert!(code.contains("    Key { #[serde(flatten)] base: EventBase, code: Option<String> },\n"), "{}", code);
+
+        let typescript = TypeScriptEmitter.emit(&result.generated_types(), "").unwrap();
+        assert!(typescript.contains("export type Event =\n  | { x: number } & EventBase\n  | { code?: string | null } & EventBase;\n"), "{}", typescript);
+    }
 }
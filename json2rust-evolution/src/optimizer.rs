@@ -909,6 +909,7 @@ impl ShapeOptimizer {
     }
     
     /// Create a mixed result that includes both folded-back patterns and non-matching variants
+    #[allow(clippy::too_many_arguments)]
     fn create_mixed_fold_back_result(
         &self, 
         folded_variant_indices: &[usize], 
@@ -1023,6 +1024,10 @@ impl ShapeOptimizer {
             ("u16", "u32" | "u64") => field1.clone(),
             ("u32" | "u64", "u16") => field2.clone(),
             
+            // Declared signed types win over the u32 inferred for non-negative samples
+            ("i8" | "i16" | "i32" | "i64", "u32") => field1.clone(),
+            ("u32", "i8" | "i16" | "i32" | "i64") => field2.clone(),
+            
             // If one field is required and other is optional, prefer required
             _ if field1.is_required && !field2.is_required => field1.clone(),
             _ if !field1.is_required && field2.is_required => field2.clone(),
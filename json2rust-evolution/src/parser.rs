@@ -42,6 +42,12 @@ pub struct RustParser {
     source_code: Option<String>,
 }
 
+impl Default for RustParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RustParser {
     pub fn new() -> Self {
         Self { source_code: None }
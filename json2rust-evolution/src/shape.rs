@@ -14,6 +14,12 @@ pub struct ShapeMetadata {
     pub source_enum_type: Option<String>,          // Track which enum type this shape came from
 }
 
+impl Default for ShapeMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ShapeMetadata {
     pub fn new() -> Self {
         Self {
@@ -42,6 +48,12 @@ pub struct ShapeExpander {
     known_types: HashMap<String, TypeInfo>,
 }
 
+impl Default for ShapeExpander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ShapeExpander {
     pub fn new() -> Self {
         Self { 
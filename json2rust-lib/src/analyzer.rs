@@ -1,83 +1,145 @@
+use crate::case::to_pascal_case;
+use crate::formats::detect_string_format;
 use crate::types::*;
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// Bounds on the distinct values kept per string field for enum inference
+const MAX_TRACKED_STRING_VALUES: usize = 64;
+const MAX_TRACKED_STRING_LEN: usize = 64;
+
+// Map detection: minimum keys sharing an id-like pattern, and the key count past
+// which any object with uniformly shaped values is treated as a map
+const MIN_PATTERN_MAP_KEYS: usize = 4;
+const MIN_UNPATTERNED_MAP_KEYS: usize = 64;
+const MIN_MAP_VALUE_KEY_OVERLAP: f64 = 0.5;
+
+// Mixed arrays up to this length are read as fixed-position tuples rather than lists
+pub(crate) const MAX_TUPLE_LEN: usize = 8;
+
+// Tagged enum detection: conventional discriminator keys are tried first and may tag
+// singleton groups; any other string field must see each of its values at least twice
+pub(crate) const TAG_FIELD_NAMES: &[&str] = &["type", "kind", "tag", "event", "event_type", "eventType", "op", "action"];
+pub(crate) const MAX_TAGGED_VARIANTS: usize = 32;
+
+// A nested object is folded into an ancestor when their key sets overlap this much
+const MIN_SELF_SIMILAR_KEY_OVERLAP: f64 = 0.5;
 
 pub fn analyze_json(json_str: &str, root_name: &str) -> Result<JsonSchema, Json2RustError> {
+    analyze_json_with_options(json_str, root_name, &AnalysisOptions::default())
+}
+
+pub fn analyze_json_with_options(
+    json_str: &str,
+    root_name: &str,
+    options: &AnalysisOptions,
+) -> Result<JsonSchema, Json2RustError> {
     let value: Value = serde_json::from_str(json_str)?;
-    
-    if json_str.trim().starts_with('[') {
-        analyze_json_array(&value, root_name)
+    analyze_document(&value, root_name, options)
+}
+
+/// Analyzes an already parsed document; a top-level array describes a list of root records.
+pub fn analyze_document(value: &Value, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    if value.is_array() {
+        analyze_json_array(value, root_name, options)
     } else {
-        analyze_json_value(&value, root_name)
+        analyze_json_value(value, root_name, "", options)
     }
 }
 
-fn analyze_json_array(value: &Value, root_name: &str) -> Result<JsonSchema, Json2RustError> {
+/// Analyzes every sample on its own and merges the schemas in sample order, as if each were
+/// one more iteration of the same API response. No samples give an empty root object.
+pub fn analyze_samples<S: AsRef<str>>(samples: &[S], root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let mut merged: Option<JsonSchema> = None;
+    for sample in samples {
+        let schema = analyze_json_with_options(sample.as_ref(), root_name, options)?;
+        merged = Some(match merged {
+            Some(existing) => merge_schemas(existing, schema)?,
+            None => schema,
+        });
+    }
+    Ok(merged.unwrap_or_else(|| empty_schema(root_name)))
+}
+
+pub(crate) fn empty_schema(root_name: &str) -> JsonSchema {
+    JsonSchema {
+        name: root_name.to_string(),
+        json_type: JsonType::Object(ObjectStats::default()),
+        optional: false,
+    }
+}
+
+fn analyze_json_array(value: &Value, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
     if let Value::Array(arr) = value {
         if arr.is_empty() {
             return Ok(JsonSchema {
                 name: root_name.to_string(),
-                json_type: JsonType::Array(Box::new(JsonType::Object(HashMap::new()))),
+                json_type: JsonType::Array(Box::new(JsonType::Object(ObjectStats::default()))),
                 optional: false,
             });
         }
 
-        let mut merged_schema = None;
-        for item in arr {
-            let item_schema = analyze_json_value(item, &format!("{}Item", root_name))?;
-            merged_schema = Some(match merged_schema {
-                None => item_schema,
-                Some(existing) => merge_schemas(existing, item_schema)?,
+        if let Some(tagged) = analyze_tagged_union(arr, "[]", options)? {
+            return Ok(JsonSchema {
+                name: root_name.to_string(),
+                json_type: JsonType::Array(Box::new(JsonType::Tagged(tagged))),
+                optional: false,
             });
         }
 
-        if let Some(schema) = merged_schema {
-            Ok(JsonSchema {
-                name: root_name.to_string(),
-                json_type: JsonType::Array(Box::new(schema.json_type)),
-                optional: false,
-            })
-        } else {
-            Ok(JsonSchema {
-                name: root_name.to_string(),
-                json_type: JsonType::Array(Box::new(JsonType::Object(HashMap::new()))),
-                optional: false,
-            })
+        let mut item_types = Vec::new();
+        for item in arr {
+            item_types.push(analyze_json_value(item, &format!("{}Item", root_name), "[]", options)?.json_type);
         }
+        Ok(JsonSchema {
+            name: root_name.to_string(),
+            json_type: JsonType::Array(Box::new(merge_element_types(item_types)?)),
+            optional: false,
+        })
     } else {
-        analyze_json_value(value, root_name)
+        analyze_json_value(value, root_name, "", options)
     }
 }
 
-fn analyze_json_value(value: &Value, name: &str) -> Result<JsonSchema, Json2RustError> {
+/// `path` locates `value` in the document using the syntax of `AnalysisOptions::map_paths`:
+/// dot-separated keys, `[]` for array elements and `*` for map values.
+pub(crate) fn analyze_json_value(value: &Value, name: &str, path: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
     let json_type = match value {
         Value::Null => JsonType::Null,
         Value::Bool(_) => JsonType::Boolean,
-        Value::Number(_) => JsonType::Number,
-        Value::String(_) => JsonType::String,
+        Value::Number(n) => number_type(n),
+        Value::String(s) => string_type(s),
         Value::Array(arr) => {
             if arr.is_empty() {
                 JsonType::Array(Box::new(JsonType::Null))
+            } else if let Some(tagged) = analyze_tagged_union(arr, &format!("{}[]", path), options)? {
+                JsonType::Array(Box::new(JsonType::Tagged(tagged)))
             } else {
-                let mut element_type = None;
+                let mut item_types = Vec::new();
                 for item in arr {
-                    let item_schema = analyze_json_value(item, &format!("{}Item", name))?;
-                    element_type = Some(match element_type {
-                        None => item_schema.json_type,
-                        Some(existing) => merge_types(existing, item_schema.json_type)?,
-                    });
+                    let item_schema = analyze_json_value(item, &format!("{}Item", name), &format!("{}[]", path), options)?;
+                    item_types.push(item_schema.json_type);
+                }
+                let element_type = merge_element_types(item_types.clone())?;
+                
+                if is_mixed(&element_type) && arr.len() <= MAX_TUPLE_LEN {
+                    JsonType::Tuple(item_types)
+                } else {
+                    JsonType::Array(Box::new(element_type))
                 }
-                JsonType::Array(Box::new(element_type.unwrap_or(JsonType::Null)))
             }
         }
-        Value::Object(obj) => {
-            let mut fields = HashMap::new();
-            for (key, val) in obj {
-                let field_schema = analyze_json_value(val, &to_pascal_case(key))?;
-                fields.insert(key.clone(), field_schema.json_type);
+        Value::Object(obj) if is_map_path(path, options) || (options.detect_maps && is_map_like(&entry_shapes(obj))) => {
+            let value_path = join_path(path, "*");
+            let mut value_types = Vec::new();
+            for val in obj.values() {
+                value_types.push(analyze_json_value(val, name, &value_path, options)?.json_type);
             }
-            JsonType::Object(fields)
+            JsonType::Map(MapKey::String, Box::new(merge_element_types(value_types)?))
         }
+        Value::Object(obj) => JsonType::Object(analyze_fields(obj, path, options)?),
     };
 
     Ok(JsonSchema {
@@ -87,7 +149,260 @@ fn analyze_json_value(value: &Value, name: &str) -> Result<JsonSchema, Json2Rust
     })
 }
 
-fn merge_schemas(schema1: JsonSchema, schema2: JsonSchema) -> Result<JsonSchema, Json2RustError> {
+fn analyze_fields<'a>(
+    fields: impl IntoIterator<Item = (&'a String, &'a Value)>,
+    path: &str,
+    options: &AnalysisOptions,
+) -> Result<ObjectStats, Json2RustError> {
+    let mut field_types = IndexMap::new();
+    for (key, val) in fields {
+        let field_schema = analyze_json_value(val, &to_pascal_case(key), &join_path(path, key), options)?;
+        field_types.insert(key.clone(), field_schema.json_type);
+    }
+    Ok(ObjectStats::from_fields(field_types))
+}
+
+/// Looks for a string field whose value splits the objects of `arr` into groups with
+/// different field sets, and analyzes each group as one variant of a tagged enum.
+/// `item_path` is the path of the array's elements.
+fn analyze_tagged_union(arr: &[Value], item_path: &str, options: &AnalysisOptions) -> Result<Option<TaggedUnion>, Json2RustError> {
+    let Some(objects) = arr.iter().map(Value::as_object).collect::<Option<Vec<_>>>() else {
+        return Ok(None);
+    };
+    let Some((tag, content)) = find_tag_field(&objects) else {
+        return Ok(None);
+    };
+    
+    let mut variants: IndexMap<String, JsonType> = IndexMap::new();
+    for obj in objects {
+        let (tag_value, payload) = analyze_tagged_variant(obj, &tag, content.as_deref(), item_path, options)?;
+        add_tagged_variant(&mut variants, tag_value, payload)?;
+    }
+    
+    Ok(Some(TaggedUnion { tag, content, variants }))
+}
+
+/// Splits one object of a tagged array into its tag value and analyzed payload.
+pub(crate) fn analyze_tagged_variant(
+    obj: &Map<String, Value>,
+    tag: &str,
+    content: Option<&str>,
+    item_path: &str,
+    options: &AnalysisOptions,
+) -> Result<(String, JsonType), Json2RustError> {
+    let tag_value = obj[tag].as_str().unwrap_or_default().to_string();
+    let payload = match content {
+        Some(content) => analyze_json_value(&obj[content], "", &join_path(item_path, content), options)?.json_type,
+        None => JsonType::Object(analyze_fields(obj.iter().filter(|(key, _)| *key != tag), item_path, options)?),
+    };
+    Ok((tag_value, payload))
+}
+
+pub(crate) fn add_tagged_variant(variants: &mut IndexMap<String, JsonType>, tag_value: String, payload: JsonType) -> Result<(), Json2RustError> {
+    match variants.get_mut(&tag_value) {
+        Some(existing) => {
+            let existing_payload = std::mem::replace(existing, JsonType::Null);
+            *existing = merge_types(existing_payload, payload)?;
+        }
+        None => {
+            variants.insert(tag_value, payload);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the discriminator key and, for adjacently tagged envelopes, the payload key.
+pub(crate) fn find_tag_field(objects: &[&Map<String, Value>]) -> Option<(String, Option<String>)> {
+    let first = objects.first()?;
+    let mut candidates: Vec<&String> = first
+        .keys()
+        .filter(|key| objects.iter().all(|obj| obj.get(*key).is_some_and(Value::is_string)))
+        .collect();
+    candidates.sort_by_key(|key| !TAG_FIELD_NAMES.contains(&key.as_str()));
+    
+    candidates.into_iter().find_map(|tag| {
+        let content = adjacent_content_key(objects, tag);
+        let mut groups: IndexMap<&str, HashSet<&String>> = IndexMap::new();
+        for obj in objects {
+            let payload_keys = groups.entry(obj[tag].as_str()?).or_default();
+            match &content {
+                Some(content) => payload_keys.extend(obj[content].as_object()?.keys()),
+                None => payload_keys.extend(obj.keys().filter(|key| *key != tag)),
+            }
+        }
+        
+        let group_keys: Vec<&HashSet<&String>> = groups.values().collect();
+        is_tagged_grouping(tag, &group_keys, objects.len()).then(|| (tag.clone(), content))
+    })
+}
+
+/// Whether splitting `object_count` objects by their `tag` value gives groups worth an enum:
+/// a sensible number of them, and payload key sets that are not all the same.
+pub(crate) fn is_tagged_grouping<K: Eq + Hash>(tag: &str, groups: &[&HashSet<K>], object_count: usize) -> bool {
+    let is_conventional = TAG_FIELD_NAMES.contains(&tag);
+    let variant_count_ok = groups.len() >= 2
+        && groups.len() <= MAX_TAGGED_VARIANTS
+        && (is_conventional || groups.len() * 2 <= object_count);
+    // The tag only earns an enum if the groups actually differ in shape
+    let shapes_differ = groups.iter().any(|keys| Some(keys) != groups.first());
+    variant_count_ok && shapes_differ
+}
+
+/// An adjacently tagged envelope holds exactly the tag and one object-valued payload key.
+fn adjacent_content_key(objects: &[&Map<String, Value>], tag: &str) -> Option<String> {
+    let content = objects.first()?.keys().find(|key| *key != tag)?;
+    objects
+        .iter()
+        .all(|obj| obj.len() == 2 && obj.get(content).is_some_and(Value::is_object))
+        .then(|| content.clone())
+}
+
+pub(crate) fn join_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else if segment == "[]" {
+        format!("{}[]", path)
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+pub(crate) fn is_map_path(path: &str, options: &AnalysisOptions) -> bool {
+    options.map_paths.iter().any(|map_path| {
+        let map_path = map_path.strip_prefix('$').unwrap_or(map_path);
+        map_path.strip_prefix('.').unwrap_or(map_path) == path
+    })
+}
+
+#[derive(Debug, PartialEq)]
+enum KeyPattern {
+    Numeric,
+    Uuid,
+    Date,
+    Hex,
+    Prefixed(String),  // Shared alphabetic prefix followed by digits, e.g. `u123`
+}
+
+fn key_pattern(key: &str) -> Option<KeyPattern> {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_digit()) {
+        return Some(KeyPattern::Numeric);
+    }
+    match detect_string_format(key) {
+        Some(StringFormat::Uuid) => return Some(KeyPattern::Uuid),
+        Some(StringFormat::Date | StringFormat::DateTime) => return Some(KeyPattern::Date),
+        _ => {}
+    }
+    if key.len() >= 8 && key.chars().all(|c| c.is_ascii_hexdigit()) && key.chars().any(|c| c.is_ascii_digit()) {
+        return Some(KeyPattern::Hex);
+    }
+
+    let prefix = key.trim_end_matches(|c: char| c.is_ascii_digit());
+    let is_prefixed_id = !prefix.is_empty()
+        && prefix.len() < key.len()
+        && prefix.chars().all(|c| c.is_ascii_alphabetic() || c == '_' || c == '-');
+    is_prefixed_id.then(|| KeyPattern::Prefixed(prefix.to_string()))
+}
+
+/// The kind of a JSON value, as far as the map heuristic cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+    Bytes,  // Only produced by binary input formats
+}
+
+/// What the map heuristic needs to know about one entry of an object.
+pub(crate) struct EntryShape<'a> {
+    pub key: &'a str,
+    pub kind: ValueKind,
+    pub keys: Option<Vec<&'a str>>,  // Keys of an object value
+}
+
+fn entry_shapes(obj: &Map<String, Value>) -> Vec<EntryShape<'_>> {
+    obj.iter()
+        .map(|(key, value)| EntryShape {
+            key,
+            kind: value_kind(value),
+            keys: value.as_object().map(|o| o.keys().map(String::as_str).collect()),
+        })
+        .collect()
+}
+
+pub(crate) fn value_kind(value: &Value) -> ValueKind {
+    match value {
+        Value::Null => ValueKind::Null,
+        Value::Bool(_) => ValueKind::Bool,
+        Value::Number(_) => ValueKind::Number,
+        Value::String(_) => ValueKind::String,
+        Value::Array(_) => ValueKind::Array,
+        Value::Object(_) => ValueKind::Object,
+    }
+}
+
+/// Heuristic for objects that are really dictionaries: the keys look like data rather
+/// than field names (or there are a great many of them) and the values share one shape.
+pub(crate) fn is_map_like(entries: &[EntryShape]) -> bool {
+    if entries.len() < MIN_PATTERN_MAP_KEYS || !has_uniform_values(entries) {
+        return false;
+    }
+    if entries.len() >= MIN_UNPATTERNED_MAP_KEYS {
+        return true;
+    }
+
+    let mut patterns = entries.iter().map(|entry| key_pattern(entry.key));
+    match patterns.next() {
+        Some(Some(first)) => patterns.all(|pattern| pattern.as_ref() == Some(&first)),
+        _ => false,
+    }
+}
+
+fn has_uniform_values(entries: &[EntryShape]) -> bool {
+    let kinds: Vec<ValueKind> = entries.iter().map(|e| e.kind).filter(|kind| *kind != ValueKind::Null).collect();
+    let Some(first) = kinds.first() else {
+        return true;
+    };
+    if !kinds.iter().all(|kind| kind == first) {
+        return false;
+    }
+
+    // Object values must mostly share their keys
+    let key_sets: Vec<HashSet<&str>> = entries
+        .iter()
+        .filter_map(|e| e.keys.as_ref())
+        .map(|keys| keys.iter().copied().collect())
+        .collect();
+    let all_keys: HashSet<&str> = key_sets.iter().flatten().copied().collect();
+    all_keys.is_empty()
+        || key_sets
+            .iter()
+            .all(|keys| keys.len() as f64 / all_keys.len() as f64 >= MIN_MAP_VALUE_KEY_OVERLAP)
+}
+
+pub(crate) fn string_type(s: &str) -> JsonType {
+    JsonType::String(StringStats {
+        format: detect_string_format(s),
+        values: (s.len() <= MAX_TRACKED_STRING_LEN).then(|| vec![s.to_string()]),
+        samples: 1,
+        declared: false,
+    })
+}
+
+pub(crate) fn number_type(n: &serde_json::Number) -> JsonType {
+    if let Some(i) = n.as_i64() {
+        JsonType::Integer { min: i, max: i }
+    } else if let Some(u) = n.as_u64() {
+        JsonType::Unsigned { min: u, max: u }
+    } else {
+        let f = n.as_f64().unwrap_or(0.0);
+        JsonType::Float { min: f, max: f }
+    }
+}
+
+pub(crate) fn merge_schemas(schema1: JsonSchema, schema2: JsonSchema) -> Result<JsonSchema, Json2RustError> {
     Ok(JsonSchema {
         name: schema1.name,
         json_type: merge_types(schema1.json_type, schema2.json_type)?,
@@ -95,65 +410,421 @@ fn merge_schemas(schema1: JsonSchema, schema2: JsonSchema) -> Result<JsonSchema,
     })
 }
 
-fn merge_types(type1: JsonType, type2: JsonType) -> Result<JsonType, Json2RustError> {
+/// Merges the elements of an array or the values of a map. Object fields count their nulls,
+/// but a collection keeps them in its type, so `[1, null]` holds `Nullable(Integer)`.
+pub(crate) fn merge_element_types(types: impl IntoIterator<Item = JsonType>) -> Result<JsonType, Json2RustError> {
+    let mut merged = JsonType::Null;
+    let mut has_null = false;
+    for json_type in types {
+        has_null |= json_type == JsonType::Null;
+        merged = merge_types(merged, json_type)?;
+    }
+    Ok(if has_null { nullable(merged) } else { merged })
+}
+
+/// `json_type` with null added to its values. Elements that were all null give
+/// `Nullable(Null)`, unlike the `Null` of an empty array, which stands for no elements at all.
+pub(crate) fn nullable(json_type: JsonType) -> JsonType {
+    match json_type {
+        JsonType::Nullable(_) => json_type,
+        json_type => JsonType::Nullable(Box::new(json_type)),
+    }
+}
+
+/// Whether the elements of an array differ in kind, the precondition for reading it as a tuple.
+pub(crate) fn is_mixed(element_type: &JsonType) -> bool {
+    match element_type {
+        JsonType::Union(_) => true,
+        JsonType::Nullable(inner) => is_mixed(inner),
+        _ => false,
+    }
+}
+
+pub(crate) fn merge_types(type1: JsonType, type2: JsonType) -> Result<JsonType, Json2RustError> {
     match (type1, type2) {
         (JsonType::Null, other) | (other, JsonType::Null) => Ok(other),
-        (JsonType::String, JsonType::String) => Ok(JsonType::String),
-        (JsonType::Number, JsonType::Number) => Ok(JsonType::Number),
+        (JsonType::Nullable(inner1), JsonType::Nullable(inner2)) => Ok(nullable(merge_types(*inner1, *inner2)?)),
+        (JsonType::Nullable(inner), other) | (other, JsonType::Nullable(inner)) => Ok(nullable(merge_types(*inner, other)?)),
+        (JsonType::String(stats1), JsonType::String(stats2)) => Ok(JsonType::String(merge_string_stats(stats1, stats2))),
+        (JsonType::Integer { min: min1, max: max1 }, JsonType::Integer { min: min2, max: max2 }) => {
+            Ok(JsonType::Integer { min: min1.min(min2), max: max1.max(max2) })
+        }
+        (JsonType::Unsigned { min: min1, max: max1 }, JsonType::Unsigned { min: min2, max: max2 }) => {
+            Ok(JsonType::Unsigned { min: min1.min(min2), max: max1.max(max2) })
+        }
+        (JsonType::Integer { min, max }, JsonType::Unsigned { min: umin, max: umax })
+        | (JsonType::Unsigned { min: umin, max: umax }, JsonType::Integer { min, max }) => {
+            if min >= 0 {
+                // Every value is non-negative, so u64 covers both sides
+                Ok(JsonType::Unsigned { min: (min as u64).min(umin), max: (max as u64).max(umax) })
+            } else {
+                // Negative values and values above i64::MAX only fit in a float
+                Ok(JsonType::Float { min: min as f64, max: umax as f64 })
+            }
+        }
+        (JsonType::FixedInt { signed: signed1, bits: bits1 }, JsonType::FixedInt { signed: signed2, bits: bits2 }) => {
+            Ok(widen_fixed_int((signed1, bits1), (signed2, bits2)))
+        }
+        (JsonType::FixedInt { signed, bits }, other) | (other, JsonType::FixedInt { signed, bits })
+            if matches!(other, JsonType::Integer { .. } | JsonType::Unsigned { .. }) =>
+        {
+            let (min, max) = integer_range(&other);
+            Ok(widen_fixed_int((signed, bits), fixed_int_width(min, max)))
+        }
+        (type1, type2) if is_number(&type1) && is_number(&type2) => {
+            let (min1, max1) = number_range(&type1);
+            let (min2, max2) = number_range(&type2);
+            Ok(JsonType::Float { min: min1.min(min2), max: max1.max(max2) })
+        }
         (JsonType::Boolean, JsonType::Boolean) => Ok(JsonType::Boolean),
+        (JsonType::Bytes, JsonType::Bytes) => Ok(JsonType::Bytes),
+        (JsonType::Map(key1, value1), JsonType::Map(key2, value2)) => {
+            // Keys of different kinds can only be told apart as strings
+            let key = if key1 == key2 { key1 } else { MapKey::String };
+            Ok(JsonType::Map(key, Box::new(merge_types(*value1, *value2)?)))
+        }
+        (JsonType::Map(_, value_type), JsonType::Object(object)) | (JsonType::Object(object), JsonType::Map(_, value_type)) => {
+            // One sample looked like a map, so fold the other's fields into its values
+            let values = object.fields.into_values().flat_map(|field| {
+                let null = (field.nulls > 0).then_some(JsonType::Null);
+                std::iter::once(field.json_type).chain(null)
+            });
+            let merged = merge_types(*value_type, merge_element_types(values)?)?;
+            Ok(JsonType::Map(MapKey::String, Box::new(merged)))
+        }
         (JsonType::Array(elem1), JsonType::Array(elem2)) => {
             let merged_elem = merge_types(*elem1, *elem2)?;
             Ok(JsonType::Array(Box::new(merged_elem)))
         }
-        (JsonType::Object(fields1), JsonType::Object(fields2)) => {
-            let mut merged_fields = fields1;
-            for (key, type2) in fields2 {
-                if let Some(type1) = merged_fields.get(&key) {
-                    let merged_type = merge_types(type1.clone(), type2)?;
-                    merged_fields.insert(key, merged_type);
-                } else {
-                    merged_fields.insert(key, type2);
+        (JsonType::Object(object1), JsonType::Object(object2)) => {
+            // Keys keep their first-seen position; keys new to this sample are appended
+            let mut merged = object1;
+            merged.samples += object2.samples;
+            for (key, field2) in object2.fields {
+                match merged.fields.get_mut(&key) {
+                    Some(field1) => {
+                        let type1 = std::mem::replace(&mut field1.json_type, JsonType::Null);
+                        field1.json_type = merge_types(type1, field2.json_type)?;
+                        field1.present += field2.present;
+                        field1.nulls += field2.nulls;
+                    }
+                    None => {
+                        merged.fields.insert(key, field2);
+                    }
+                }
+            }
+            Ok(JsonType::Object(merged))
+        }
+        (JsonType::Tuple(items1), JsonType::Tuple(items2)) if items1.len() == items2.len() => {
+            let merged_items = items1
+                .into_iter()
+                .zip(items2)
+                .map(|(item1, item2)| merge_element_types([item1, item2]))
+                .collect::<Result<_, _>>()?;
+            Ok(JsonType::Tuple(merged_items))
+        }
+        (JsonType::Tuple(items1), JsonType::Tuple(items2)) => {
+            // Lengths differ, so the positions carry no meaning after all
+            Ok(JsonType::Array(Box::new(merge_element_types(items1.into_iter().chain(items2))?)))
+        }
+        (JsonType::Tuple(items), JsonType::Array(element)) | (JsonType::Array(element), JsonType::Tuple(items)) => {
+            // An empty array's `Null` element stands for no elements, not for a null one
+            Ok(JsonType::Array(Box::new(merge_types(*element, merge_element_types(items)?)?)))
+        }
+        (JsonType::Tagged(union1), JsonType::Tagged(union2)) if union1.tag == union2.tag && union1.content == union2.content => {
+            let mut merged = union1;
+            for (tag_value, payload) in union2.variants {
+                add_tagged_variant(&mut merged.variants, tag_value, payload)?;
+            }
+            Ok(JsonType::Tagged(merged))
+        }
+        (JsonType::Tagged(union), JsonType::Object(object)) | (JsonType::Object(object), JsonType::Tagged(union)) => {
+            // Arrays holding a single variant analyze as plain objects; file them under their tag
+            match tagged_variant_of(&union, &object) {
+                Some((tag_value, payload)) => {
+                    let mut merged = union;
+                    add_tagged_variant(&mut merged.variants, tag_value, payload)?;
+                    Ok(JsonType::Tagged(merged))
                 }
+                None => merge_types(flatten_tagged_union(union)?, JsonType::Object(object)),
             }
-            Ok(JsonType::Object(merged_fields))
         }
-        (JsonType::String, JsonType::Number) | (JsonType::Number, JsonType::String) => {
-            Ok(JsonType::String)
+        (JsonType::Recursive(name1), JsonType::Recursive(name2)) if name1 == name2 => Ok(JsonType::Recursive(name1)),
+        (JsonType::Named(name, inner), other) | (other, JsonType::Named(name, inner)) if type_kind(&inner) == type_kind(&other) => {
+            // Samples of a declared type keep its name
+            let other = match other {
+                JsonType::Named(_, other) => *other,
+                other => other,
+            };
+            Ok(JsonType::Named(name, Box::new(merge_types(*inner, other)?)))
         }
-        _ => Ok(JsonType::String),
+        (JsonType::Tagged(union), other) | (other, JsonType::Tagged(union)) if type_kind(&other) == TypeKind::Object => {
+            merge_types(flatten_tagged_union(union)?, other)
+        }
+        (JsonType::Union(members), other) | (other, JsonType::Union(members)) => {
+            let mut members = members;
+            add_union_member(&mut members, other)?;
+            Ok(JsonType::Union(members))
+        }
+        (type1, type2) => Ok(JsonType::Union(vec![type1, type2])),
     }
 }
 
-pub fn to_pascal_case(s: &str) -> String {
-    s.split(['_', '-', ' '])
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
-            }
-        })
-        .collect()
+#[derive(Debug, PartialEq)]
+pub(crate) enum TypeKind {
+    Null,
+    String,
+    Number,
+    Boolean,
+    Bytes,
+    Array,
+    Object,
+    Recursive,
+    Union,
 }
 
-pub fn to_snake_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut prev_lowercase = false;
+/// Groups types that `merge_types` can combine without resorting to a union.
+pub(crate) fn type_kind(json_type: &JsonType) -> TypeKind {
+    match json_type {
+        JsonType::Null => TypeKind::Null,
+        JsonType::String(_) => TypeKind::String,
+        JsonType::Integer { .. } | JsonType::Unsigned { .. } | JsonType::Float { .. } | JsonType::FixedInt { .. } => TypeKind::Number,
+        JsonType::Boolean => TypeKind::Boolean,
+        JsonType::Bytes => TypeKind::Bytes,
+        JsonType::Array(_) | JsonType::Tuple(_) => TypeKind::Array,
+        JsonType::Object(_) | JsonType::Map(..) | JsonType::Tagged(_) => TypeKind::Object,
+        JsonType::Recursive(_) => TypeKind::Recursive,
+        JsonType::Union(_) => TypeKind::Union,
+        JsonType::Named(_, inner) | JsonType::Nullable(inner) => type_kind(inner),
+    }
+}
+
+/// Folds every nested object that repeats the shape of `object` back into it, replacing
+/// each with a `Recursive(name)` reference, so tree-shaped data yields one struct named
+/// `name` instead of a new struct per nesting level.
+pub(crate) fn fold_self_similar(object: ObjectStats, name: &str) -> Result<ObjectStats, Json2RustError> {
+    let mut pending = Vec::new();
+    let mut object = object;
+    replace_self_similar(&object.clone(), &mut object, name, &mut pending);
+    if pending.is_empty() {
+        return Ok(object);
+    }
     
-    for (i, ch) in s.char_indices() {
-        if ch.is_uppercase() {
-            if i > 0 && prev_lowercase {
-                result.push('_');
+    let ancestor = object.clone();
+    let mut merged = JsonType::Object(object);
+    while let Some(mut nested) = pending.pop() {
+        replace_self_similar(&ancestor, &mut nested, name, &mut pending);
+        merged = merge_types(merged, JsonType::Object(nested))?;
+    }
+    match merged {
+        JsonType::Object(object) => Ok(object),
+        _ => Err(Json2RustError::CodeGeneration(format!("Could not fold recursive type {}", name))),
+    }
+}
+
+fn replace_self_similar(ancestor: &ObjectStats, object: &mut ObjectStats, name: &str, pending: &mut Vec<ObjectStats>) {
+    for (key, field) in object.fields.iter_mut() {
+        replace_self_similar_in(ancestor, key, &mut field.json_type, name, pending);
+    }
+}
+
+/// `key` is the field of the object being scanned that leads to `json_type`.
+fn replace_self_similar_in(ancestor: &ObjectStats, key: &str, json_type: &mut JsonType, name: &str, pending: &mut Vec<ObjectStats>) {
+    match json_type {
+        JsonType::Object(nested) if is_self_similar(ancestor, nested, key, name) => {
+            let JsonType::Object(nested) = std::mem::replace(json_type, JsonType::Recursive(name.to_string())) else {
+                return;
+            };
+            pending.push(nested);
+        }
+        JsonType::Object(nested) => replace_self_similar(ancestor, nested, name, pending),
+        JsonType::Array(element) | JsonType::Map(_, element) => replace_self_similar_in(ancestor, key, element, name, pending),
+        JsonType::Tuple(items) | JsonType::Union(items) => {
+            for item in items {
+                replace_self_similar_in(ancestor, key, item, name, pending);
             }
-            result.push(ch.to_lowercase().next().unwrap());
-            prev_lowercase = false;
-        } else {
-            result.push(ch);
-            prev_lowercase = ch.is_lowercase();
         }
+        JsonType::Tagged(union) => {
+            for payload in union.variants.values_mut() {
+                replace_self_similar_in(ancestor, key, payload, name, pending);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A nested object repeats its ancestor's shape when most keys are shared with compatible
+/// types, and it either continues the chain through the same key or shares more than one key.
+/// Leaves reached through a key already known to recurse only need to fit inside the ancestor.
+fn is_self_similar(ancestor: &ObjectStats, nested: &ObjectStats, key: &str, name: &str) -> bool {
+    let common: Vec<&String> = nested.fields.keys().filter(|k| ancestor.fields.contains_key(*k)).collect();
+    let compatible = common.iter().all(|k| {
+        let (kind1, kind2) = (type_kind(&ancestor.fields[*k].json_type), type_kind(&nested.fields[*k].json_type));
+        // A recursion marker stands in for an object that was folded already
+        let as_object = |kind| if kind == TypeKind::Recursive { TypeKind::Object } else { kind };
+        kind1 == TypeKind::Null || kind2 == TypeKind::Null || as_object(kind1) == as_object(kind2)
+    });
+    if common.is_empty() || !compatible {
+        return false;
     }
     
-    result
+    let is_leaf_of_known_recursion = common.len() == nested.fields.len()
+        && ancestor.field_type(key).is_some_and(|json_type| refers_to(json_type, name));
+    let all_keys = ancestor.fields.len() + nested.fields.len() - common.len();
+    let overlaps = (common.len() as f64) / (all_keys as f64) >= MIN_SELF_SIMILAR_KEY_OVERLAP;
+    is_leaf_of_known_recursion || (overlaps && (common.len() >= 2 || nested.fields.contains_key(key)))
+}
+
+fn refers_to(json_type: &JsonType, name: &str) -> bool {
+    match json_type {
+        JsonType::Recursive(target) => target == name,
+        JsonType::Array(element) | JsonType::Map(_, element) | JsonType::Named(_, element) | JsonType::Nullable(element) => {
+            refers_to(element, name)
+        }
+        JsonType::Tuple(items) | JsonType::Union(items) => items.iter().any(|item| refers_to(item, name)),
+        _ => false,
+    }
+}
+
+/// Reads an object as a single variant of `union` when its tag field only ever held one value.
+fn tagged_variant_of(union: &TaggedUnion, object: &ObjectStats) -> Option<(String, JsonType)> {
+    let Some(JsonType::String(StringStats { values: Some(values), .. })) = object.field_type(&union.tag) else {
+        return None;
+    };
+    let [tag_value] = values.as_slice() else {
+        return None;
+    };
+    let payload = match &union.content {
+        Some(content) if object.fields.len() == 2 => object.field_type(content)?.clone(),
+        Some(_) => return None,
+        None => JsonType::Object(ObjectStats {
+            fields: object
+                .fields
+                .iter()
+                .filter(|(key, _)| **key != union.tag)
+                .map(|(key, field)| (key.clone(), field.clone()))
+                .collect(),
+            samples: object.samples,
+        }),
+    };
+    Some((tag_value.clone(), payload))
+}
+
+/// Collapses a tagged union back into the single object type it would have been without
+/// tag detection, for merging with samples that cannot be split by tag.
+fn flatten_tagged_union(union: TaggedUnion) -> Result<JsonType, Json2RustError> {
+    let tag_values = union.variants.keys().cloned().collect();
+    let mut payload = JsonType::Null;
+    for variant in union.variants.into_values() {
+        payload = merge_types(payload, variant)?;
+    }
+    
+    let samples = match &payload {
+        JsonType::Object(object) => object.samples,
+        _ => 1,
+    };
+    let tag_type = JsonType::String(StringStats { format: None, values: Some(tag_values), samples, declared: false });
+    let mut object = ObjectStats { fields: IndexMap::new(), samples };
+    object.fields.insert(union.tag, FieldStats { json_type: tag_type, present: samples, nulls: 0 });
+    match (union.content, payload) {
+        (Some(content), payload) => {
+            object.fields.insert(content, FieldStats { json_type: payload, present: samples, nulls: 0 });
+        }
+        (None, JsonType::Object(payload_object)) => object.fields.extend(payload_object.fields),
+        (None, _) => {}
+    }
+    Ok(JsonType::Object(object))
+}
+
+fn add_union_member(members: &mut Vec<JsonType>, json_type: JsonType) -> Result<(), Json2RustError> {
+    match json_type {
+        JsonType::Null => {}
+        JsonType::Union(others) => {
+            for other in others {
+                add_union_member(members, other)?;
+            }
+        }
+        json_type => match members.iter().position(|member| type_kind(member) == type_kind(&json_type)) {
+            Some(index) => {
+                let member = std::mem::replace(&mut members[index], JsonType::Null);
+                members[index] = merge_types(member, json_type)?;
+            }
+            None => members.push(json_type),
+        },
+    }
+    Ok(())
+}
+
+fn merge_string_stats(stats1: StringStats, stats2: StringStats) -> StringStats {
+    let values = match (stats1.values, stats2.values) {
+        (Some(mut values), Some(other)) => {
+            for value in other {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            // Declared enums are complete however long they are
+            (values.len() <= MAX_TRACKED_STRING_VALUES || (stats1.declared && stats2.declared)).then_some(values)
+        }
+        _ => None,
+    };
+
+    StringStats {
+        // A format survives only if every sample matched it
+        format: if stats1.format == stats2.format { stats1.format } else { None },
+        values,
+        samples: stats1.samples + stats2.samples,
+        declared: stats1.declared && stats2.declared,
+    }
+}
+
+fn is_number(json_type: &JsonType) -> bool {
+    matches!(json_type, JsonType::Integer { .. } | JsonType::Unsigned { .. } | JsonType::Float { .. } | JsonType::FixedInt { .. })
+}
+
+fn number_range(json_type: &JsonType) -> (f64, f64) {
+    match json_type {
+        JsonType::Float { min, max } => (*min, *max),
+        _ => {
+            let (min, max) = integer_range(json_type);
+            (min as f64, max as f64)
+        }
+    }
+}
+
+/// Range of an integer type; a fixed-width integer spans everything its width can hold.
+fn integer_range(json_type: &JsonType) -> (i128, i128) {
+    match json_type {
+        JsonType::Integer { min, max } => (*min as i128, *max as i128),
+        JsonType::Unsigned { min, max } => (*min as i128, *max as i128),
+        JsonType::FixedInt { signed: true, bits } => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+        JsonType::FixedInt { signed: false, bits } => (0, (1i128 << bits) - 1),
+        _ => (0, 0),
+    }
+}
+
+/// Narrowest standard width holding every value in `min..=max`.
+pub(crate) fn fixed_int_width(min: i128, max: i128) -> (bool, u8) {
+    let signed = min < 0;
+    let bits = [8u8, 16, 32, 64]
+        .into_iter()
+        .find(|&bits| {
+            let (low, high) = integer_range(&JsonType::FixedInt { signed, bits });
+            low <= min && max <= high
+        })
+        .unwrap_or(64);
+    (signed, bits)
+}
+
+/// Narrowest width holding both; an unsigned width meeting a signed one needs the next
+/// signed width up, and `u64` meeting signed values settles for `i64`.
+fn widen_fixed_int((signed1, bits1): (bool, u8), (signed2, bits2): (bool, u8)) -> JsonType {
+    let (signed, bits) = match (signed1, signed2) {
+        (false, false) | (true, true) => (signed1, bits1.max(bits2)),
+        (true, false) => (true, bits1.max(bits2.saturating_mul(2)).min(64)),
+        (false, true) => (true, bits2.max(bits1.saturating_mul(2)).min(64)),
+    };
+    JsonType::FixedInt { signed, bits }
 }
 
 #[cfg(test)]
@@ -167,10 +838,10 @@ mod tests {
         
         assert_eq!(schema.name, "Person");
         if let JsonType::Object(fields) = schema.json_type {
-            assert_eq!(fields.len(), 3);
-            assert!(matches!(fields.get("name"), Some(JsonType::String)));
-            assert!(matches!(fields.get("age"), Some(JsonType::Number)));
-            assert!(matches!(fields.get("active"), Some(JsonType::Boolean)));
+            assert_eq!(fields.fields.len(), 3);
+            assert!(matches!(fields.field_type("name"), Some(JsonType::String(StringStats { format: None, .. }))));
+            assert!(matches!(fields.field_type("age"), Some(JsonType::Integer { min: 30, max: 30 })));
+            assert!(matches!(fields.field_type("active"), Some(JsonType::Boolean)));
         } else {
             panic!("Expected object type");
         }
@@ -184,7 +855,7 @@ mod tests {
         assert_eq!(schema.name, "Items");
         if let JsonType::Array(elem_type) = schema.json_type {
             if let JsonType::Object(fields) = *elem_type {
-                assert!(matches!(fields.get("id"), Some(JsonType::Number)));
+                assert!(matches!(fields.field_type("id"), Some(JsonType::Integer { min: 1, max: 2 })));
             } else {
                 panic!("Expected object element type");
             }
@@ -194,16 +865,313 @@ mod tests {
     }
 
     #[test]
-    fn test_to_pascal_case() {
-        assert_eq!(to_pascal_case("first_name"), "FirstName");
-        assert_eq!(to_pascal_case("user-id"), "UserId");
-        assert_eq!(to_pascal_case("API_KEY"), "ApiKey");
+    fn test_merge_number_types() {
+        let int = JsonType::Integer { min: -5, max: 10 };
+        let uint = JsonType::Unsigned { min: u64::MAX, max: u64::MAX };
+        let float = JsonType::Float { min: 0.5, max: 0.5 };
+
+        assert_eq!(
+            merge_types(int.clone(), JsonType::Integer { min: 0, max: 100 }).unwrap(),
+            JsonType::Integer { min: -5, max: 100 }
+        );
+        assert_eq!(
+            merge_types(int.clone(), float).unwrap(),
+            JsonType::Float { min: -5.0, max: 10.0 }
+        );
+        assert_eq!(
+            merge_types(JsonType::Integer { min: 1, max: 2 }, uint.clone()).unwrap(),
+            JsonType::Unsigned { min: 1, max: u64::MAX }
+        );
+        assert!(matches!(merge_types(int, uint).unwrap(), JsonType::Float { .. }));
+    }
+
+    #[test]
+    fn test_merge_fixed_width_integers() {
+        let fixed = |signed, bits| JsonType::FixedInt { signed, bits };
+
+        assert_eq!(merge_types(fixed(false, 8), fixed(false, 32)).unwrap(), fixed(false, 32));
+        assert_eq!(merge_types(fixed(false, 8), fixed(true, 8)).unwrap(), fixed(true, 16));
+        assert_eq!(merge_types(fixed(false, 64), fixed(true, 16)).unwrap(), fixed(true, 64));
+        assert_eq!(merge_types(fixed(false, 8), JsonType::Integer { min: 0, max: 1000 }).unwrap(), fixed(false, 16));
+        assert_eq!(merge_types(JsonType::Integer { min: -1, max: 1 }, fixed(true, 32)).unwrap(), fixed(true, 32));
+        assert_eq!(
+            merge_types(fixed(false, 8), JsonType::Float { min: 0.5, max: 0.5 }).unwrap(),
+            JsonType::Float { min: 0.0, max: 255.0 }
+        );
+    }
+
+    #[test]
+    fn test_analyze_large_unsigned() {
+        let json = r#"{"hash": 18446744073709551615, "ratio": 1.5}"#;
+        let schema = analyze_json(json, "Stats").unwrap();
+
+        if let JsonType::Object(fields) = schema.json_type {
+            assert!(matches!(fields.field_type("hash"), Some(JsonType::Unsigned { .. })));
+            assert!(matches!(fields.field_type("ratio"), Some(JsonType::Float { .. })));
+        } else {
+            panic!("Expected object type");
+        }
+    }
+
+    #[test]
+    fn test_string_format_requires_every_sample() {
+        let json = r#"[
+            {"id": "550e8400-e29b-41d4-a716-446655440000", "at": "2024-01-01T00:00:00Z"},
+            {"id": "6ba7b810-9dad-11d1-80b4-00c04fd430c8", "at": "yesterday"}
+        ]"#;
+        let schema = analyze_json(json, "Events").unwrap();
+
+        if let JsonType::Array(elem_type) = schema.json_type {
+            if let JsonType::Object(fields) = *elem_type {
+                assert!(matches!(fields.field_type("id"), Some(JsonType::String(StringStats { format: Some(StringFormat::Uuid), .. }))));
+                assert!(matches!(fields.field_type("at"), Some(JsonType::String(StringStats { format: None, .. }))));
+            } else {
+                panic!("Expected object element type");
+            }
+        } else {
+            panic!("Expected array type");
+        }
+    }
+
+    #[test]
+    fn test_string_values_are_tracked() {
+        let json = r#"[{"status": "active"}, {"status": "deleted"}, {"status": "active"}]"#;
+        let schema = analyze_json(json, "Accounts").unwrap();
+
+        if let JsonType::Array(elem_type) = schema.json_type {
+            if let JsonType::Object(fields) = *elem_type {
+                let Some(JsonType::String(stats)) = fields.field_type("status") else {
+                    panic!("Expected string field");
+                };
+                assert_eq!(stats.samples, 3);
+                assert_eq!(stats.values, Some(vec!["active".to_string(), "deleted".to_string()]));
+            } else {
+                panic!("Expected object element type");
+            }
+        } else {
+            panic!("Expected array type");
+        }
+    }
+
+    #[test]
+    fn test_mixed_arrays() {
+        let json = r#"{"point": [1.5, 2, "home"], "tags": ["a", 1, true, "b", 2, false, "c", 3, true]}"#;
+        let schema = analyze_json(json, "Place").unwrap();
+        
+        let JsonType::Object(fields) = schema.json_type else { panic!("Expected object type") };
+        assert!(matches!(
+            fields.field_type("point"),
+            Some(JsonType::Tuple(items)) if matches!(
+                items.as_slice(),
+                [JsonType::Float { .. }, JsonType::Integer { .. }, JsonType::String(_)]
+            )
+        ));
+        let Some(JsonType::Array(tag_type)) = fields.field_type("tags") else { panic!("Expected array type") };
+        assert!(matches!(
+            tag_type.as_ref(),
+            JsonType::Union(members) if matches!(
+                members.as_slice(),
+                [JsonType::String(_), JsonType::Integer { .. }, JsonType::Boolean]
+            )
+        ));
+    }
+
+    #[test]
+    fn test_merge_tuples_and_unions() {
+        let tuple = |items: Vec<JsonType>| JsonType::Tuple(items);
+        let string = || JsonType::String(StringStats::default());
+        let int = |n| JsonType::Integer { min: n, max: n };
+        
+        assert_eq!(
+            merge_types(tuple(vec![int(1), string()]), tuple(vec![int(5), string()])).unwrap(),
+            tuple(vec![JsonType::Integer { min: 1, max: 5 }, string()])
+        );
+        assert_eq!(
+            merge_types(tuple(vec![int(1), string()]), tuple(vec![string()])).unwrap(),
+            JsonType::Array(Box::new(JsonType::Union(vec![int(1), string()])))
+        );
+        assert_eq!(
+            merge_types(JsonType::Union(vec![int(1), string()]), JsonType::Boolean).unwrap(),
+            JsonType::Union(vec![int(1), string(), JsonType::Boolean])
+        );
+        assert_eq!(
+            merge_types(JsonType::Boolean, JsonType::Union(vec![int(1), JsonType::Boolean])).unwrap(),
+            JsonType::Union(vec![int(1), JsonType::Boolean])
+        );
+    }
+
+    #[test]
+    fn test_nulls_inside_collections() {
+        let json = r#"{"values": [1, null, 2], "rows": [[1, "a", true], [null, "b", false]], "scores": {"a": 1, "b": null}}"#;
+        let options = AnalysisOptions { map_paths: vec!["scores".to_string()], ..AnalysisOptions::default() };
+        let JsonType::Object(fields) = analyze_json_with_options(json, "Root", &options).unwrap().json_type else { panic!("Expected object type") };
+        let int = |min, max| Box::new(JsonType::Integer { min, max });
+        
+        assert_eq!(fields.field_type("values"), Some(&JsonType::Array(Box::new(JsonType::Nullable(int(1, 2))))));
+        let Some(JsonType::Array(row)) = fields.field_type("rows") else { panic!("Expected array type") };
+        let JsonType::Tuple(slots) = row.as_ref() else { panic!("Expected tuple type, got {:?}", row) };
+        assert_eq!(slots[0], JsonType::Nullable(int(1, 1)));
+        assert_eq!(slots[2], JsonType::Boolean);
+        assert_eq!(fields.field_type("scores"), Some(&JsonType::Map(MapKey::String, Box::new(JsonType::Nullable(int(1, 1))))));
+        
+        // Only-null elements are nullable, while an empty array's `Null` stands for no elements
+        let only_nulls = JsonType::Array(Box::new(merge_element_types([JsonType::Null]).unwrap()));
+        assert_eq!(only_nulls, JsonType::Array(Box::new(JsonType::Nullable(Box::new(JsonType::Null)))));
+        assert_eq!(
+            merge_types(only_nulls, JsonType::Array(int(1, 1))).unwrap(),
+            JsonType::Array(Box::new(JsonType::Nullable(int(1, 1))))
+        );
+        assert_eq!(
+            merge_types(JsonType::Array(Box::new(JsonType::Null)), JsonType::Array(int(1, 1))).unwrap(),
+            JsonType::Array(int(1, 1))
+        );
+        assert_eq!(
+            merge_types(JsonType::Array(Box::new(JsonType::Null)), JsonType::Tuple(vec![*int(1, 1), JsonType::Boolean])).unwrap(),
+            JsonType::Array(Box::new(JsonType::Union(vec![*int(1, 1), JsonType::Boolean])))
+        );
+    }
+
+    #[test]
+    fn test_detect_internally_tagged_objects() {
+        let json = r#"[{"type": "click", "x": 1, "y": 2}, {"type": "key", "code": "a"}, {"type": "click", "x": 3, "y": 4}]"#;
+        let schema = analyze_json(json, "Events").unwrap();
+        
+        let JsonType::Array(elem_type) = schema.json_type else { panic!("Expected array type") };
+        let JsonType::Tagged(union) = *elem_type else { panic!("Expected tagged type") };
+        assert_eq!(union.tag, "type");
+        assert_eq!(union.content, None);
+        let tags: Vec<&str> = union.variants.keys().map(String::as_str).collect();
+        assert_eq!(tags, ["click", "key"]);
+        let Some(JsonType::Object(click)) = union.variants.get("click") else { panic!("Expected object payload") };
+        assert!(matches!(click.field_type("x"), Some(JsonType::Integer { min: 1, max: 3 })));
+        assert!(!click.fields.contains_key("type"));
+    }
+
+    #[test]
+    fn test_detect_adjacently_tagged_objects() {
+        let json = r#"{"log": [{"kind": "created", "data": {"id": 1}}, {"kind": "renamed", "data": {"from": "a", "to": "b"}}]}"#;
+        let schema = analyze_json(json, "Audit").unwrap();
+        
+        let JsonType::Object(fields) = schema.json_type else { panic!("Expected object type") };
+        let Some(JsonType::Array(elem_type)) = fields.field_type("log") else { panic!("Expected array type") };
+        let JsonType::Tagged(union) = elem_type.as_ref() else { panic!("Expected tagged type") };
+        assert_eq!(union.tag, "kind");
+        assert_eq!(union.content.as_deref(), Some("data"));
+        assert!(matches!(union.variants.get("renamed"), Some(JsonType::Object(payload)) if payload.fields.len() == 2));
+    }
+
+    #[test]
+    fn test_tag_detection_needs_distinct_shapes() {
+        // Same shape for every value: a plain string field, not a discriminator
+        let same_shape = r#"[{"type": "a", "n": 1}, {"type": "b", "n": 2}]"#;
+        let JsonType::Array(elem_type) = analyze_json(same_shape, "Items").unwrap().json_type else { panic!() };
+        assert!(matches!(*elem_type, JsonType::Object(_)));
+        
+        // Unique values of an unconventional key look like ids
+        let id_like = r#"[{"name": "a", "x": 1}, {"name": "b", "y": 2}]"#;
+        let JsonType::Array(elem_type) = analyze_json(id_like, "Items").unwrap().json_type else { panic!() };
+        assert!(matches!(*elem_type, JsonType::Object(_)));
+    }
+
+    #[test]
+    fn test_merge_single_variant_into_tagged_union() {
+        let tagged = analyze_json(r#"[{"op": "add", "value": 1}, {"op": "remove"}]"#, "Ops").unwrap().json_type;
+        let single = analyze_json(r#"[{"op": "move", "from": "a"}]"#, "Ops").unwrap().json_type;
+        
+        let JsonType::Array(elem_type) = merge_types(tagged, single).unwrap() else { panic!("Expected array type") };
+        let JsonType::Tagged(union) = *elem_type else { panic!("Expected tagged type") };
+        let tags: Vec<&str> = union.variants.keys().map(String::as_str).collect();
+        assert_eq!(tags, ["add", "remove", "move"]);
+    }
+
+    #[test]
+    fn test_field_presence_and_null_counts() {
+        let json = r#"[{"id": 1, "note": null}, {"id": 2, "note": "x", "tag": "a"}, {"id": 3}]"#;
+        let schema = analyze_json(json, "Items").unwrap();
+        
+        let JsonType::Array(elem_type) = schema.json_type else { panic!("Expected array type") };
+        let JsonType::Object(object) = *elem_type else { panic!("Expected object type") };
+        assert_eq!(object.samples, 3);
+        
+        let note = &object.fields["note"];
+        assert_eq!((note.present, note.nulls), (2, 1));
+        assert!(matches!(note.json_type, JsonType::String(_)));
+        assert!(object.is_optional(note));
+        assert_eq!(object.fields["tag"].present, 1);
+        assert!(!object.is_optional(&object.fields["id"]));
+    }
+
+    #[test]
+    fn test_fold_self_similar() {
+        let json = r#"{"id": 1, "replies": [{"id": 2, "replies": [{"id": 3, "replies": [], "text": "hi"}]}], "author": {"name": "a"}}"#;
+        let JsonType::Object(object) = analyze_json(json, "Comment").unwrap().json_type else { panic!("Expected object type") };
+        
+        let folded = fold_self_similar(object, "Comment").unwrap();
+        assert_eq!(folded.samples, 3);
+        assert_eq!(
+            folded.field_type("replies"),
+            Some(&JsonType::Array(Box::new(JsonType::Recursive("Comment".to_string()))))
+        );
+        assert_eq!(folded.fields["text"].present, 1);
+        assert!(matches!(folded.field_type("author"), Some(JsonType::Object(_))));
+    }
+
+    #[test]
+    fn test_field_order_is_first_seen() {
+        let json = r#"[{"zeta": 1, "alpha": 2, "mid": 3}, {"alpha": 4, "extra": 5, "zeta": 6}]"#;
+        let schema = analyze_json(json, "Items").unwrap();
+        
+        let JsonType::Array(elem_type) = schema.json_type else { panic!("Expected array type") };
+        let JsonType::Object(fields) = *elem_type else { panic!("Expected object type") };
+        let keys: Vec<&str> = fields.fields.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["zeta", "alpha", "mid", "extra"]);
+    }
+
+    #[test]
+    fn test_detect_map_like_objects() {
+        let json = r#"{"users": {
+            "u101": {"name": "Ann", "age": 31},
+            "u102": {"name": "Bob"},
+            "u103": {"name": "Cid", "age": 40},
+            "u104": {"name": "Dee", "age": 22}
+        }, "owner": {"name": "Eve", "age": 50, "email": "eve@example.com", "admin": true}}"#;
+        let schema = analyze_json(json, "Root").unwrap();
+
+        let JsonType::Object(fields) = schema.json_type else {
+            panic!("Expected object type");
+        };
+        let Some(JsonType::Map(MapKey::String, value_type)) = fields.field_type("users") else {
+            panic!("Expected users to be a map");
+        };
+        let JsonType::Object(value_fields) = value_type.as_ref() else {
+            panic!("Expected object map values");
+        };
+        assert!(value_fields.fields.contains_key("age"));
+        assert!(matches!(fields.field_type("owner"), Some(JsonType::Object(_))));
+    }
+
+    #[test]
+    fn test_map_path_override() {
+        let json = r#"[{"teams": [{"members": {"alice": 1, "bob": 2}}]}]"#;
+        let options = AnalysisOptions {
+            map_paths: vec!["$[].teams[].members".to_string()],
+            ..AnalysisOptions::default()
+        };
+        let schema = analyze_json_with_options(json, "Orgs", &options).unwrap();
+
+        let JsonType::Array(org) = schema.json_type else { panic!("Expected array type") };
+        let JsonType::Object(org_fields) = *org else { panic!("Expected object type") };
+        let Some(JsonType::Array(team)) = org_fields.field_type("teams") else { panic!("Expected teams array") };
+        let JsonType::Object(team_fields) = team.as_ref() else { panic!("Expected object type") };
+        assert!(matches!(team_fields.field_type("members"), Some(JsonType::Map(..))));
     }
 
     #[test]
-    fn test_to_snake_case() {
-        assert_eq!(to_snake_case("FirstName"), "first_name");
-        assert_eq!(to_snake_case("UserId"), "user_id");
-        assert_eq!(to_snake_case("APIKey"), "apikey");
+    fn test_key_patterns() {
+        assert_eq!(key_pattern("12345"), Some(KeyPattern::Numeric));
+        assert_eq!(key_pattern("2024-01-31"), Some(KeyPattern::Date));
+        assert_eq!(key_pattern("9f86d081884c7d65"), Some(KeyPattern::Hex));
+        assert_eq!(key_pattern("user_42"), Some(KeyPattern::Prefixed("user_".to_string())));
+        assert_eq!(key_pattern("name"), None);
     }
 }
\ No newline at end of file
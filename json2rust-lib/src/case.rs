@@ -0,0 +1,182 @@
+use crate::types::*;
+
+impl RenameRule {
+    /// Candidates for `rename_all`, in order of preference when several fit equally well.
+    pub const ALL: [RenameRule; 5] = [
+        RenameRule::CamelCase,
+        RenameRule::PascalCase,
+        RenameRule::KebabCase,
+        RenameRule::ScreamingSnakeCase,
+        RenameRule::ScreamingKebabCase,
+    ];
+
+    pub fn serde_name(self) -> &'static str {
+        match self {
+            RenameRule::PascalCase => "PascalCase",
+            RenameRule::CamelCase => "camelCase",
+            RenameRule::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+            RenameRule::KebabCase => "kebab-case",
+            RenameRule::ScreamingKebabCase => "SCREAMING-KEBAB-CASE",
+        }
+    }
+
+    /// Renames a snake_case field exactly the way serde's `rename_all` does.
+    pub fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply_to_field(field);
+                let mut chars = pascal.chars();
+                chars.next().map(|first| first.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+            }
+            RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            RenameRule::KebabCase => field.replace('_', "-"),
+            RenameRule::ScreamingKebabCase => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// Splits an identifier or JSON key into words. Any character other than an ASCII letter or
+/// digit separates words; inside a run a word starts at a lower-to-upper change (`userId`)
+/// or at the last capital of an acronym that runs into a lowercase word (`APIKey`). Digits
+/// stay on the word they follow and a plural `s` stays on its acronym (`userIDs`).
+pub fn split_words(s: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let char_at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if let Some(begin) = start.take() {
+                words.push(&s[begin..offset]);
+            }
+            continue;
+        }
+        match start {
+            Some(begin) if c.is_ascii_uppercase() && starts_word(chars[i - 1].1, char_at(i + 1), char_at(i + 2)) => {
+                words.push(&s[begin..offset]);
+                start = Some(offset);
+            }
+            Some(_) => {}
+            None => start = Some(offset),
+        }
+    }
+    if let Some(begin) = start {
+        words.push(&s[begin..]);
+    }
+    words
+}
+
+fn starts_word(previous: char, next: Option<char>, after_next: Option<char>) -> bool {
+    if previous.is_ascii_lowercase() || previous.is_ascii_digit() {
+        return true;
+    }
+    let plural_acronym = next == Some('s') && !after_next.is_some_and(|c| c.is_ascii_lowercase());
+    previous.is_ascii_uppercase() && next.is_some_and(|c| c.is_ascii_lowercase()) && !plural_acronym
+}
+
+pub fn to_pascal_case(s: &str) -> String {
+    split_words(s)
+        .into_iter()
+        .map(|word| {
+            let lower = word.to_ascii_lowercase();
+            lower[..1].to_ascii_uppercase() + &lower[1..]
+        })
+        .collect()
+}
+
+pub fn to_snake_case(s: &str) -> String {
+    split_words(s)
+        .into_iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Picks the `rename_all` rule that leaves the fewest fields needing their own rename, as
+/// long as the rule attribute plus those renames is fewer attributes than renaming every
+/// field separately. `fields` pairs each unescaped field name with its name in the JSON.
+pub fn dominant_rename_rule(fields: &[(&str, &str)]) -> Option<RenameRule> {
+    let per_field_renames = fields.iter().filter(|(name, wire)| name != wire).count();
+    RenameRule::ALL
+        .into_iter()
+        .map(|rule| {
+            let renames = fields.iter().filter(|(name, wire)| rule.apply_to_field(name) != *wire).count();
+            (rule, renames)
+        })
+        .filter(|&(_, renames)| renames + 1 < per_field_renames)
+        .min_by_key(|&(_, renames)| renames)
+        .map(|(rule, _)| rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("userId"), ["user", "Id"]);
+        assert_eq!(split_words("APIKey"), ["API", "Key"]);
+        assert_eq!(split_words("userIDs"), ["user", "IDs"]);
+        assert_eq!(split_words("HTTPServerError"), ["HTTP", "Server", "Error"]);
+        assert_eq!(split_words("SCREAMING_CASE"), ["SCREAMING", "CASE"]);
+        assert_eq!(split_words("kebab-case key"), ["kebab", "case", "key"]);
+        assert_eq!(split_words("base64Data"), ["base64", "Data"]);
+        assert_eq!(split_words("__typename"), ["typename"]);
+    }
+
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("first_name"), "FirstName");
+        assert_eq!(to_pascal_case("user-id"), "UserId");
+        assert_eq!(to_pascal_case("API_KEY"), "ApiKey");
+        assert_eq!(to_pascal_case("userID"), "UserId");
+        assert_eq!(to_pascal_case("v2Endpoint"), "V2Endpoint");
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("FirstName"), "first_name");
+        assert_eq!(to_snake_case("UserId"), "user_id");
+        assert_eq!(to_snake_case("APIKey"), "api_key");
+        assert_eq!(to_snake_case("userIDs"), "user_ids");
+        assert_eq!(to_snake_case("MAX_RETRY-count"), "max_retry_count");
+    }
+
+    #[test]
+    fn test_apply_rename_rule() {
+        assert_eq!(RenameRule::CamelCase.apply_to_field("user_id"), "userId");
+        assert_eq!(RenameRule::PascalCase.apply_to_field("user_id"), "UserId");
+        assert_eq!(RenameRule::KebabCase.apply_to_field("user_id"), "user-id");
+        assert_eq!(RenameRule::ScreamingKebabCase.apply_to_field("user_id"), "USER-ID");
+        assert_eq!(RenameRule::CamelCase.apply_to_field("_1st"), "1st");
+    }
+
+    #[test]
+    fn test_dominant_rename_rule() {
+        let camel = [("user_id", "userId"), ("created_at", "createdAt"), ("name", "name")];
+        assert_eq!(dominant_rename_rule(&camel), Some(RenameRule::CamelCase));
+
+        let kebab = [("content_type", "content-type"), ("max_age", "max-age"), ("user_id", "userId")];
+        assert_eq!(dominant_rename_rule(&kebab), Some(RenameRule::KebabCase));
+
+        // One rename is as cheap as one rename_all
+        assert_eq!(dominant_rename_rule(&[("user_id", "userId"), ("name", "name")]), None);
+        assert_eq!(dominant_rename_rule(&[("user_id", "user_id")]), None);
+    }
+}
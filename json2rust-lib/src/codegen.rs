@@ -1,9 +1,20 @@
 use crate::types::*;
-use crate::analyzer::{to_pascal_case, to_snake_case};
-use crate::parser::calculate_struct_similarity;
+use crate::analyzer::fold_self_similar;
+use crate::case::{dominant_rename_rule, to_pascal_case};
+use crate::dedupe::deduplicate_structs;
+use crate::emitter::Emitter;
+use crate::formats::{serde_with_for_type, string_format_type_name};
+use crate::identifiers::{sanitize_field_names, sanitize_type_name, unraw};
+use crate::inflection::element_type_name;
+use crate::parser::{calculate_struct_similarity, syn_type_ref, type_ref_from_rust, widen_numeric_type};
+use indexmap::IndexMap;
+use proc_macro2::TokenStream;
+use quote::quote;
 use std::collections::HashMap;
 
 const SIMILARITY_THRESHOLD: f64 = 0.6;
+const MIN_SAMPLES_PER_ENUM_VARIANT: usize = 2;
+const DOUBLE_OPTION_HELPER: &str = "serde_with::rust::double_option";
 
 pub fn generate_rust_structs(
     schema: &JsonSchema,
@@ -32,48 +43,130 @@ pub fn generate_rust_types_with_strategy(
     schema: &JsonSchema,
     existing_structs: &[ExistingStruct],
     merge_strategy: &MergeStrategy,
+) -> Result<GeneratedTypes, Json2RustError> {
+    generate_rust_types_with_config(schema, existing_structs, merge_strategy, &GenerationConfig::default())
+}
+
+pub fn generate_rust_types_with_config(
+    schema: &JsonSchema,
+    existing_structs: &[ExistingStruct],
+    merge_strategy: &MergeStrategy,
+    config: &GenerationConfig,
+) -> Result<GeneratedTypes, Json2RustError> {
+    generate_rust_types_for_schemas(std::slice::from_ref(schema), existing_structs, merge_strategy, config)
+}
+
+/// `generate_rust_types_with_config` for several root types generated together, like the
+/// schemas of an API description. Types the roots share are generated once, and no root is
+/// merged away by deduplication.
+pub fn generate_rust_types_for_schemas(
+    schemas: &[JsonSchema],
+    existing_structs: &[ExistingStruct],
+    merge_strategy: &MergeStrategy,
+    config: &GenerationConfig,
 ) -> Result<GeneratedTypes, Json2RustError> {
     let mut structs = Vec::new();
     let mut enums = Vec::new();
     let mut generated_names = HashMap::new();
+    for schema in schemas {
+        generate_root_types(schema, existing_structs, &mut structs, &mut enums, &mut generated_names, merge_strategy, config)?;
+    }
     
+    let mut types = GeneratedTypes { structs, enums };
+    if config.dedupe_structs {
+        let protected: Vec<String> = schemas
+            .iter()
+            .map(|schema| schema.name.clone())
+            .chain(existing_structs.iter().map(|s| s.name.clone()))
+            .collect();
+        deduplicate_structs(&mut types, config.struct_naming, &protected);
+    }
+    Ok(types)
+}
+
+fn generate_root_types(
+    schema: &JsonSchema,
+    existing_structs: &[ExistingStruct],
+    structs: &mut Vec<RustStruct>,
+    enums: &mut Vec<RustEnum>,
+    generated_names: &mut HashMap<String, usize>,
+    merge_strategy: &MergeStrategy,
+    config: &GenerationConfig,
+) -> Result<(), Json2RustError> {
     match &schema.json_type {
         JsonType::Array(element_type) => {
             // For arrays, try to find existing struct with singular name first
-            let element_name = get_singular_name(&schema.name);
-            let element_type_name = generate_struct_from_schema(
+            let element_name = element_type_name(&schema.name, &config.singular_overrides);
+            let element_type = generate_struct_from_schema(
                 &JsonSchema {
                     name: element_name,
                     json_type: (**element_type).clone(),
                     optional: false,
                 },
                 existing_structs,
-                &mut structs,
-                &mut enums,
-                &mut generated_names,
+                structs,
+                enums,
+                generated_names,
                 merge_strategy,
+                config,
             )?;
             
             let root_struct = RustStruct {
                 name: schema.name.clone(),
                 fields: vec![RustField {
                     name: "items".to_string(),
-                    type_name: format!("Vec<{}>", element_type_name),
+                    type_name: format!("Vec<{}>", element_type.rust),
+                    ty: TypeRef::List(Box::new(element_type.ty)),
                     is_optional: false,
                     serde_rename: None,
                     serde_flatten: false,
+                    serde_with: None,
+                    serde_default: false,
                 }],
                 derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
                 is_optional: false,
+                docs: Vec::new(),
             };
             structs.push(root_struct);
         }
+        JsonType::Map(key, value_type) => {
+            // Root-level maps get a wrapper struct that flattens the entries
+            let value_type = generate_struct_from_schema(
+                &JsonSchema {
+                    name: format!("{}Value", schema.name),
+                    json_type: (**value_type).clone(),
+                    optional: false,
+                },
+                existing_structs,
+                structs,
+                enums,
+                generated_names,
+                merge_strategy,
+                config,
+            )?;
+            
+            structs.push(RustStruct {
+                name: schema.name.clone(),
+                fields: vec![RustField {
+                    name: "entries".to_string(),
+                    type_name: format!("{}<{}, {}>", map_type_path(config.map_type), map_key_type_name(*key), value_type.rust),
+                    ty: TypeRef::Map(*key, Box::new(value_type.ty)),
+                    is_optional: false,
+                    serde_rename: None,
+                    serde_flatten: true,
+                    serde_with: None,
+                    serde_default: false,
+                }],
+                derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
+                is_optional: false,
+                docs: Vec::new(),
+            });
+        }
         _ => {
-            generate_struct_from_schema(schema, existing_structs, &mut structs, &mut enums, &mut generated_names, merge_strategy)?;
+            generate_struct_from_schema(schema, existing_structs, structs, enums, generated_names, merge_strategy, config)?;
         }
     }
-    
-    Ok(GeneratedTypes { structs, enums })
+    Ok(())
 }
 
 fn generate_struct_from_schema(
@@ -83,17 +176,20 @@ fn generate_struct_from_schema(
     enums: &mut Vec<RustEnum>,
     generated_names: &mut HashMap<String, usize>,
     merge_strategy: &MergeStrategy,
-) -> Result<String, Json2RustError> {
+    config: &GenerationConfig,
+) -> Result<GeneratedType, Json2RustError> {
     match &schema.json_type {
-        JsonType::Object(fields) => {
+        JsonType::Object(object) => {
             let struct_name = ensure_unique_name(&schema.name, generated_names);
+            let object = fold_self_similar(object.clone(), &struct_name)?;
             let rust_fields = generate_fields_from_object(
-                fields,
+                &object,
                 existing_structs,
                 structs,
                 enums,
                 generated_names,
                 merge_strategy,
+                config,
             )?;
             
             let rust_struct = if let Some(existing) = find_compatible_struct(&rust_fields, existing_structs) {
@@ -104,49 +200,291 @@ fn generate_struct_from_schema(
                     fields: rust_fields,
                     derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
                     is_optional: schema.optional,
+                    docs: Vec::new(),
                 }
             };
             
             structs.push(rust_struct);
-            Ok(struct_name)
+            Ok(GeneratedType::named(struct_name))
         }
         JsonType::Array(element_type) => {
-            let element_type_name = generate_struct_from_schema(
-                &JsonSchema {
-                    name: format!("{}Item", schema.name),
-                    json_type: (**element_type).clone(),
-                    optional: false,
-                },
+            let element_type = match element_type.as_ref() {
+                // The Vec already provides the indirection a recursive type needs
+                JsonType::Recursive(name) => GeneratedType::named(name.clone()),
+                _ => generate_struct_from_schema(
+                    &JsonSchema {
+                        name: element_type_name(&schema.name, &config.singular_overrides),
+                        json_type: (**element_type).clone(),
+                        optional: false,
+                    },
+                    existing_structs,
+                    structs,
+                    enums,
+                    generated_names,
+                    merge_strategy,
+                    config,
+                )?,
+            };
+            Ok(GeneratedType { rust: format!("Vec<{}>", element_type.rust), ty: TypeRef::List(Box::new(element_type.ty)) })
+        }
+        JsonType::Recursive(name) => Ok(GeneratedType { rust: format!("Box<{}>", name), ty: TypeRef::Named(name.clone()) }),
+        JsonType::Named(name, inner) => {
+            // Every reference to a declared type shares the one generated for the first
+            if structs.iter().any(|s| s.name == *name) || enums.iter().any(|e| e.name == *name) {
+                return Ok(GeneratedType::named(name.clone()));
+            }
+            generate_struct_from_schema(
+                &JsonSchema { name: name.clone(), json_type: (**inner).clone(), optional: false },
+                existing_structs,
+                structs,
+                enums,
+                generated_names,
+                merge_strategy,
+                config,
+            )
+        }
+        JsonType::String(stats) => {
+            if let Some(values) = string_enum_values(stats, config) {
+                // Fields holding the same set of values share one enum, so the structs
+                // around them stay identical for deduplication
+                let candidate = create_string_enum(&schema.name, values, config);
+                if let Some(existing) = enums.iter().find(|e| same_variant_set(e, &candidate)) {
+                    return Ok(GeneratedType::named(existing.name.clone()));
+                }
+                let enum_name = ensure_unique_name(&schema.name, generated_names);
+                enums.push(RustEnum { name: enum_name.clone(), ..candidate });
+                Ok(GeneratedType::named(enum_name))
+            } else {
+                Ok(string_type(stats, config))
+            }
+        }
+        JsonType::Integer { .. } | JsonType::Unsigned { .. } | JsonType::Float { .. } | JsonType::FixedInt { .. } => {
+            Ok(GeneratedType { rust: number_type_name(&schema.json_type).to_string(), ty: number_type_ref(&schema.json_type) })
+        }
+        JsonType::Bytes => Ok(GeneratedType { rust: "Vec<u8>".to_string(), ty: TypeRef::Bytes }),
+        JsonType::Tuple(items) => {
+            let mut item_types = Vec::new();
+            for (index, item_type) in items.iter().enumerate() {
+                item_types.push(generate_struct_from_schema(
+                    &JsonSchema {
+                        name: format!("{}Item{}", schema.name, index),
+                        json_type: item_type.clone(),
+                        optional: false,
+                    },
+                    existing_structs,
+                    structs,
+                    enums,
+                    generated_names,
+                    merge_strategy,
+                    config,
+                )?);
+            }
+            let item_type_names: Vec<String> = item_types.iter().map(|item| item.rust.clone()).collect();
+            Ok(GeneratedType {
+                rust: format!("({})", item_type_names.join(", ")),
+                ty: TypeRef::Tuple(item_types.into_iter().map(|item| item.ty).collect()),
+            })
+        }
+        JsonType::Union(members) => {
+            let enum_name = ensure_unique_name(&schema.name, generated_names);
+            // Untagged enums try variants in order and structs also deserialize from
+            // sequences, so arrays and tuples have to be tried first
+            let mut members: Vec<&JsonType> = members.iter().collect();
+            members.sort_by_key(|member| !matches!(member, JsonType::Array(_) | JsonType::Tuple(_)));
+            let mut variants = Vec::new();
+            for member in members {
+                let variant_name = union_variant_name(member);
+                let payload = generate_struct_from_schema(
+                    &JsonSchema {
+                        name: format!("{}{}", enum_name, variant_name),
+                        json_type: (*member).clone(),
+                        optional: false,
+                    },
+                    existing_structs,
+                    structs,
+                    enums,
+                    generated_names,
+                    merge_strategy,
+                    config,
+                )?;
+                variants.push(RustEnumVariant {
+                    name: variant_name.to_string(),
+                    fields: Vec::new(),
+                    serde_rename: None,
+                    serde_other: false,
+                    newtype: Some(payload.rust),
+                    newtype_ty: Some(payload.ty),
+                });
+            }
+            
+            enums.push(RustEnum {
+                name: enum_name.clone(),
+                variants,
+                derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
+                representation: EnumRepresentation::Untagged,
+                docs: Vec::new(),
+            });
+            Ok(GeneratedType::named(enum_name))
+        }
+        JsonType::Map(key, value_type) => {
+            let value_type = match value_type.as_ref() {
+                JsonType::Recursive(name) => GeneratedType::named(name.clone()),
+                _ => generate_struct_from_schema(
+                    &JsonSchema {
+                        name: format!("{}Value", schema.name),
+                        json_type: (**value_type).clone(),
+                        optional: false,
+                    },
+                    existing_structs,
+                    structs,
+                    enums,
+                    generated_names,
+                    merge_strategy,
+                    config,
+                )?,
+            };
+            Ok(GeneratedType {
+                rust: format!("{}<{}, {}>", map_type_path(config.map_type), map_key_type_name(*key), value_type.rust),
+                ty: TypeRef::Map(*key, Box::new(value_type.ty)),
+            })
+        }
+        JsonType::Tagged(union) => {
+            let enum_name = ensure_unique_name(&schema.name, generated_names);
+            let mut variant_names = HashMap::new();
+            let mut variants = Vec::new();
+            for (tag_value, payload) in &union.variants {
+                let name = ensure_unique_name(&sanitize_type_name(tag_value), &mut variant_names);
+                let (fields, newtype) = match payload {
+                    JsonType::Object(fields) => (
+                        generate_fields_from_object(fields, existing_structs, structs, enums, generated_names, merge_strategy, config)?,
+                        None,
+                    ),
+                    payload => {
+                        let payload_type = generate_struct_from_schema(
+                            &JsonSchema {
+                                name: format!("{}{}", enum_name, name),
+                                json_type: payload.clone(),
+                                optional: false,
+                            },
+                            existing_structs,
+                            structs,
+                            enums,
+                            generated_names,
+                            merge_strategy,
+                            config,
+                        )?;
+                        (Vec::new(), Some(payload_type))
+                    }
+                };
+                let (newtype, newtype_ty) = newtype.map(|payload| (payload.rust, payload.ty)).unzip();
+                variants.push(RustEnumVariant {
+                    serde_rename: (name != *tag_value).then(|| tag_value.clone()),
+                    name,
+                    fields,
+                    serde_other: false,
+                    newtype,
+                    newtype_ty,
+                });
+            }
+            
+            let representation = match &union.content {
+                Some(content) => EnumRepresentation::Adjacent { tag: union.tag.clone(), content: content.clone() },
+                None => EnumRepresentation::Internal { tag: union.tag.clone() },
+            };
+            enums.push(RustEnum {
+                name: enum_name.clone(),
+                variants,
+                derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
+                representation,
+                docs: Vec::new(),
+            });
+            Ok(GeneratedType::named(enum_name))
+        }
+        // Only nulls seen: `Null` already generates an `Option`
+        JsonType::Nullable(inner) if **inner == JsonType::Null => Ok(GeneratedType::any_or_null()),
+        JsonType::Nullable(inner) => {
+            let inner_type = generate_struct_from_schema(
+                &JsonSchema { name: schema.name.clone(), json_type: (**inner).clone(), optional: false },
                 existing_structs,
                 structs,
                 enums,
                 generated_names,
                 merge_strategy,
+                config,
             )?;
-            Ok(format!("Vec<{}>", element_type_name))
+            Ok(GeneratedType { rust: format!("Option<{}>", inner_type.rust), ty: TypeRef::Optional(Box::new(inner_type.ty)) })
         }
-        JsonType::String => Ok("String".to_string()),
-        JsonType::Number => Ok("f64".to_string()),
-        JsonType::Boolean => Ok("bool".to_string()),
-        JsonType::Null => Ok("Option<serde_json::Value>".to_string()),
+        JsonType::Boolean => Ok(GeneratedType { rust: "bool".to_string(), ty: TypeRef::Boolean }),
+        JsonType::Null => Ok(GeneratedType::any_or_null()),
+    }
+}
+
+/// A type generated for a schema, spelled in Rust and as the `TypeRef` other emitters read.
+struct GeneratedType {
+    rust: String,
+    ty: TypeRef,
+}
+
+impl GeneratedType {
+    fn named(name: String) -> Self {
+        Self { ty: TypeRef::Named(name.clone()), rust: name }
+    }
+
+    /// What a value that was only ever null becomes.
+    fn any_or_null() -> Self {
+        Self { rust: "Option<serde_json::Value>".to_string(), ty: TypeRef::Optional(Box::new(TypeRef::Any)) }
+    }
+}
+
+fn union_variant_name(json_type: &JsonType) -> &str {
+    match json_type {
+        JsonType::String(_) => "String",
+        JsonType::Integer { .. } | JsonType::Unsigned { .. } | JsonType::FixedInt { .. } => "Integer",
+        JsonType::Float { .. } => "Float",
+        JsonType::Boolean => "Bool",
+        JsonType::Bytes => "Bytes",
+        JsonType::Array(_) => "Array",
+        JsonType::Tuple(_) => "Tuple",
+        JsonType::Object(_) | JsonType::Tagged(_) => "Object",
+        JsonType::Map(..) => "Map",
+        JsonType::Recursive(name) | JsonType::Named(name, _) => name,
+        JsonType::Nullable(inner) => union_variant_name(inner),
+        JsonType::Union(_) | JsonType::Null => "Value",
+    }
+}
+
+fn map_type_path(map_type: MapType) -> &'static str {
+    match map_type {
+        MapType::HashMap => "std::collections::HashMap",
+        MapType::BTreeMap => "std::collections::BTreeMap",
+    }
+}
+
+fn map_key_type_name(key: MapKey) -> &'static str {
+    match key {
+        MapKey::String => "String",
+        MapKey::Integer => "i64",
+        MapKey::Boolean => "bool",
     }
 }
 
 fn generate_fields_from_object(
-    fields: &HashMap<String, JsonType>,
+    object: &ObjectStats,
     existing_structs: &[ExistingStruct],
     structs: &mut Vec<RustStruct>,
     enums: &mut Vec<RustEnum>,
     generated_names: &mut HashMap<String, usize>,
     merge_strategy: &MergeStrategy,
+    config: &GenerationConfig,
 ) -> Result<Vec<RustField>, Json2RustError> {
     let mut rust_fields = Vec::new();
+    let rust_names = sanitize_field_names(object.fields.keys(), config.keyword_style);
     
-    for (field_name, field_type) in fields {
-        let field_type_name = generate_struct_from_schema(
+    for ((field_name, field), rust_name) in object.fields.iter().zip(rust_names) {
+        let field_type = generate_struct_from_schema(
             &JsonSchema {
-                name: to_pascal_case(field_name),
-                json_type: field_type.clone(),
+                name: sanitize_type_name(field_name),
+                json_type: field.json_type.clone(),
                 optional: false,
             },
             existing_structs,
@@ -154,26 +492,155 @@ fn generate_fields_from_object(
             enums,
             generated_names,
             merge_strategy,
+            config,
         )?;
         
-        let rust_field = RustField {
-            name: to_snake_case(field_name),
-            type_name: field_type_name,
-            is_optional: matches!(field_type, JsonType::Null),
-            serde_rename: if to_snake_case(field_name) != *field_name {
-                Some(field_name.clone())
-            } else {
-                None
-            },
-            serde_flatten: false,
-        };
-        
-        rust_fields.push(rust_field);
+        let serde_with = serde_with_for_type(&field.json_type, config);
+        rust_fields.push(object_field(field_name, rust_name, field_type, serde_with, field, object, config));
     }
     
     Ok(rust_fields)
 }
 
+/// Builds the field for one key of an analyzed object. Keys that some samples lacked or
+/// held null for become optional, in the shape the config asks for.
+fn object_field(
+    field_name: &str,
+    rust_name: String,
+    field_type: GeneratedType,
+    serde_with: Option<String>,
+    field: &FieldStats,
+    object: &ObjectStats,
+    config: &GenerationConfig,
+) -> RustField {
+    let is_optional = object.is_optional(field);
+    let mut rust_field = RustField {
+        serde_rename: (unraw(&rust_name) != field_name).then(|| field_name.to_string()),
+        name: rust_name,
+        type_name: field_type.rust,
+        ty: field_type.ty,
+        is_optional,
+        serde_flatten: false,
+        serde_with,
+        serde_default: config.serde_default && field.present < object.samples,
+    };
+    
+    // Outer None means absent, Some(None) means an explicit null
+    if config.double_option && is_optional && rust_field.serde_with.is_none() {
+        rust_field.make_optional();
+        rust_field.type_name = format!("Option<{}>", rust_field.type_name);
+        rust_field.ty = TypeRef::Optional(Box::new(rust_field.ty));
+        rust_field.serde_with = Some(DOUBLE_OPTION_HELPER.to_string());
+    }
+    
+    rust_field
+}
+
+/// Returns the distinct values of a string field when they are few enough, relative to
+/// the number of samples, to be treated as a closed set.
+fn string_enum_values<'a>(stats: &'a StringStats, config: &GenerationConfig) -> Option<&'a [String]> {
+    if stats.declared {
+        // A schema's `enum` is a closed set however few samples there are
+        return stats.values.as_deref().filter(|values| !values.is_empty());
+    }
+    if !config.string_enums || stats.format.is_some() {
+        return None;
+    }
+    let values = stats.values.as_deref()?;
+    let is_closed_set = values.len() >= 2
+        && values.len() <= config.enum_max_variants
+        && stats.samples >= values.len() * MIN_SAMPLES_PER_ENUM_VARIANT;
+    is_closed_set.then_some(values)
+}
+
+fn create_string_enum(enum_name: &str, values: &[String], config: &GenerationConfig) -> RustEnum {
+    let mut variant_names = HashMap::new();
+    let mut variants: Vec<RustEnumVariant> = values
+        .iter()
+        .map(|value| {
+            let name = ensure_unique_name(&sanitize_type_name(value), &mut variant_names);
+            RustEnumVariant {
+                serde_rename: (name != *value).then(|| value.clone()),
+                name,
+                fields: Vec::new(),
+                serde_other: false,
+                newtype: None,
+                newtype_ty: None,
+            }
+        })
+        .collect();
+    
+    if config.enum_unknown_variant {
+        variants.push(RustEnumVariant {
+            name: ensure_unique_name("Unknown", &mut variant_names),
+            fields: Vec::new(),
+            serde_rename: None,
+            serde_other: true,
+            newtype: None,
+            newtype_ty: None,
+        });
+    }
+    
+    RustEnum {
+        name: enum_name.to_string(),
+        variants,
+        derives: vec![
+            "Debug".to_string(),
+            "Clone".to_string(),
+            "PartialEq".to_string(),
+            "Eq".to_string(),
+            "Serialize".to_string(),
+            "Deserialize".to_string(),
+        ],
+        representation: EnumRepresentation::External,
+        docs: Vec::new(),
+    }
+}
+
+/// True when two string enums accept the same values, whatever order they were seen in.
+fn same_variant_set(a: &RustEnum, b: &RustEnum) -> bool {
+    fn sorted(rust_enum: &RustEnum) -> Vec<&RustEnumVariant> {
+        let mut variants: Vec<&RustEnumVariant> = rust_enum.variants.iter().collect();
+        variants.sort_by(|x, y| x.name.cmp(&y.name));
+        variants
+    }
+    a.representation == b.representation && sorted(a) == sorted(b)
+}
+
+fn string_type(stats: &StringStats, config: &GenerationConfig) -> GeneratedType {
+    match stats.format {
+        // TOML datetimes do not deserialize into strings, so they are always typed
+        Some(format) if format == StringFormat::TomlDatetime || stats.declared || config.string_formats.contains(&format) => {
+            GeneratedType { rust: string_format_type_name(format).to_string(), ty: TypeRef::Format(format) }
+        }
+        _ => GeneratedType { rust: "String".to_string(), ty: TypeRef::String },
+    }
+}
+
+fn number_type_name(json_type: &JsonType) -> &'static str {
+    match number_type_ref(json_type) {
+        TypeRef::Integer { signed: false, bits: 8 } => "u8",
+        TypeRef::Integer { signed: false, bits: 16 } => "u16",
+        TypeRef::Integer { signed: false, bits: 32 } => "u32",
+        TypeRef::Integer { signed: false, .. } => "u64",
+        TypeRef::Integer { signed: true, bits: 8 } => "i8",
+        TypeRef::Integer { signed: true, bits: 16 } => "i16",
+        TypeRef::Integer { signed: true, bits: 32 } => "i32",
+        TypeRef::Integer { signed: true, .. } => "i64",
+        _ => "f64",
+    }
+}
+
+fn number_type_ref(json_type: &JsonType) -> TypeRef {
+    match json_type {
+        JsonType::Integer { min, max } if *min >= 0 && *max <= u32::MAX as i64 => TypeRef::Integer { signed: false, bits: 32 },
+        JsonType::Integer { .. } => TypeRef::Integer { signed: true, bits: 64 },
+        JsonType::Unsigned { .. } => TypeRef::Integer { signed: false, bits: 64 },
+        JsonType::FixedInt { signed, bits } => TypeRef::Integer { signed: *signed, bits: *bits },
+        _ => TypeRef::Float,
+    }
+}
+
 fn find_compatible_struct<'a>(
     new_fields: &[RustField],
     existing_structs: &'a [ExistingStruct],
@@ -216,19 +683,13 @@ fn extend_with_optional_fields(existing: &ExistingStruct, classification: FieldC
     
     // Add old-only fields as optional (for backward compatibility)
     for mut field in classification.old_only_fields {
-        if !field.type_name.starts_with("Option<") {
-            field.type_name = format!("Option<{}>", field.type_name);
-            field.is_optional = true;
-        }
+        field.make_optional();
         fields.push(field);
     }
     
     // Add new-only fields as optional (for backward compatibility)
     for mut field in classification.new_only_fields {
-        if !field.type_name.starts_with("Option<") {
-            field.type_name = format!("Option<{}>", field.type_name);
-            field.is_optional = true;
-        }
+        field.make_optional();
         fields.push(field);
     }
     
@@ -237,6 +698,7 @@ fn extend_with_optional_fields(existing: &ExistingStruct, classification: FieldC
         fields,
         derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
         is_optional: false,
+        docs: Vec::new(),
     }
 }
 
@@ -255,10 +717,7 @@ fn extend_with_enum_fields(existing: &ExistingStruct, new_fields: Vec<RustField>
     for field in &classification.old_only_fields {
         if field.name != "schema_variant" {
             let mut optional_field = field.clone();
-            if !optional_field.type_name.starts_with("Option<") {
-                optional_field.type_name = format!("Option<{}>", optional_field.type_name);
-                optional_field.is_optional = true;
-            }
+            optional_field.make_optional();
             fields.push(optional_field);
         }
     }
@@ -281,9 +740,12 @@ fn extend_with_enum_fields(existing: &ExistingStruct, new_fields: Vec<RustField>
             fields.push(RustField {
                 name: "schema_variant".to_string(),
                 type_name: existing_enum_type.clone(),
+                ty: TypeRef::Named(existing_enum_type.clone()),
                 is_optional: false,
                 serde_rename: None,
                 serde_flatten: true,
+                serde_with: None,
+                serde_default: false,
             });
         } else {
             eprintln!("🔍 Existing enum '{}' not found in enums collection, creating new one", existing_enum_type);
@@ -307,6 +769,7 @@ fn extend_with_enum_fields(existing: &ExistingStruct, new_fields: Vec<RustField>
         fields,
         derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
         is_optional: false,
+        docs: Vec::new(),
     }
 }
 
@@ -330,18 +793,12 @@ fn extend_with_hybrid_fields(existing: &ExistingStruct, classification: FieldCla
     } else {
         // Use optional for small field groups
         for mut field in classification.old_only_fields {
-            if !field.type_name.starts_with("Option<") {
-                field.type_name = format!("Option<{}>", field.type_name);
-                field.is_optional = true;
-            }
+            field.make_optional();
             fields.push(field);
         }
         
         for mut field in classification.new_only_fields {
-            if !field.type_name.starts_with("Option<") {
-                field.type_name = format!("Option<{}>", field.type_name);
-                field.is_optional = true;
-            }
+            field.make_optional();
             fields.push(field);
         }
     }
@@ -351,6 +808,7 @@ fn extend_with_hybrid_fields(existing: &ExistingStruct, classification: FieldCla
         fields,
         derives: vec!["Debug".to_string(), "Clone".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
         is_optional: false,
+        docs: Vec::new(),
     }
 }
 
@@ -361,10 +819,13 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
         eprintln!("🔄 Enum '{}' already exists, skipping duplicate", enum_name);
         return RustField {
             name: "schema_variant".to_string(),
+            ty: TypeRef::Named(enum_name.clone()),
             type_name: enum_name,
             is_optional: false,
             serde_rename: None,
             serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
         };
     }
     
@@ -378,18 +839,19 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
     if !new_fields.is_empty() {
         for field in new_fields {
             if field.name != "schema_variant" {
-                let variant_name = generate_variant_name(&[field.clone()]);
+                let variant_name = generate_variant_name(std::slice::from_ref(field));
                 
                 // Make field optional for variant detection
                 let mut optional_field = field.clone();
-                if !optional_field.type_name.starts_with("Option<") {
-                    optional_field.type_name = format!("Option<{}>", optional_field.type_name);
-                    optional_field.is_optional = true;
-                }
+                optional_field.make_optional();
                 
                 variants.push(RustEnumVariant {
                     name: variant_name,
                     fields: vec![optional_field],
+                    serde_rename: None,
+                    serde_other: false,
+                    newtype: None,
+                    newtype_ty: None,
                 });
             }
         }
@@ -402,9 +864,8 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
         // Make all fields optional for variant detection
         let mut optional_old_fields = old_fields.to_vec();
         for field in &mut optional_old_fields {
-            if field.name != "schema_variant" && !field.type_name.starts_with("Option<") {
-                field.type_name = format!("Option<{}>", field.type_name);
-                field.is_optional = true;
+            if field.name != "schema_variant" {
+                field.make_optional();
             }
         }
         
@@ -415,6 +876,10 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
             variants.push(RustEnumVariant {
                 name: variant_name,
                 fields: optional_old_fields,
+                serde_rename: None,
+                serde_other: false,
+                newtype: None,
+                newtype_ty: None,
             });
         }
     }
@@ -429,6 +894,8 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
             "Serialize".to_string(),
             "Deserialize".to_string(),
         ],
+        representation: EnumRepresentation::Untagged,
+        docs: Vec::new(),
     };
     
     // Add enum to the collection
@@ -437,10 +904,13 @@ fn create_schema_variant_enum(struct_name: &str, old_fields: &[RustField], new_f
     // Return the field that references this enum
     RustField {
         name: "schema_variant".to_string(),
+        ty: TypeRef::Named(enum_name.clone()),
         type_name: enum_name,
         is_optional: false,
         serde_rename: None,
                 serde_flatten: false,
+                serde_with: None,
+                serde_default: false,
     }
 }
 
@@ -457,34 +927,26 @@ fn generate_variant_name(fields: &[RustField]) -> String {
     format!("{}Variant", pascal_name)
 }
 
-fn extract_fields_from_schema(schema: &JsonSchema) -> Result<Vec<RustField>, Json2RustError> {
+fn extract_fields_from_schema(schema: &JsonSchema, new_struct: &RustStruct, config: &GenerationConfig) -> Result<Vec<RustField>, Json2RustError> {
     match &schema.json_type {
-        JsonType::Object(fields) => {
+        JsonType::Object(object) => {
             let mut rust_fields = Vec::new();
+            let rust_names = sanitize_field_names(object.fields.keys(), config.keyword_style);
             
-            for (field_name, field_type) in fields {
-                let field_type_name = match field_type {
-                    JsonType::String => "String".to_string(),
-                    JsonType::Number => "f64".to_string(),
-                    JsonType::Boolean => "bool".to_string(),
-                    JsonType::Null => "Option<serde_json::Value>".to_string(),
-                    JsonType::Array(_) => "Vec<serde_json::Value>".to_string(), // Simplified for now
-                    JsonType::Object(_) => "serde_json::Value".to_string(), // Simplified for now
-                };
-                
-                let rust_field = RustField {
-                    name: to_snake_case(field_name),
-                    type_name: field_type_name,
-                    is_optional: matches!(field_type, JsonType::Null),
-                    serde_rename: if to_snake_case(field_name) != *field_name {
-                        Some(field_name.clone())
-                    } else {
-                        None
+            for ((field_name, field), rust_name) in object.fields.iter().zip(rust_names) {
+                // The generated struct already holds the type, and any nested type it names
+                let generated_field = new_struct.fields.iter().find(|f| f.name == rust_name).ok_or_else(|| {
+                    Json2RustError::CodeGeneration(format!("Field '{}' is missing from generated struct '{}'", rust_name, new_struct.name))
+                })?;
+                let field_type = GeneratedType {
+                    rust: extract_option_inner(&generated_field.type_name).to_string(),
+                    ty: match &generated_field.ty {
+                        TypeRef::Optional(inner) => (**inner).clone(),
+                        ty => ty.clone(),
                     },
-                    serde_flatten: false,
                 };
-                
-                rust_fields.push(rust_field);
+                let serde_with = serde_with_for_type(&field.json_type, config);
+                rust_fields.push(object_field(field_name, rust_name, field_type, serde_with, field, object, config));
             }
             
             Ok(rust_fields)
@@ -493,7 +955,7 @@ fn extract_fields_from_schema(schema: &JsonSchema) -> Result<Vec<RustField>, Jso
     }
 }
 
-fn create_extended_enum(_struct_name: &str, old_fields: &[RustField], new_fields: &[RustField], existing_enum: &RustEnum) -> RustEnum {
+fn create_extended_enum(_struct_name: &str, _old_fields: &[RustField], new_fields: &[RustField], existing_enum: &RustEnum) -> RustEnum {
     let mut variants = existing_enum.variants.clone();
     
     // Collect all truly new fields that don't exist anywhere in the enum
@@ -531,16 +993,17 @@ fn create_extended_enum(_struct_name: &str, old_fields: &[RustField], new_fields
         let mut optional_fields = Vec::new();
         for field in &truly_new_fields {
             let mut optional_field = field.clone();
-            if !optional_field.type_name.starts_with("Option<") {
-                optional_field.type_name = format!("Option<{}>", optional_field.type_name);
-                optional_field.is_optional = true;
-            }
+            optional_field.make_optional();
             optional_fields.push(optional_field);
         }
         
         let new_variant = RustEnumVariant {
             name: variant_name.clone(),
             fields: optional_fields,
+            serde_rename: None,
+            serde_other: false,
+            newtype: None,
+            newtype_ty: None,
         };
         
         variants.push(new_variant);
@@ -551,6 +1014,8 @@ fn create_extended_enum(_struct_name: &str, old_fields: &[RustField], new_fields
         name: existing_enum.name.clone(),
         variants,
         derives: existing_enum.derives.clone(),
+        representation: existing_enum.representation.clone(),
+        docs: Vec::new(),
     }
 }
 
@@ -598,12 +1063,21 @@ fn classify_fields_for_extension_with_enums(
         if let Some(new_field) = new_field_map.get(existing_field_name) {
             // Common field - exists in both schemas
             let compatible_type = get_compatible_type(existing_field_type, &new_field.type_name);
+            // The serde adapter only applies if the new field's type was kept
+            let serde_with = if extract_option_inner(&compatible_type) == new_field.type_name {
+                new_field.serde_with.clone()
+            } else {
+                None
+            };
             common_fields.push(RustField {
                 name: existing_field_name.clone(),
+                ty: merged_type_ref(&compatible_type, new_field),
                 type_name: compatible_type,
                 is_optional: new_field.is_optional || existing_field_type.starts_with("Option<"),
                 serde_rename: new_field.serde_rename.clone(),
                 serde_flatten: false,
+                serde_with,
+                serde_default: new_field.serde_default,
             });
         } else {
             // Old-only field - exists only in existing schema
@@ -611,9 +1085,12 @@ fn classify_fields_for_extension_with_enums(
             old_only_fields.push(RustField {
                 name: existing_field_name.clone(),
                 type_name: existing_field_type.clone(),
+                ty: type_ref_from_rust(existing_field_type),
                 is_optional: existing_field_type.starts_with("Option<"),
                 serde_rename: None,
             serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
             });
         }
     }
@@ -653,34 +1130,46 @@ fn get_compatible_type(existing_type: &str, new_type: &str) -> String {
         return existing_type.to_string();
     }
     
-    // Handle numeric type compatibility
-    if is_numeric_type(existing_type) && new_type == "f64" {
-        return existing_type.to_string(); // Prefer existing numeric type
+    // Numbers keep the existing type unless the samples need a wider one
+    if let Some(widened) = widen_numeric_type(existing_type, new_type) {
+        return widened;
     }
     
     // Handle Option types - if existing is Option<T> and new is T, keep as Option<T>
     if existing_type.starts_with("Option<") && !new_type.starts_with("Option<") {
         let inner_existing = extract_option_inner(existing_type);
-        if inner_existing == new_type || (is_numeric_type(inner_existing) && new_type == "f64") {
+        if inner_existing == new_type {
             return existing_type.to_string(); // Keep existing Optional type
         }
+        if let Some(widened) = widen_numeric_type(inner_existing, new_type) {
+            return format!("Option<{}>", widened);
+        }
     }
     
     if !existing_type.starts_with("Option<") && new_type.starts_with("Option<") {
         let inner_new = extract_option_inner(new_type);
-        if existing_type == inner_new || (is_numeric_type(existing_type) && inner_new == "f64") {
+        if existing_type == inner_new {
             return new_type.to_string();
         }
+        if let Some(widened) = widen_numeric_type(existing_type, inner_new) {
+            return format!("Option<{}>", widened);
+        }
     }
     
     // Default to new type if no compatibility found
     new_type.to_string()
 }
 
-fn is_numeric_type(type_name: &str) -> bool {
-    matches!(type_name, "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | 
-                        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | 
-                        "f32" | "f64")
+/// The `TypeRef` for `type_name`, the type merging settled on for a field generated as
+/// `generated`: the generated one where merging kept it, read from the Rust otherwise.
+fn merged_type_ref(type_name: &str, generated: &RustField) -> TypeRef {
+    if type_name == generated.type_name {
+        generated.ty.clone()
+    } else if extract_option_inner(type_name) == generated.type_name {
+        TypeRef::Optional(Box::new(generated.ty.clone()))
+    } else {
+        type_ref_from_rust(type_name)
+    }
 }
 
 fn extract_option_inner(option_type: &str) -> &str {
@@ -691,18 +1180,6 @@ fn extract_option_inner(option_type: &str) -> &str {
     }
 }
 
-fn get_singular_name(plural_name: &str) -> String {
-    // Simple pluralization rules - can be enhanced
-    if plural_name.ends_with("ies") {
-        format!("{}y", &plural_name[..plural_name.len()-3])
-    } else if plural_name.ends_with("s") && !plural_name.ends_with("ss") {
-        plural_name[..plural_name.len()-1].to_string()
-    } else {
-        // If no clear plural pattern, use "Item" suffix
-        format!("{}Item", plural_name)
-    }
-}
-
 fn ensure_unique_name(base_name: &str, generated_names: &mut HashMap<String, usize>) -> String {
     let count = generated_names.entry(base_name.to_string()).or_insert(0);
     *count += 1;
@@ -719,64 +1196,77 @@ pub fn generate_code(structs: &[RustStruct]) -> Result<String, Json2RustError> {
 }
 
 pub fn generate_code_with_types(types: &GeneratedTypes) -> Result<String, Json2RustError> {
-    let mut code = String::new();
-    
-    code.push_str("use serde::{Deserialize, Serialize};\n\n");
-    
-    // Generate enums first
-    for rust_enum in &types.enums {
-        code.push_str(&generate_enum_code(rust_enum)?);
-        code.push('\n');
+    RustEmitter.emit(types, "")
+}
+
+/// The Rust backend: serde-annotated structs and enums, enums first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustEmitter;
+
+impl Emitter for RustEmitter {
+    fn name(&self) -> &str {
+        "rust"
     }
-    
-    // Generate structs
-    for rust_struct in &types.structs {
-        code.push_str(&generate_struct_code(rust_struct)?);
-        code.push('\n');
+
+    fn file_extension(&self) -> &str {
+        "rs"
+    }
+
+    fn prelude(&self, _types: &GeneratedTypes) -> String {
+        "use serde::{Deserialize, Serialize};\n\n".to_string()
+    }
+
+    fn emit_struct(&self, rust_struct: &RustStruct, _types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        generate_struct_code(rust_struct)
+    }
+
+    fn emit_enum(&self, rust_enum: &RustEnum, _types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        generate_enum_code(rust_enum)
     }
-    
-    Ok(code)
 }
 
 fn generate_enum_code(rust_enum: &RustEnum) -> Result<String, Json2RustError> {
-    let mut code = String::new();
-    
-    let derives = rust_enum.derives.join(", ");
-    code.push_str(&format!("#[derive({})]\n", derives));
-    
-    // Use untagged serialization for field-based variant detection
-    code.push_str(&format!("#[serde(untagged)]\n"));
-    code.push_str(&format!("pub enum {} {{\n", rust_enum.name));
+    let mut attrs = type_attributes(&rust_enum.docs, &rust_enum.derives)?;
+    match &rust_enum.representation {
+        // Use untagged serialization for field-based variant detection
+        EnumRepresentation::Untagged => attrs.push(quote!(#[serde(untagged)])),
+        EnumRepresentation::External => {}
+        EnumRepresentation::Internal { tag } => attrs.push(quote!(#[serde(tag = #tag)])),
+        EnumRepresentation::Adjacent { tag, content } => attrs.push(quote!(#[serde(tag = #tag, content = #content)])),
+    }
     
+    let mut variants = Vec::new();
     for variant in &rust_enum.variants {
-        if variant.fields.is_empty() {
-            code.push_str(&format!("    {},\n", variant.name));
-        } else {
-            code.push_str(&format!("    {} {{\n", variant.name));
-            for field in &variant.fields {
-                if let Some(rename) = &field.serde_rename {
-                    code.push_str(&format!("        #[serde(rename = \"{}\")]\n", rename));
-                }
-                
-                if field.is_optional {
-                    code.push_str(&format!("        #[serde(skip_serializing_if = \"Option::is_none\")]\n"));
-                }
-                
-                let field_type = if field.is_optional && !field.type_name.starts_with("Option<") {
-                    format!("Option<{}>", field.type_name)
-                } else {
-                    field.type_name.clone()
-                };
-                
-                code.push_str(&format!("        {}: {},\n", field.name, field_type));
-            }
-            code.push_str("    },\n");
+        let mut variant_attrs = Vec::new();
+        if let Some(rename) = &variant.serde_rename {
+            variant_attrs.push(quote!(#[serde(rename = #rename)]));
+        }
+        if variant.serde_other {
+            variant_attrs.push(quote!(#[serde(other)]));
         }
+        
+        let name = parse_ident(&variant.name)?;
+        variants.push(if let Some(payload) = &variant.newtype {
+            let payload = parse_type(payload)?;
+            quote!(#(#variant_attrs)* #name(#payload))
+        } else if variant.fields.is_empty() {
+            quote!(#(#variant_attrs)* #name)
+        } else {
+            let fields = variant.fields
+                .iter()
+                .map(|field| field_tokens(field, field.serde_rename.clone(), TokenStream::new()))
+                .collect::<Result<Vec<_>, _>>()?;
+            quote!(#(#variant_attrs)* #name { #(#fields),* })
+        });
     }
     
-    code.push_str("}\n");
-    
-    Ok(code)
+    let name = parse_ident(&rust_enum.name)?;
+    render_item(quote! {
+        #(#attrs)*
+        pub enum #name {
+            #(#variants),*
+        }
+    })
 }
 
 pub fn generate_code_with_preservation(
@@ -801,7 +1291,7 @@ pub fn generate_code_with_types_and_preservation(
     merge_strategy: &MergeStrategy,
 ) -> Result<String, Json2RustError> {
     if let Some(original) = original_code {
-        generate_code_preserving_original(&types.structs, original, merge_strategy)
+        generate_code_preserving_original(&types.structs, &types.enums, original, merge_strategy)
     } else {
         generate_code_with_types(types)
     }
@@ -812,9 +1302,19 @@ pub fn generate_code_with_types_and_preservation_and_schema(
     original_code: Option<&str>,
     merge_strategy: &MergeStrategy,
     schema: Option<&JsonSchema>,
+) -> Result<String, Json2RustError> {
+    generate_code_with_types_and_preservation_and_config(types, original_code, merge_strategy, schema, &GenerationConfig::default())
+}
+
+pub fn generate_code_with_types_and_preservation_and_config(
+    types: &GeneratedTypes,
+    original_code: Option<&str>,
+    merge_strategy: &MergeStrategy,
+    schema: Option<&JsonSchema>,
+    config: &GenerationConfig,
 ) -> Result<String, Json2RustError> {
     if let Some(original) = original_code {
-        generate_code_preserving_original_with_schema(&types.structs, original, merge_strategy, schema)
+        generate_code_preserving_original_with_schema(&types.structs, &types.enums, original, merge_strategy, schema, config)
     } else {
         generate_code_with_types(types)
     }
@@ -822,9 +1322,11 @@ pub fn generate_code_with_types_and_preservation_and_schema(
 
 fn generate_code_preserving_original_with_schema(
     new_structs: &[RustStruct],
+    new_enums: &[RustEnum],
     original_code: &str,
     merge_strategy: &MergeStrategy,
     schema: Option<&JsonSchema>,
+    config: &GenerationConfig,
 ) -> Result<String, Json2RustError> {
     // For preservation, we need to create a mutable enum collection for potential enum generation
     let mut temp_enums = Vec::new();
@@ -843,6 +1345,7 @@ fn generate_code_preserving_original_with_schema(
             eprintln!("🔍 Found existing enum: {}", item_enum.ident);
         }
     }
+    merge_generated_enums(&mut temp_enums, new_enums);
     
     // Create a map of new structs by name for quick lookup
     let new_struct_map: std::collections::HashMap<String, &RustStruct> = new_structs
@@ -850,9 +1353,6 @@ fn generate_code_preserving_original_with_schema(
         .map(|s| (s.name.clone(), s))
         .collect();
     
-    let mut result = String::new();
-    let mut last_end = 0;
-    
     // Find struct spans and sort them by position
     let mut struct_replacements = Vec::new();
     let mut enum_replacements = Vec::new();
@@ -864,7 +1364,6 @@ fn generate_code_preserving_original_with_schema(
                 // When user explicitly specifies a struct name, we should extend it regardless of similarity
                 // The similarity threshold only applies for automatic struct detection
                 eprintln!("🎯 Explicitly extending struct '{}' as requested by user", struct_name);
-                let _span = item_struct.span();
                 
                 // Parse the existing struct to get its fields
                 let existing_struct = parse_struct_from_item(item_struct)?;
@@ -873,7 +1372,7 @@ fn generate_code_preserving_original_with_schema(
                 let fields_to_use = if let Some(schema) = schema {
                     if schema.name == struct_name {
                         // Extract fields from the schema
-                        extract_fields_from_schema(schema)?
+                        extract_fields_from_schema(schema, new_struct, config)?
                     } else {
                         new_struct.fields.clone()
                     }
@@ -891,10 +1390,8 @@ fn generate_code_preserving_original_with_schema(
                     eprintln!("🔄 Enum count changed from {} to {}", initial_enum_count, temp_enums.len());
                 }
                 
-                // For proc_macro2::Span, we need to use a different approach
-                // Let's find the struct boundaries by searching for the struct name
-                let start_byte = find_struct_start(original_code, &struct_name)?;
-                let end_byte = find_struct_end(original_code, start_byte)?;
+                // Replace the whole item, every attribute above it included
+                let std::ops::Range { start: start_byte, end: end_byte } = item_struct.span().byte_range();
                 
                 struct_replacements.push(StructReplacement {
                     start: start_byte,
@@ -917,8 +1414,7 @@ fn generate_code_preserving_original_with_schema(
                 // Check if the enum was actually modified (has different content)
                 let original_enum = parse_enum_from_item(item_enum)?;
                 if enum_was_modified(&original_enum, modified_enum) {
-                    let start_byte = find_enum_start(original_code, &enum_name)?;
-                    let end_byte = find_enum_end(original_code, start_byte)?;
+                    let std::ops::Range { start: start_byte, end: end_byte } = item_enum.span().byte_range();
                     
                     enum_replacements.push(EnumReplacement {
                         start: start_byte,
@@ -952,46 +1448,27 @@ fn generate_code_preserving_original_with_schema(
         all_replacements.push((replacement.start, replacement.end, code));
     }
     
-    // Sort all replacements by start position
-    all_replacements.sort_by_key(|r| r.0);
-    
-    // Process the file, preserving original text and replacing specific items
-    for (start, end, code) in all_replacements {
-        // Add original text up to this item
-        result.push_str(&original_code[last_end..start]);
-        
-        // Add the new item code
-        result.push_str(&code);
-        
-        last_end = end;
-    }
-    
-    // Add remaining original text
-    result.push_str(&original_code[last_end..]);
-    
-    // Add completely new structs that weren't in the original file
+    // Add completely new structs and enums that weren't in the original file
+    let mut appended = Vec::new();
     for new_struct in new_structs {
         if !struct_exists_in_original(&ast, &new_struct.name) {
-            result.push('\n');
-            result.push_str(&generate_struct_code(new_struct)?);
+            appended.push(generate_struct_code(new_struct)?);
             eprintln!("✨ Added new struct '{}'", new_struct.name);
         }
     }
-    
-    // Add any generated enums (only new ones, not existing ones)
     for rust_enum in &temp_enums {
         if !existing_enum_names.contains(&rust_enum.name) {
-            result.push('\n');
-            result.push_str(&generate_enum_code(rust_enum)?);
+            appended.push(generate_enum_code(rust_enum)?);
             eprintln!("✨ Added new enum '{}'", rust_enum.name);
         }
     }
     
-    Ok(result)
+    splice_items(original_code, all_replacements, appended)
 }
 
 fn generate_code_preserving_original(
     new_structs: &[RustStruct],
+    new_enums: &[RustEnum],
     original_code: &str,
     merge_strategy: &MergeStrategy,
 ) -> Result<String, Json2RustError> {
@@ -1012,6 +1489,7 @@ fn generate_code_preserving_original(
             eprintln!("🔍 Found existing enum: {}", item_enum.ident);
         }
     }
+    merge_generated_enums(&mut temp_enums, new_enums);
     
     // Create a map of new structs by name for quick lookup
     let new_struct_map: std::collections::HashMap<String, &RustStruct> = new_structs
@@ -1019,9 +1497,6 @@ fn generate_code_preserving_original(
         .map(|s| (s.name.clone(), s))
         .collect();
     
-    let mut result = String::new();
-    let mut last_end = 0;
-    
     // Find struct spans and sort them by position
     let mut struct_replacements = Vec::new();
     let mut enum_replacements = Vec::new();
@@ -1033,7 +1508,6 @@ fn generate_code_preserving_original(
                 // When user explicitly specifies a struct name, we should extend it regardless of similarity
                 // The similarity threshold only applies for automatic struct detection
                 eprintln!("🎯 Explicitly extending struct '{}' as requested by user", struct_name);
-                let _span = item_struct.span();
                 
                 // Parse the existing struct to get its fields
                 let existing_struct = parse_struct_from_item(item_struct)?;
@@ -1048,10 +1522,8 @@ fn generate_code_preserving_original(
                     eprintln!("🔄 Enum count changed from {} to {}", initial_enum_count, temp_enums.len());
                 }
                 
-                // For proc_macro2::Span, we need to use a different approach
-                // Let's find the struct boundaries by searching for the struct name
-                let start_byte = find_struct_start(original_code, &struct_name)?;
-                let end_byte = find_struct_end(original_code, start_byte)?;
+                // Replace the whole item, every attribute above it included
+                let std::ops::Range { start: start_byte, end: end_byte } = item_struct.span().byte_range();
                 
                 struct_replacements.push(StructReplacement {
                     start: start_byte,
@@ -1074,8 +1546,7 @@ fn generate_code_preserving_original(
                 // Check if the enum was actually modified (has different content)
                 let original_enum = parse_enum_from_item(item_enum)?;
                 if enum_was_modified(&original_enum, modified_enum) {
-                    let start_byte = find_enum_start(original_code, &enum_name)?;
-                    let end_byte = find_enum_end(original_code, start_byte)?;
+                    let std::ops::Range { start: start_byte, end: end_byte } = item_enum.span().byte_range();
                     
                     enum_replacements.push(EnumReplacement {
                         start: start_byte,
@@ -1109,122 +1580,95 @@ fn generate_code_preserving_original(
         all_replacements.push((replacement.start, replacement.end, code));
     }
     
-    // Sort all replacements by start position
-    all_replacements.sort_by_key(|r| r.0);
-    
-    // Process the file, preserving original text and replacing specific items
-    for (start, end, code) in all_replacements {
-        // Add original text up to this item
-        result.push_str(&original_code[last_end..start]);
-        
-        // Add the new item code
-        result.push_str(&code);
-        
-        last_end = end;
-    }
-    
-    // Add remaining original text
-    result.push_str(&original_code[last_end..]);
-    
-    // Add completely new structs that weren't in the original file
+    // Add completely new structs and enums that weren't in the original file
+    let mut appended = Vec::new();
     for new_struct in new_structs {
         if !struct_exists_in_original(&ast, &new_struct.name) {
-            result.push('\n');
-            result.push_str(&generate_struct_code(new_struct)?);
+            appended.push(generate_struct_code(new_struct)?);
             eprintln!("✨ Added new struct '{}'", new_struct.name);
         }
     }
-    
-    // Add any generated enums (only new ones, not existing ones)
     for rust_enum in &temp_enums {
         if !existing_enum_names.contains(&rust_enum.name) {
-            result.push('\n');
-            result.push_str(&generate_enum_code(rust_enum)?);
+            appended.push(generate_enum_code(rust_enum)?);
             eprintln!("✨ Added new enum '{}'", rust_enum.name);
         }
     }
     
-    Ok(result)
+    splice_items(original_code, all_replacements, appended)
 }
 
-struct StructReplacement {
-    start: usize,
-    end: usize,
-    new_struct: RustStruct,
-    #[allow(dead_code)]
-    name: String,
-}
-
-struct EnumReplacement {
-    start: usize,
-    end: usize,
-    new_enum: RustEnum,
-    #[allow(dead_code)]
-    name: String,
+/// Folds freshly generated enums into the enums parsed from the original file: new enums
+/// are appended, and unit-only enums that already exist gain any newly observed values.
+fn merge_generated_enums(existing_enums: &mut Vec<RustEnum>, new_enums: &[RustEnum]) {
+    for new_enum in new_enums {
+        match existing_enums.iter_mut().find(|e| e.name == new_enum.name) {
+            Some(existing_enum) => merge_unit_variants(existing_enum, new_enum),
+            None => existing_enums.push(new_enum.clone()),
+        }
+    }
 }
 
-fn find_struct_start(source: &str, struct_name: &str) -> Result<usize, Json2RustError> {
-    // Simple approach: look for the start of the struct definition including derives
-    let lines: Vec<&str> = source.lines().collect();
-    let mut struct_line_idx = None;
-    
-    // Find the line with the struct definition
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim().contains(&format!("struct {}", struct_name)) {
-            struct_line_idx = Some(i);
-            break;
-        }
+fn merge_unit_variants(existing_enum: &mut RustEnum, new_enum: &RustEnum) {
+    let is_unit_only = |rust_enum: &RustEnum| {
+        rust_enum.representation == EnumRepresentation::External
+            && rust_enum.variants.iter().all(|v| v.fields.is_empty() && v.newtype.is_none())
+    };
+    if !is_unit_only(existing_enum) || !is_unit_only(new_enum) {
+        return;
     }
     
-    if let Some(struct_idx) = struct_line_idx {
-        // Look backwards for derive attributes
-        let mut start_idx = struct_idx;
-        
-        while start_idx > 0 {
-            let prev_line = lines[start_idx - 1].trim();
-            if prev_line.starts_with("#[derive(") || prev_line.starts_with("pub ") || prev_line.starts_with("//") || prev_line.is_empty() {
-                start_idx -= 1;
-            } else {
-                break;
-            }
+    let wire_name = |variant: &RustEnumVariant| variant.serde_rename.clone().unwrap_or_else(|| variant.name.clone());
+    let mut variant_names: HashMap<String, usize> = existing_enum
+        .variants
+        .iter()
+        .map(|v| (v.name.clone(), 1))
+        .collect();
+    
+    for variant in new_enum.variants.iter().filter(|v| !v.serde_other) {
+        let value = wire_name(variant);
+        if existing_enum.variants.iter().any(|v| !v.serde_other && wire_name(v) == value) {
+            continue;
         }
         
-        // Calculate byte position
-        let byte_pos = lines[..start_idx].iter().map(|l| l.len() + 1).sum::<usize>();
-        Ok(byte_pos)
-    } else {
-        Err(Json2RustError::CodeGeneration(format!("Could not find struct {} in source", struct_name)))
+        let name = ensure_unique_name(&variant.name, &mut variant_names);
+        // Keep any catch-all variant last
+        let position = existing_enum
+            .variants
+            .iter()
+            .position(|v| v.serde_other)
+            .unwrap_or(existing_enum.variants.len());
+        existing_enum.variants.insert(position, RustEnumVariant {
+            serde_rename: (name != value).then_some(value),
+            name,
+            fields: Vec::new(),
+            serde_other: false,
+            newtype: None,
+            newtype_ty: None,
+        });
     }
 }
 
-fn find_struct_end(source: &str, start: usize) -> Result<usize, Json2RustError> {
-    let remaining = &source[start..];
-    let mut brace_count = 0;
-    let mut in_struct = false;
-    
-    for (i, ch) in remaining.char_indices() {
-        match ch {
-            '{' => {
-                brace_count += 1;
-                in_struct = true;
-            }
-            '}' => {
-                brace_count -= 1;
-                if in_struct && brace_count == 0 {
-                    return Ok(start + i + 1);
-                }
-            }
-            _ => {}
-        }
-    }
-    
-    Err(Json2RustError::CodeGeneration("Could not find end of struct".to_string()))
+struct StructReplacement {
+    start: usize,
+    end: usize,
+    new_struct: RustStruct,
+    #[allow(dead_code)]
+    name: String,
+}
+
+struct EnumReplacement {
+    start: usize,
+    end: usize,
+    new_enum: RustEnum,
+    #[allow(dead_code)]
+    name: String,
 }
 
 fn struct_exists_in_original(ast: &syn::File, name: &str) -> bool {
     ast.items.iter().any(|item| {
         if let syn::Item::Struct(item_struct) = item {
-            item_struct.ident.to_string() == name
+            item_struct.ident == name
         } else {
             false
         }
@@ -1250,65 +1694,8 @@ fn enum_was_modified(original: &RustEnum, modified: &RustEnum) -> bool {
     false
 }
 
-fn find_enum_start(source: &str, enum_name: &str) -> Result<usize, Json2RustError> {
-    let lines: Vec<&str> = source.lines().collect();
-    let mut enum_line_idx = None;
-    
-    // Find the line with the enum definition
-    for (i, line) in lines.iter().enumerate() {
-        if line.trim().contains(&format!("enum {}", enum_name)) {
-            enum_line_idx = Some(i);
-            break;
-        }
-    }
-    
-    if let Some(enum_idx) = enum_line_idx {
-        // Look backwards for derive attributes
-        let mut start_idx = enum_idx;
-        
-        while start_idx > 0 {
-            let prev_line = lines[start_idx - 1].trim();
-            if prev_line.starts_with("#[derive(") || prev_line.starts_with("#[serde(") || prev_line.starts_with("pub ") || prev_line.starts_with("//") || prev_line.is_empty() {
-                start_idx -= 1;
-            } else {
-                break;
-            }
-        }
-        
-        // Calculate byte position
-        let byte_pos = lines[..start_idx].iter().map(|l| l.len() + 1).sum::<usize>();
-        Ok(byte_pos)
-    } else {
-        Err(Json2RustError::CodeGeneration(format!("Could not find enum {} in source", enum_name)))
-    }
-}
-
-fn find_enum_end(source: &str, start: usize) -> Result<usize, Json2RustError> {
-    let remaining = &source[start..];
-    let mut brace_count = 0;
-    let mut in_enum = false;
-    
-    for (i, ch) in remaining.char_indices() {
-        match ch {
-            '{' => {
-                brace_count += 1;
-                in_enum = true;
-            }
-            '}' => {
-                brace_count -= 1;
-                if in_enum && brace_count == 0 {
-                    return Ok(start + i + 1);
-                }
-            }
-            _ => {}
-        }
-    }
-    
-    Err(Json2RustError::CodeGeneration("Could not find end of enum".to_string()))
-}
-
 fn parse_struct_from_item(item_struct: &syn::ItemStruct) -> Result<ExistingStruct, Json2RustError> {
-    let mut fields = HashMap::new();
+    let mut fields = IndexMap::new();
     
     if let syn::Fields::Named(named_fields) = &item_struct.fields {
         for field in &named_fields.named {
@@ -1331,8 +1718,16 @@ fn parse_enum_from_item(item_enum: &syn::ItemEnum) -> Result<RustEnum, Json2Rust
     for variant in &item_enum.variants {
         let variant_name = variant.ident.to_string();
         let mut fields = Vec::new();
+        let mut newtype = None;
+        let mut newtype_ty = None;
         
         // Parse variant fields
+        if let syn::Fields::Unnamed(unnamed_fields) = &variant.fields {
+            if let [field] = unnamed_fields.unnamed.iter().collect::<Vec<_>>().as_slice() {
+                newtype = Some(type_to_string(&field.ty));
+                newtype_ty = Some(syn_type_ref(&field.ty));
+            }
+        }
         if let syn::Fields::Named(named_fields) = &variant.fields {
             for field in &named_fields.named {
                 if let Some(field_name) = &field.ident {
@@ -1347,9 +1742,12 @@ fn parse_enum_from_item(item_enum: &syn::ItemEnum) -> Result<RustEnum, Json2Rust
                     fields.push(RustField {
                         name: field_name.to_string(),
                         type_name: field_type,
+                        ty: syn_type_ref(&field.ty),
                         is_optional,
                         serde_rename,
-                        serde_flatten: false,
+                        serde_flatten: has_serde_flag(&field.attrs, "flatten"),
+                        serde_with: None,
+                        serde_default: false,
                     });
                 }
             }
@@ -1358,104 +1756,288 @@ fn parse_enum_from_item(item_enum: &syn::ItemEnum) -> Result<RustEnum, Json2Rust
         variants.push(RustEnumVariant {
             name: variant_name,
             fields,
+            serde_rename: extract_serde_rename(&variant.attrs),
+            serde_other: has_serde_flag(&variant.attrs, "other"),
+            newtype,
+            newtype_ty,
         });
     }
     
     // Extract derive attributes
     let derives = extract_derives(&item_enum.attrs);
     
+    let representation = if has_serde_flag(&item_enum.attrs, "untagged") {
+        EnumRepresentation::Untagged
+    } else if let Some(tag) = extract_serde_value(&item_enum.attrs, "tag") {
+        match extract_serde_value(&item_enum.attrs, "content") {
+            Some(content) => EnumRepresentation::Adjacent { tag, content },
+            None => EnumRepresentation::Internal { tag },
+        }
+    } else {
+        EnumRepresentation::External
+    };
+    
     Ok(RustEnum {
         name: item_enum.ident.to_string(),
         variants,
         derives,
+        representation,
+        docs: Vec::new(),
     })
 }
 
-fn extract_serde_rename(_attrs: &[syn::Attribute]) -> Option<String> {
-    // For now, return None to avoid complex attribute parsing
-    // TODO: Implement proper serde rename extraction
-    None
+fn serde_metas(attrs: &[syn::Attribute]) -> Vec<syn::Meta> {
+    use syn::punctuated::Punctuated;
+    
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated).ok())
+        .flatten()
+        .collect()
 }
 
-fn extract_derives(_attrs: &[syn::Attribute]) -> Vec<String> {
-    // For now, return default derives to avoid complex attribute parsing
-    // TODO: Implement proper derive extraction
-    vec![
-        "Debug".to_string(),
-        "Clone".to_string(),
-        "Serialize".to_string(),
-        "Deserialize".to_string(),
-    ]
+fn extract_serde_rename(attrs: &[syn::Attribute]) -> Option<String> {
+    extract_serde_value(attrs, "rename")
 }
 
+fn extract_serde_value(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+    serde_metas(attrs).into_iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(name_value) if name_value.path.is_ident(key) => match name_value.value {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Some(lit.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn has_serde_flag(attrs: &[syn::Attribute], flag: &str) -> bool {
+    serde_metas(attrs)
+        .iter()
+        .any(|meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(flag)))
+}
+
+fn extract_derives(attrs: &[syn::Attribute]) -> Vec<String> {
+    use syn::punctuated::Punctuated;
+    
+    let derives: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated).ok())
+        .flatten()
+        .map(|path| {
+            path.segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::")
+        })
+        .collect();
+    
+    if derives.is_empty() {
+        vec![
+            "Debug".to_string(),
+            "Clone".to_string(),
+            "Serialize".to_string(),
+            "Deserialize".to_string(),
+        ]
+    } else {
+        derives
+    }
+}
+
+/// A type as generated code writes it, with its full path: `Option<serde_json::Value>`.
 fn type_to_string(ty: &syn::Type) -> String {
     match ty {
         syn::Type::Path(type_path) => {
-            if let Some(segment) = type_path.path.segments.last() {
-                match &segment.arguments {
-                    syn::PathArguments::None => segment.ident.to_string(),
-                    syn::PathArguments::AngleBracketed(args) => {
-                        let inner_types: Vec<String> = args
+            let segments: Vec<String> = type_path
+                .path
+                .segments
+                .iter()
+                .map(|segment| {
+                    let inner_types: Vec<String> = match &segment.arguments {
+                        syn::PathArguments::AngleBracketed(args) => args
                             .args
                             .iter()
-                            .filter_map(|arg| {
-                                if let syn::GenericArgument::Type(inner_ty) = arg {
-                                    Some(type_to_string(inner_ty))
-                                } else {
-                                    None
-                                }
+                            .filter_map(|arg| match arg {
+                                syn::GenericArgument::Type(inner_ty) => Some(type_to_string(inner_ty)),
+                                _ => None,
                             })
-                            .collect();
-                        
-                        if inner_types.is_empty() {
-                            segment.ident.to_string()
-                        } else {
-                            format!("{}<{}>", segment.ident, inner_types.join(", "))
-                        }
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    if inner_types.is_empty() {
+                        segment.ident.to_string()
+                    } else {
+                        format!("{}<{}>", segment.ident, inner_types.join(", "))
                     }
-                    _ => segment.ident.to_string(),
-                }
-            } else {
-                "Unknown".to_string()
-            }
+                })
+                .collect();
+            let leading_colon = if type_path.path.leading_colon.is_some() { "::" } else { "" };
+            format!("{}{}", leading_colon, segments.join("::"))
+        }
+        syn::Type::Tuple(tuple) => {
+            let items: Vec<String> = tuple.elems.iter().map(type_to_string).collect();
+            format!("({})", items.join(", "))
         }
         _ => "Unknown".to_string(),
     }
 }
 
+/// Fields are made optional after their adapter is chosen, so an `As::<T>` adapter
+/// has to be widened to `As::<Option<T>>` once the final field type is known.
+fn effective_serde_with(field: &RustField, field_type: &str) -> Option<String> {
+    let with = field.serde_with.as_ref()?;
+    match with.strip_prefix("serde_with::As::<").and_then(|rest| rest.strip_suffix('>')) {
+        Some(adapter) if field_type.starts_with("Option<") && !adapter.starts_with("Option<") => {
+            Some(format!("serde_with::As::<Option<{}>>", adapter))
+        }
+        _ => Some(with.clone()),
+    }
+}
 
 fn generate_struct_code(rust_struct: &RustStruct) -> Result<String, Json2RustError> {
-    let mut code = String::new();
+    let mut attrs = type_attributes(&rust_struct.docs, &rust_struct.derives)?;
     
-    let derives = rust_struct.derives.join(", ");
-    code.push_str(&format!("#[derive({})]\n", derives));
-    code.push_str(&format!("pub struct {} {{\n", rust_struct.name));
+    // A struct-wide rename_all replaces per-field renames that follow one convention
+    let wire_name = |field: &RustField| field.serde_rename.clone().unwrap_or_else(|| unraw(&field.name).to_string());
+    let keyed_fields: Vec<(&str, String)> = rust_struct.fields.iter()
+        .filter(|field| !field.serde_flatten)
+        .map(|field| (unraw(&field.name), wire_name(field)))
+        .collect();
+    let keyed_refs: Vec<(&str, &str)> = keyed_fields.iter().map(|(name, wire)| (*name, wire.as_str())).collect();
+    let rename_all = dominant_rename_rule(&keyed_refs);
+    if let Some(rule) = rename_all {
+        let rule = rule.serde_name();
+        attrs.push(quote!(#[serde(rename_all = #rule)]));
+    }
     
+    let mut fields = Vec::new();
     for field in &rust_struct.fields {
-        if let Some(rename) = &field.serde_rename {
-            code.push_str(&format!("    #[serde(rename = \"{}\")]\n", rename));
-        }
-        
-        if field.serde_flatten {
-            code.push_str("    #[serde(flatten)]\n");
-        }
-        
-        if field.is_optional {
-            code.push_str(&format!("    #[serde(skip_serializing_if = \"Option::is_none\")]\n"));
-        }
-        
-        let field_type = if field.is_optional && !field.type_name.starts_with("Option<") {
-            format!("Option<{}>", field.type_name)
-        } else {
-            field.type_name.clone()
+        let rename = match rename_all {
+            Some(rule) if !field.serde_flatten => {
+                let wire = wire_name(field);
+                (rule.apply_to_field(unraw(&field.name)) != wire).then_some(wire)
+            }
+            _ => field.serde_rename.clone(),
         };
-        
-        code.push_str(&format!("    pub {}: {},\n", field.name, field_type));
+        fields.push(field_tokens(field, rename, quote!(pub))?);
     }
     
-    code.push_str("}\n");
+    let name = parse_ident(&rust_struct.name)?;
+    render_item(quote! {
+        #(#attrs)*
+        pub struct #name {
+            #(#fields),*
+        }
+    })
+}
+
+/// Doc comments and the derive list of a generated struct or enum.
+fn type_attributes(docs: &[String], derives: &[String]) -> Result<Vec<TokenStream>, Json2RustError> {
+    let mut attrs: Vec<TokenStream> = docs
+        .iter()
+        .map(|line| {
+            // prettyplease writes `#[doc = " text"]` back as `/// text`
+            let line = if line.is_empty() { String::new() } else { format!(" {}", line) };
+            quote!(#[doc = #line])
+        })
+        .collect();
+    let derives = derives
+        .iter()
+        .map(|derive| syn::parse_str::<syn::Path>(derive).map_err(|e| invalid_code("derive", derive, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !derives.is_empty() {
+        attrs.push(quote!(#[derive(#(#derives),*)]));
+    }
+    Ok(attrs)
+}
+
+/// A named field with its serde attributes; `visibility` is empty for enum variant fields.
+fn field_tokens(field: &RustField, rename: Option<String>, visibility: TokenStream) -> Result<TokenStream, Json2RustError> {
+    let field_type = if field.is_optional && !field.type_name.starts_with("Option<") {
+        format!("Option<{}>", field.type_name)
+    } else {
+        field.type_name.clone()
+    };
+    
+    let mut attrs = Vec::new();
+    if let Some(rename) = rename {
+        attrs.push(quote!(#[serde(rename = #rename)]));
+    }
+    if field.serde_flatten {
+        attrs.push(quote!(#[serde(flatten)]));
+    }
+    let serde_with = effective_serde_with(field, &field_type);
+    // `with` adapters do not imply a default for missing fields
+    if field.serde_default || (serde_with.is_some() && field_type.starts_with("Option<")) {
+        attrs.push(quote!(#[serde(default)]));
+    }
+    if let Some(with) = serde_with {
+        attrs.push(quote!(#[serde(with = #with)]));
+    }
+    if field.is_optional {
+        attrs.push(quote!(#[serde(skip_serializing_if = "Option::is_none")]));
+    }
+    
+    let name = parse_ident(&field.name)?;
+    let ty = parse_type(&field_type)?;
+    Ok(quote!(#(#attrs)* #visibility #name: #ty))
+}
+
+fn parse_ident(name: &str) -> Result<syn::Ident, Json2RustError> {
+    syn::parse_str(name).map_err(|e| invalid_code("identifier", name, e))
+}
+
+fn parse_type(type_name: &str) -> Result<syn::Type, Json2RustError> {
+    syn::parse_str(type_name).map_err(|e| invalid_code("type", type_name, e))
+}
+
+fn invalid_code(kind: &str, text: &str, error: syn::Error) -> Json2RustError {
+    Json2RustError::CodeGeneration(format!("Invalid {} `{}`: {}", kind, text, error))
+}
+
+/// Formats one generated item as rustfmt would lay it out.
+fn render_item(tokens: TokenStream) -> Result<String, Json2RustError> {
+    let item: syn::Item = syn::parse2(tokens)
+        .map_err(|e| Json2RustError::CodeGeneration(format!("Generated item is not valid Rust: {}", e)))?;
+    let code = prettyplease::unparse(&syn::File { shebang: None, attrs: Vec::new(), items: vec![item] });
+    ensure_parses(code)
+}
+
+/// `original_code` with each `(start, end, code)` range replaced by a generated item and the
+/// `appended` items added after it. Only generated items are laid out anew; every other byte,
+/// comments and hand-written formatting included, is kept as it was.
+fn splice_items(original_code: &str, mut replacements: Vec<(usize, usize, String)>, appended: Vec<String>) -> Result<String, Json2RustError> {
+    replacements.sort_by_key(|r| r.0);
+    let mut result = String::new();
+    let mut last_end = 0;
+    for (start, end, code) in replacements {
+        result.push_str(&original_code[last_end..start]);
+        // The span stops at the closing brace, so the original text after it brings the newline
+        result.push_str(code.trim_end());
+        last_end = end;
+    }
+    result.push_str(&original_code[last_end..]);
     
-    Ok(code)
+    for code in appended {
+        result.truncate(result.trim_end().len());
+        if !result.is_empty() {
+            result.push_str("\n\n");
+        }
+        result.push_str(&code);
+    }
+    
+    // Splicing works on the source text, so check the stitched file is still Rust
+    ensure_parses(result)
+}
+
+/// `code`, once it is known to parse as a Rust file.
+fn ensure_parses(code: String) -> Result<String, Json2RustError> {
+    match syn::parse_file(&code) {
+        Ok(_) => Ok(code),
+        Err(e) => Err(Json2RustError::CodeGeneration(format!("Generated code does not parse: {}", e))),
+    }
 }
 
 #[cfg(test)]
@@ -1464,13 +2046,13 @@ mod tests {
 
     #[test]
     fn test_generate_simple_struct() {
-        let mut fields = HashMap::new();
-        fields.insert("name".to_string(), JsonType::String);
-        fields.insert("age".to_string(), JsonType::Number);
+        let mut fields = IndexMap::new();
+        fields.insert("name".to_string(), JsonType::String(StringStats::default()));
+        fields.insert("age".to_string(), JsonType::Integer { min: 30, max: 30 });
         
         let schema = JsonSchema {
             name: "Person".to_string(),
-            json_type: JsonType::Object(fields),
+            json_type: JsonType::Object(ObjectStats::from_fields(fields)),
             optional: false,
         };
         
@@ -1488,28 +2070,528 @@ mod tests {
                 RustField {
                     name: "name".to_string(),
                     type_name: "String".to_string(),
+                    ty: TypeRef::String,
                     is_optional: false,
                     serde_rename: None,
             serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
                 },
                 RustField {
                     name: "age".to_string(),
                     type_name: "f64".to_string(),
+                    ty: TypeRef::Float,
                     is_optional: false,
                     serde_rename: None,
             serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
                 },
             ],
             derives: vec!["Debug".to_string(), "Serialize".to_string(), "Deserialize".to_string()],
             is_optional: false,
+            docs: vec!["A person.".to_string()],
         };
         
         let code = generate_code(&[rust_struct]).unwrap();
-        assert!(code.contains("pub struct Person"));
+        assert!(code.contains("/// A person.\n#[derive(Debug, Serialize, Deserialize)]\npub struct Person"));
         assert!(code.contains("pub name: String"));
         assert!(code.contains("pub age: f64"));
     }
 
+    #[test]
+    fn test_attribute_strings_are_escaped() {
+        let field = |name: &str, type_name: &str, serde_rename: Option<&str>| RustField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            ty: type_ref_from_rust(type_name),
+            is_optional: false,
+            serde_rename: serde_rename.map(str::to_string),
+            serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
+        };
+        let rust_struct = RustStruct {
+            name: "Quoted".to_string(),
+            fields: vec![field("say_hi", "String", Some(r#"say "hi""#)), field("path", "String", Some(r"C:\path"))],
+            derives: vec!["Debug".to_string()],
+            is_optional: false,
+            docs: Vec::new(),
+        };
+        
+        let code = generate_code(std::slice::from_ref(&rust_struct)).unwrap();
+        assert!(code.contains(r#"    #[serde(rename = "say \"hi\"")]"#), "{}", code);
+        assert!(code.contains(r#"    #[serde(rename = "C:\\path")]"#), "{}", code);
+        syn::parse_file(&code).unwrap();
+        
+        let broken = RustStruct { fields: vec![field("count", "Vec<u32", None)], ..rust_struct };
+        let error = generate_code(&[broken]).unwrap_err();
+        assert!(error.to_string().contains("Invalid type `Vec<u32`"), "{}", error);
+    }
+
+    #[test]
+    fn test_string_formats_are_opt_in() {
+        let mut fields = IndexMap::new();
+        fields.insert("created_at".to_string(), JsonType::String(StringStats { format: Some(StringFormat::DateTime), ..Default::default() }));
+        fields.insert("id".to_string(), JsonType::String(StringStats { format: Some(StringFormat::Uuid), ..Default::default() }));
+        fields.insert("payload".to_string(), JsonType::String(StringStats { format: Some(StringFormat::Base64), ..Default::default() }));
+        
+        let schema = JsonSchema {
+            name: "Event".to_string(),
+            json_type: JsonType::Object(ObjectStats::from_fields(fields)),
+            optional: false,
+        };
+        
+        let plain = generate_rust_structs(&schema, &[]).unwrap();
+        assert!(plain[0].fields.iter().all(|f| f.type_name == "String" && f.serde_with.is_none()));
+        
+        let config = GenerationConfig {
+            string_formats: vec![StringFormat::DateTime, StringFormat::Base64],
+            ..GenerationConfig::default()
+        };
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        let field = |name: &str| types.structs[0].fields.iter().find(|f| f.name == name).unwrap().clone();
+        assert_eq!(field("created_at").type_name, "chrono::DateTime<chrono::Utc>");
+        assert_eq!(field("id").type_name, "String");
+        assert_eq!(field("payload").type_name, "Vec<u8>");
+        
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("#[serde(with = \"serde_with::As::<serde_with::base64::Base64>\")]"));
+    }
+
+    #[test]
+    fn test_string_enum_from_low_cardinality_field() {
+        let json = r#"[
+            {"status": "active"}, {"status": "suspended"}, {"status": "active"},
+            {"status": "IN_REVIEW"}, {"status": "active"}, {"status": "suspended"}
+        ]"#;
+        let schema = crate::analyzer::analyze_json(json, "Accounts").unwrap();
+        let config = GenerationConfig {
+            string_enums: true,
+            enum_unknown_variant: true,
+            ..GenerationConfig::default()
+        };
+        
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        let account = types.structs.iter().find(|s| s.name == "Account").unwrap();
+        assert_eq!(account.fields[0].type_name, "Status");
+        
+        let status = &types.enums[0];
+        assert_eq!(status.representation, EnumRepresentation::External);
+        let names: Vec<&str> = status.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["Active", "Suspended", "InReview", "Unknown"]);
+        assert_eq!(status.variants[0].serde_rename.as_deref(), Some("active"));
+        assert!(status.variants[3].serde_other);
+        
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(!code.contains("untagged"));
+        assert!(code.contains("    #[serde(rename = \"IN_REVIEW\")]\n    InReview,"));
+        assert!(code.contains("    #[serde(other)]\n    Unknown,"));
+    }
+
+    #[test]
+    fn test_constant_string_stays_string() {
+        let json = r#"[{"kind": "order"}, {"kind": "order"}, {"kind": "order"}]"#;
+        let schema = crate::analyzer::analyze_json(json, "Events").unwrap();
+        let config = GenerationConfig { string_enums: true, ..GenerationConfig::default() };
+        
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        assert!(types.enums.is_empty());
+        assert_eq!(types.structs[0].fields[0].type_name, "String");
+    }
+
+    #[test]
+    fn test_fields_with_same_values_share_string_enum() {
+        let json = r#"[
+            {"billing_address": {"country": "US"}, "shipping_address": {"country": "DE"}},
+            {"billing_address": {"country": "DE"}, "shipping_address": {"country": "US"}},
+            {"billing_address": {"country": "US"}, "shipping_address": {"country": "US"}},
+            {"billing_address": {"country": "DE"}, "shipping_address": {"country": "DE"}}
+        ]"#;
+        let schema = crate::analyzer::analyze_json(json, "Orders").unwrap();
+        let config = GenerationConfig {
+            string_enums: true,
+            struct_naming: StructNaming::CommonSuffix,
+            ..GenerationConfig::default()
+        };
+        
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        assert_eq!(types.enums.len(), 1);
+        assert_eq!(types.enums[0].name, "Country");
+        let names: Vec<&str> = types.structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Address", "Order", "Orders"]);
+    }
+
+    #[test]
+    fn test_high_cardinality_strings_stay_strings() {
+        let json = r#"[{"name": "Alice"}, {"name": "Bob"}, {"name": "Carol"}]"#;
+        let schema = crate::analyzer::analyze_json(json, "Users").unwrap();
+        let config = GenerationConfig { string_enums: true, ..GenerationConfig::default() };
+        
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        assert!(types.enums.is_empty());
+        assert_eq!(types.structs[0].fields[0].type_name, "String");
+    }
+
+    #[test]
+    fn test_existing_string_enum_gains_new_values() {
+        let original = r#"#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Status {
+    #[serde(rename = "active")]
+    Active,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub status: Status,
+}
+"#;
+        let json = r#"[{"status": "active"}, {"status": "deleted"}, {"status": "deleted"}, {"status": "active"}]"#;
+        let schema = crate::analyzer::analyze_json(json, "Accounts").unwrap();
+        let config = GenerationConfig { string_enums: true, ..GenerationConfig::default() };
+        let existing = crate::parser::parse_existing_structs(original).unwrap();
+        
+        let types = generate_rust_types_with_config(&schema, &existing, &MergeStrategy::Optional, &config).unwrap();
+        let code = generate_code_with_types_and_preservation_and_config(&types, Some(original), &MergeStrategy::Optional, Some(&schema), &config).unwrap();
+        
+        let ast: syn::File = syn::parse_str(&code).unwrap();
+        let item_enum = ast.items.iter().find_map(|item| match item {
+            syn::Item::Enum(item_enum) => Some(item_enum),
+            _ => None,
+        }).unwrap();
+        let parsed = parse_enum_from_item(item_enum).unwrap();
+        let names: Vec<&str> = parsed.variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["Active", "Deleted", "Unknown"]);
+        assert_eq!(parsed.variants[1].serde_rename.as_deref(), Some("deleted"));
+        assert!(parsed.variants[2].serde_other);
+        assert!(parsed.derives.contains(&"PartialEq".to_string()));
+        assert_eq!(code.matches("pub enum Status").count(), 1);
+    }
+
+    #[test]
+    fn test_nulls_inside_collections_round_trip() {
+        let json = r#"{"values": [1, null, 2], "rows": [[1, "a", true], [null, "b", false]], "scores": {"a": 1, "b": null}, "nested_nulls": [[null], [1]]}"#;
+        let options = AnalysisOptions { map_paths: vec!["scores".to_string()], ..AnalysisOptions::default() };
+        let schema = crate::analyzer::analyze_json_with_options(json, "Root", &options).unwrap();
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        
+        assert!(code.contains("pub values: Vec<Option<u32>>,"), "{}", code);
+        assert!(code.contains("pub rows: Vec<(Option<u32>, String, bool)>,"), "{}", code);
+        assert!(code.contains("pub scores: std::collections::HashMap<String, Option<u32>>,"), "{}", code);
+        assert!(code.contains("pub nested_nulls: Vec<Vec<Option<u32>>>,"), "{}", code);
+        
+        // The generated fields, spelled out, read the sample back with every null in place
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Root {
+            values: Vec<Option<u32>>,
+            rows: Vec<(Option<u32>, String, bool)>,
+            scores: HashMap<String, Option<u32>>,
+            nested_nulls: Vec<Vec<Option<u32>>>,
+        }
+        let root: Root = serde_json::from_str(json).unwrap();
+        assert_eq!(root.values, [Some(1), None, Some(2)]);
+        assert_eq!(root.rows[1], (None, "b".to_string(), false));
+        assert_eq!(root.scores["b"], None);
+        assert_eq!(root.nested_nulls, [vec![None], vec![Some(1)]]);
+    }
+
+    #[test]
+    fn test_untagged_union_tries_sequences_first() {
+        let json = r#"[{"value": {"id": 1}}, {"value": [3]}, {"value": {"id": 2}}]"#;
+        let schema = crate::analyzer::analyze_json(json, "Root").unwrap();
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        
+        assert!(code.contains("pub enum ValueType {\n    Array(Vec<u32>),\n    Object(ValueTypeObject),\n}"), "{}", code);
+        
+        // The generated enum, spelled out: a one-field struct would also accept `[3]`
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(untagged)]
+        enum ValueType {
+            Array(Vec<u32>),
+            Object(ValueTypeObject),
+        }
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct ValueTypeObject {
+            id: u32,
+        }
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct RootItem {
+            value: ValueType,
+        }
+        let items: Vec<RootItem> = serde_json::from_str(json).unwrap();
+        assert_eq!(items[0].value, ValueType::Object(ValueTypeObject { id: 1 }));
+        assert_eq!(items[1].value, ValueType::Array(vec![3]));
+    }
+
+    #[test]
+    fn test_preserved_struct_keeps_generated_field_types() {
+        let original = "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Root {\n    pub id: u32,\n}\n";
+        let json = r#"{
+            "id": 1,
+            "events": [{"type": "click", "x": 1}, {"type": "key", "code": "a"}, {"type": "click", "x": 2}],
+            "users": {"u1": {"name": "a"}, "u2": {"name": "b"}, "u3": {"name": "c"}, "u4": {"name": "d"}},
+            "scores": [1.5, 2.5],
+            "owner": {"login": "e", "admin": true}
+        }"#;
+        let schema = crate::analyzer::analyze_json(json, "Root").unwrap();
+        let existing = crate::parser::parse_existing_structs(original).unwrap();
+        
+        let types = generate_rust_types_with_config(&schema, &existing, &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types_and_preservation_and_schema(&types, Some(original), &MergeStrategy::Optional, Some(&schema)).unwrap();
+        
+        assert!(code.contains("pub events: Option<Vec<Event>>,"), "{}", code);
+        assert!(code.contains("pub users: Option<std::collections::HashMap<String, UsersValue>>,"), "{}", code);
+        assert!(code.contains("pub scores: Option<Vec<f64>>,"), "{}", code);
+        assert!(code.contains("pub owner: Option<Owner>,"), "{}", code);
+        assert!(code.contains("pub enum Event {"), "{}", code);
+        assert!(code.contains("pub struct UsersValue {"), "{}", code);
+        assert!(!code.contains("serde_json::Value"), "{}", code);
+    }
+
+    #[test]
+    fn test_preserved_output_is_formatted() {
+        let original = "use serde::{Deserialize, Serialize};\n#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Root {\n    pub id: u32,\n    pub meta: Option<serde_json::Value>,\n    pub pair: (u32, String),\n}\n";
+        let json = r#"{"id": 1, "meta": null, "pair": [1, "a"], "owner": {"name": "a"}}"#;
+        let schema = crate::analyzer::analyze_json(json, "Root").unwrap();
+        let existing = crate::parser::parse_existing_structs(original).unwrap();
+        
+        let types = generate_rust_types_with_config(&schema, &existing, &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types_and_preservation_and_schema(&types, Some(original), &MergeStrategy::Optional, Some(&schema)).unwrap();
+        
+        // Every generated item reads exactly as prettyplease prints it
+        use syn::spanned::Spanned;
+        let file = syn::parse_file(&code).unwrap();
+        for item in &file.items[1..] {
+            let printed = prettyplease::unparse(&syn::File { shebang: None, attrs: Vec::new(), items: vec![item.clone()] });
+            assert_eq!(&code[item.span().byte_range()], printed.trim_end(), "{}", code);
+        }
+        assert!(code.contains("pub meta: Option<serde_json::Value>,"), "{}", code);
+        assert!(code.contains("pub pair: (u32, String),"), "{}", code);
+        assert!(code.contains("}\n\n#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Owner {"), "{}", code);
+    }
+
+    #[test]
+    fn test_preservation_keeps_hand_written_code() {
+        let header = "//! Hand-maintained API types — café édition.\nuse serde::{Deserialize, Serialize};\n\n// Keep in sync with the server\n";
+        let user = "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct User {\n    pub name: String,\n}\n";
+        let footer = "\nimpl User {\n    // helper\n    pub fn shout(&self) -> String { self.name.to_uppercase() }   // one-liner on purpose\n}\n";
+        let original = format!("{}{}{}", header, user, footer);
+        let schema = crate::analyzer::analyze_json(r#"{"name": "a", "age": 3}"#, "User").unwrap();
+        let existing = crate::parser::parse_existing_structs(&original).unwrap();
+        
+        let types = generate_rust_types_with_config(&schema, &existing, &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types_and_preservation_and_schema(&types, Some(&original), &MergeStrategy::Optional, Some(&schema)).unwrap();
+        
+        assert!(code.starts_with(header), "{}", code);
+        assert!(code.ends_with(footer), "{}", code);
+        assert!(code.contains("    pub age: Option<u32>,\n}\n\nimpl User {"), "{}", code);
+    }
+
+    #[test]
+    fn test_output_is_deterministic() {
+        let json = r#"{"zulu": 1, "yankee": {"xray": true, "whiskey": "w"}, "victor": [1.5], "uniform": null}"#;
+        let generate = || {
+            let schema = crate::analyzer::analyze_json(json, "Root").unwrap();
+            let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+            generate_code_with_types(&types).unwrap()
+        };
+        
+        let code = generate();
+        let field_positions: Vec<usize> = ["pub zulu", "pub yankee", "pub victor", "pub uniform"]
+            .iter()
+            .map(|field| code.find(field).unwrap())
+            .collect();
+        assert!(field_positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(code.find("pub xray").unwrap() < code.find("pub whiskey").unwrap());
+        
+        for _ in 0..10 {
+            assert_eq!(generate(), code);
+        }
+    }
+
+    #[test]
+    fn test_mixed_arrays_become_tuples_and_unions() {
+        let json = r#"{"location": [52.5, 13.4, "Berlin"], "readings": [1, "two", {"three": 3}, 4, 5, 6, 7, 8, 9]}"#;
+        let schema = crate::analyzer::analyze_json(json, "Sample").unwrap();
+        
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let sample = types.structs.iter().find(|s| s.name == "Sample").unwrap();
+        assert_eq!(sample.fields[0].type_name, "(f64, f64, String)");
+        assert_eq!(sample.fields[1].type_name, "Vec<Reading>");
+        
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("#[serde(untagged)]\npub enum Reading {\n    Integer(u32),\n    String(String),\n    Object(ReadingObject),\n}"));
+        assert!(code.contains("pub struct ReadingObject"));
+    }
+
+    #[test]
+    fn test_tagged_enums() {
+        let json = r#"{"events": [{"type": "user_created", "id": 1}, {"type": "deleted", "id": 2, "reason": "spam"}], "log": [{"kind": "a", "data": {"x": 1}}, {"kind": "b", "data": {"y": true}}]}"#;
+        let schema = crate::analyzer::analyze_json(json, "Feed").unwrap();
+        
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("#[serde(tag = \"type\")]\npub enum Event {\n    #[serde(rename = \"user_created\")]\n    UserCreated { id: u32 },"));
+        assert!(code.contains("#[serde(tag = \"kind\", content = \"data\")]\npub enum LogItem {"));
+        
+        let ast: syn::File = syn::parse_str(&code).unwrap();
+        let representations: Vec<EnumRepresentation> = ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Enum(item_enum) => Some(parse_enum_from_item(item_enum).unwrap().representation),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(representations, [
+            EnumRepresentation::Internal { tag: "type".to_string() },
+            EnumRepresentation::Adjacent { tag: "kind".to_string(), content: "data".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_missing_and_null_fields_are_optional() {
+        let json = r#"[{"id": 1, "name": "a", "parent": null}, {"id": 2, "parent": 1}]"#;
+        let schema = crate::analyzer::analyze_json(json, "Nodes").unwrap();
+        
+        let structs = generate_rust_structs(&schema, &[]).unwrap();
+        let node = structs.iter().find(|s| s.name == "Node").unwrap();
+        let types: Vec<(&str, bool)> = node.fields.iter().map(|f| (f.type_name.as_str(), f.is_optional)).collect();
+        assert_eq!(types, [("u32", false), ("String", true), ("u32", true)]);
+        
+        let config = GenerationConfig { serde_default: true, double_option: true, ..GenerationConfig::default() };
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        let node = types.structs.iter().find(|s| s.name == "Node").unwrap();
+        assert_eq!(node.fields[1].type_name, "Option<Option<String>>");
+        assert!(node.fields[1].serde_default);
+        assert!(!node.fields[2].serde_default);
+        
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("    #[serde(default)]\n    #[serde(with = \"serde_with::rust::double_option\")]\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub parent: Option<Option<u32>>,"));
+        assert_eq!(code.matches("#[serde(default)]").count(), 2);
+    }
+
+    #[test]
+    fn test_recursive_types_reuse_ancestor() {
+        let json = r#"{"name": "ceo", "reports": [{"name": "cto", "reports": [{"name": "dev", "reports": []}]}], "mentor": {"name": "x", "mentor": null}}"#;
+        let schema = crate::analyzer::analyze_json(json, "Employee").unwrap();
+        
+        let structs = generate_rust_structs(&schema, &[]).unwrap();
+        assert_eq!(structs.len(), 1);
+        let types: Vec<&str> = structs[0].fields.iter().map(|f| f.type_name.as_str()).collect();
+        assert_eq!(types, ["String", "Vec<Employee>", "Box<Employee>"]);
+        assert!(structs[0].fields[2].is_optional);
+    }
+
+    #[test]
+    fn test_map_like_objects_become_maps() {
+        let json = r#"{"scores": {"2024-01-01": {"points": 3}, "2024-01-02": {"points": 5}, "2024-01-03": {"points": 1}, "2024-01-04": {"points": 8}}}"#;
+        let schema = crate::analyzer::analyze_json(json, "Report").unwrap();
+        
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let report = types.structs.iter().find(|s| s.name == "Report").unwrap();
+        assert_eq!(report.fields[0].type_name, "std::collections::HashMap<String, ScoresValue>");
+        assert!(types.structs.iter().any(|s| s.name == "ScoresValue"));
+        
+        let config = GenerationConfig { map_type: MapType::BTreeMap, ..GenerationConfig::default() };
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        let report = types.structs.iter().find(|s| s.name == "Report").unwrap();
+        assert_eq!(report.fields[0].type_name, "std::collections::BTreeMap<String, ScoresValue>");
+    }
+
+    #[test]
+    fn test_root_map_is_flattened() {
+        let json = r#"{"1": true, "2": false, "3": true, "4": true}"#;
+        let schema = crate::analyzer::analyze_json(json, "Flags").unwrap();
+        
+        let structs = generate_rust_structs(&schema, &[]).unwrap();
+        assert_eq!(structs.len(), 1);
+        assert_eq!(structs[0].fields[0].type_name, "std::collections::HashMap<String, bool>");
+        assert!(structs[0].fields[0].serde_flatten);
+    }
+
+    #[test]
+    fn test_identical_nested_structs_are_merged() {
+        let json = r#"{"billing_address": {"street": "1 Main", "city": "Oslo"}, "shipping_address": {"street": "2 Side", "city": "Bergen"}}"#;
+        let schema = crate::analyzer::analyze_json(json, "Order").unwrap();
+        
+        let config = GenerationConfig { struct_naming: StructNaming::CommonSuffix, ..GenerationConfig::default() };
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        let names: Vec<&str> = types.structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Address", "Order"]);
+        let order = &types.structs[1];
+        assert!(order.fields.iter().all(|f| f.type_name == "Address"));
+        
+        let config = GenerationConfig { dedupe_structs: false, ..GenerationConfig::default() };
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &config).unwrap();
+        assert_eq!(types.structs.len(), 3);
+    }
+
+    #[test]
+    fn test_awkward_keys_generate_valid_code() {
+        let json = r#"{"type": "a", "1st_place": 1, "@id": "x", "content-type": "y", "userId": 1, "user_id": 2, "self": {"ok": true}}"#;
+        let schema = crate::analyzer::analyze_json(json, "Record").unwrap();
+        
+        let structs = generate_rust_structs(&schema, &[]).unwrap();
+        let record = structs.iter().find(|s| s.name == "Record").unwrap();
+        let names: Vec<&str> = record.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["r#type", "_1st_place", "id", "content_type", "user_id_2", "user_id", "self_"]);
+        assert_eq!(record.fields[0].serde_rename, None);
+        assert_eq!(record.fields[4].serde_rename.as_deref(), Some("userId"));
+        assert_eq!(record.fields[6].type_name, "SelfValue");
+        
+        let code = generate_code(&structs).unwrap();
+        assert!(syn::parse_file(&code).is_ok(), "generated code does not parse:\n{}", code);
+    }
+
+    #[test]
+    fn test_dominant_casing_uses_rename_all() {
+        let json = r#"{"userId": 1, "createdAt": "x", "updatedAt": "y", "isAPIUser": true, "name": "n", "legacy_flag": false}"#;
+        let schema = crate::analyzer::analyze_json(json, "Account").unwrap();
+        
+        let structs = generate_rust_structs(&schema, &[]).unwrap();
+        let names: Vec<&str> = structs[0].fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["user_id", "created_at", "updated_at", "is_api_user", "name", "legacy_flag"]);
+        
+        let code = generate_code(&structs).unwrap();
+        assert!(code.contains("#[serde(rename_all = \"camelCase\")]\npub struct Account"));
+        assert!(code.contains("#[serde(rename = \"isAPIUser\")]"));
+        assert!(code.contains("#[serde(rename = \"legacy_flag\")]"));
+        assert!(!code.contains("#[serde(rename = \"userId\")]"));
+    }
+
+    #[test]
+    fn test_optional_field_widens_serde_with() {
+        let field = RustField {
+            name: "payload".to_string(),
+            type_name: "Vec<u8>".to_string(),
+            ty: TypeRef::Format(StringFormat::Base64),
+            is_optional: true,
+            serde_rename: None,
+            serde_flatten: false,
+            serde_with: Some("serde_with::As::<serde_with::base64::Base64>".to_string()),
+            serde_default: false,
+        };
+        
+        assert_eq!(
+            effective_serde_with(&field, "Option<Vec<u8>>").as_deref(),
+            Some("serde_with::As::<Option<serde_with::base64::Base64>>")
+        );
+    }
+
+    #[test]
+    fn test_number_type_name() {
+        assert_eq!(number_type_name(&JsonType::Integer { min: 0, max: 4_000_000_000 }), "u32");
+        assert_eq!(number_type_name(&JsonType::Integer { min: -1, max: 10 }), "i64");
+        assert_eq!(number_type_name(&JsonType::Integer { min: 0, max: 1_700_000_000_000 }), "i64");
+        assert_eq!(number_type_name(&JsonType::Unsigned { min: 0, max: u64::MAX }), "u64");
+        assert_eq!(number_type_name(&JsonType::Float { min: 0.5, max: 1.5 }), "f64");
+    }
+
     #[test]
     fn test_ensure_unique_name() {
         let mut generated_names = HashMap::new();
@@ -1518,4 +2600,19 @@ mod tests {
         assert_eq!(ensure_unique_name("Person", &mut generated_names), "Person2");
         assert_eq!(ensure_unique_name("Person", &mut generated_names), "Person3");
     }
+
+    #[test]
+    fn test_keys_named_like_std_types() {
+        let json = r#"{"string": {"text": "a"}, "vec": {"len": 2}, "label": "x"}"#;
+        let schema = crate::analyzer::analyze_json(json, "Root").unwrap();
+        let types = generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        
+        assert!(code.contains("pub struct StringType {"), "{}", code);
+        assert!(code.contains("pub string: StringType,"), "{}", code);
+        assert!(code.contains("pub vec: VecType,"), "{}", code);
+        // `String` still means the std type
+        assert!(code.contains("pub label: String,"), "{}", code);
+        assert!(!code.contains("pub struct String "), "{}", code);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,335 @@
+use crate::types::*;
+use crate::codegen::GeneratedTypes;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+
+/// Field names, types and serde attributes of a struct, ignoring field order and optionality.
+type StructShape = (Vec<String>, Vec<(String, String, Option<String>, bool, Option<String>)>);
+
+/// Merges structs with the same shape under a single name and rewrites every reference to
+/// the names merged away. Fields that differ only in optionality still count as the same
+/// shape; the merged field is optional if it was optional in any struct of the group.
+///
+/// Names in `protected` (the root type and structs carried over from existing code) win
+/// whenever they are part of a group; a group holding two protected names is left alone.
+/// Merging runs until nothing changes, so parents that only differed by the names of their
+/// children collapse too.
+pub fn deduplicate_structs(types: &mut GeneratedTypes, naming: StructNaming, protected: &[String]) {
+    loop {
+        let renames = merge_identical_structs(types, naming, protected);
+        if renames.is_empty() {
+            break;
+        }
+        rewrite_references(types, &renames);
+    }
+}
+
+fn merge_identical_structs(
+    types: &mut GeneratedTypes,
+    naming: StructNaming,
+    protected: &[String],
+) -> HashMap<String, String> {
+    let mut groups: IndexMap<StructShape, Vec<usize>> = IndexMap::new();
+    for (index, rust_struct) in types.structs.iter().enumerate() {
+        groups.entry(struct_shape(rust_struct)).or_default().push(index);
+    }
+
+    let mut taken: HashSet<String> = types.structs.iter().map(|s| s.name.clone())
+        .chain(types.enums.iter().map(|e| e.name.clone()))
+        .collect();
+    let mut renames = HashMap::new();
+    let mut merged = HashSet::new();
+
+    for members in groups.values().filter(|members| members.len() > 1) {
+        let names: Vec<String> = members.iter().map(|&i| types.structs[i].name.clone()).collect();
+        let protected_names: Vec<&String> = names.iter().filter(|name| protected.contains(name)).collect();
+        let canonical = match protected_names.as_slice() {
+            [] => canonical_name(&names, naming, &taken),
+            [name] => (*name).clone(),
+            _ => continue,
+        };
+
+        let keep = members[0];
+        for &other in &members[1..] {
+            let other_fields = types.structs[other].fields.clone();
+            for field in &mut types.structs[keep].fields {
+                if let Some(other_field) = other_fields.iter().find(|f| f.name == field.name) {
+                    field.is_optional |= other_field.is_optional;
+                    field.serde_default |= other_field.serde_default;
+                }
+            }
+            merged.insert(other);
+        }
+
+        for name in names.into_iter().filter(|name| *name != canonical) {
+            renames.insert(name, canonical.clone());
+        }
+        types.structs[keep].name = canonical.clone();
+        taken.insert(canonical);
+    }
+
+    let mut index = 0;
+    types.structs.retain(|_| {
+        index += 1;
+        !merged.contains(&(index - 1))
+    });
+    renames
+}
+
+fn struct_shape(rust_struct: &RustStruct) -> StructShape {
+    let mut fields: Vec<_> = rust_struct.fields.iter()
+        .map(|f| (f.name.clone(), f.type_name.clone(), f.serde_rename.clone(), f.serde_flatten, f.serde_with.clone()))
+        .collect();
+    fields.sort();
+    (rust_struct.derives.clone(), fields)
+}
+
+fn canonical_name(names: &[String], naming: StructNaming, taken: &HashSet<String>) -> String {
+    let first = names[0].clone();
+    match naming {
+        StructNaming::FirstSeen => first,
+        StructNaming::Shortest => names.iter().min_by_key(|name| name.len()).cloned().unwrap_or(first),
+        StructNaming::CommonSuffix => common_word_suffix(names)
+            // Only reuse a name that is free or already belongs to the group
+            .filter(|suffix| !taken.contains(suffix) || names.contains(suffix))
+            .unwrap_or(first),
+    }
+}
+
+/// Longest run of PascalCase words that ends every name, e.g. `Address` for
+/// `BillingAddress` and `ShippingAddress`.
+fn common_word_suffix(names: &[String]) -> Option<String> {
+    let word_lists: Vec<Vec<&str>> = names.iter().map(|name| pascal_words(name)).collect();
+    let shortest = word_lists.iter().map(Vec::len).min()?;
+    let common = (0..shortest)
+        .take_while(|&i| {
+            let word = word_lists[0][word_lists[0].len() - 1 - i];
+            word_lists.iter().all(|words| words[words.len() - 1 - i] == word)
+        })
+        .count();
+    if common == 0 {
+        return None;
+    }
+    let words = &word_lists[0];
+    let suffix = words[words.len() - common..].concat();
+    // A bare number is not a usable type name
+    suffix.starts_with(|c: char| c.is_ascii_alphabetic()).then_some(suffix)
+}
+
+fn pascal_words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    for (index, c) in name.char_indices() {
+        let boundary = match previous {
+            Some(p) => (c.is_uppercase() && !p.is_uppercase()) || (c.is_ascii_digit() != p.is_ascii_digit()),
+            None => false,
+        };
+        if boundary {
+            words.push(&name[start..index]);
+            start = index;
+        }
+        previous = Some(c);
+    }
+    if start < name.len() {
+        words.push(&name[start..]);
+    }
+    words
+}
+
+fn rewrite_references(types: &mut GeneratedTypes, renames: &HashMap<String, String>) {
+    let struct_fields = types.structs.iter_mut().flat_map(|s| s.fields.iter_mut());
+    let variant_fields = types.enums.iter_mut()
+        .flat_map(|e| e.variants.iter_mut())
+        .flat_map(|variant| {
+            if let Some(newtype) = &mut variant.newtype {
+                *newtype = rename_type_refs(newtype, renames);
+            }
+            if let Some(newtype_ty) = &mut variant.newtype_ty {
+                rename_named(newtype_ty, renames);
+            }
+            variant.fields.iter_mut()
+        });
+    for field in struct_fields.chain(variant_fields) {
+        field.type_name = rename_type_refs(&field.type_name, renames);
+        rename_named(&mut field.ty, renames);
+    }
+}
+
+fn rename_named(ty: &mut TypeRef, renames: &HashMap<String, String>) {
+    match ty {
+        TypeRef::Named(name) => {
+            if let Some(renamed) = renames.get(name) {
+                *name = renamed.clone();
+            }
+        }
+        TypeRef::Optional(inner) | TypeRef::List(inner) | TypeRef::Map(_, inner) => rename_named(inner, renames),
+        TypeRef::Tuple(items) => items.iter_mut().for_each(|item| rename_named(item, renames)),
+        _ => {}
+    }
+}
+
+/// Replaces whole identifiers in a type such as `Option<Vec<BillingAddress>>`. Path segments
+/// after `::` belong to other crates and are left untouched.
+fn rename_type_refs(type_name: &str, renames: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(type_name.len());
+    let mut ident = String::new();
+    let flush = |ident: &mut String, result: &mut String| {
+        match renames.get(ident.as_str()) {
+            Some(renamed) if !result.ends_with("::") => result.push_str(renamed),
+            _ => result.push_str(ident),
+        }
+        ident.clear();
+    };
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else {
+            flush(&mut ident, &mut result);
+            result.push(c);
+        }
+    }
+    flush(&mut ident, &mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, type_name: &str) -> RustField {
+        RustField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            ty: crate::parser::type_ref_from_rust(type_name),
+            is_optional: false,
+            serde_rename: None,
+            serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
+        }
+    }
+
+    fn rust_struct(name: &str, fields: Vec<RustField>) -> RustStruct {
+        RustStruct {
+            name: name.to_string(),
+            fields,
+            derives: vec!["Debug".to_string()],
+            is_optional: false,
+            docs: Vec::new(),
+        }
+    }
+
+    fn address_types() -> GeneratedTypes {
+        let address = || vec![field("street", "String"), field("city", "String")];
+        GeneratedTypes {
+            structs: vec![
+                rust_struct("BillingAddress", address()),
+                rust_struct("ShippingAddress", address()),
+                rust_struct("Order", vec![
+                    field("billing_address", "BillingAddress"),
+                    field("shipping_address", "Option<Vec<ShippingAddress>>"),
+                ]),
+            ],
+            enums: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_identical_structs() {
+        let mut types = address_types();
+        deduplicate_structs(&mut types, StructNaming::FirstSeen, &["Order".to_string()]);
+
+        let names: Vec<&str> = types.structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["BillingAddress", "Order"]);
+        assert_eq!(types.structs[1].fields[1].type_name, "Option<Vec<BillingAddress>>");
+    }
+
+    #[test]
+    fn test_struct_naming() {
+        let mut types = address_types();
+        deduplicate_structs(&mut types, StructNaming::CommonSuffix, &[]);
+        assert_eq!(types.structs[0].name, "Address");
+        assert_eq!(types.structs[1].fields[0].type_name, "Address");
+        assert_eq!(types.structs[1].fields[1].type_name, "Option<Vec<Address>>");
+        let address = TypeRef::Named("Address".to_string());
+        assert_eq!(types.structs[1].fields[1].ty, TypeRef::Optional(Box::new(TypeRef::List(Box::new(address)))));
+
+        let mut types = address_types();
+        types.structs[1].name = "Addr".to_string();
+        types.structs[2].fields[1] = field("shipping_address", "Addr");
+        deduplicate_structs(&mut types, StructNaming::Shortest, &[]);
+        assert_eq!(types.structs[0].name, "Addr");
+        assert_eq!(types.structs[1].fields[0].type_name, "Addr");
+    }
+
+    #[test]
+    fn test_merge_cascades_to_parents() {
+        let mut types = GeneratedTypes {
+            structs: vec![
+                rust_struct("HomeGeo", vec![field("lat", "f64")]),
+                rust_struct("Home", vec![field("geo", "HomeGeo")]),
+                rust_struct("WorkGeo", vec![field("lat", "f64")]),
+                rust_struct("Work", vec![field("geo", "WorkGeo")]),
+                rust_struct("Person", vec![field("home", "Home"), field("work", "Work")]),
+            ],
+            enums: Vec::new(),
+        };
+        deduplicate_structs(&mut types, StructNaming::FirstSeen, &["Person".to_string()]);
+
+        let names: Vec<&str> = types.structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["HomeGeo", "Home", "Person"]);
+        assert_eq!(types.structs[2].fields[1].type_name, "Home");
+    }
+
+    #[test]
+    fn test_near_identical_structs_merge_optionality() {
+        let mut optional_city = field("city", "String");
+        optional_city.is_optional = true;
+        let mut types = GeneratedTypes {
+            structs: vec![
+                rust_struct("From", vec![field("city", "String")]),
+                rust_struct("To", vec![optional_city]),
+                rust_struct("Other", vec![field("city", "i64")]),
+            ],
+            enums: Vec::new(),
+        };
+        deduplicate_structs(&mut types, StructNaming::FirstSeen, &[]);
+
+        assert_eq!(types.structs.len(), 2);
+        assert!(types.structs[0].fields[0].is_optional);
+    }
+
+    #[test]
+    fn test_protected_names_are_kept() {
+        let mut types = address_types();
+        deduplicate_structs(&mut types, StructNaming::FirstSeen, &["ShippingAddress".to_string()]);
+        assert_eq!(types.structs[0].name, "ShippingAddress");
+        assert_eq!(types.structs[1].fields[0].type_name, "ShippingAddress");
+
+        let mut types = address_types();
+        let protected = ["BillingAddress".to_string(), "ShippingAddress".to_string()];
+        deduplicate_structs(&mut types, StructNaming::FirstSeen, &protected);
+        assert_eq!(types.structs.len(), 3);
+    }
+
+    #[test]
+    fn test_rename_type_refs() {
+        let renames = HashMap::from([("Value".to_string(), "Payload".to_string())]);
+        assert_eq!(
+            rename_type_refs("HashMap<String, Value>", &renames),
+            "HashMap<String, Payload>"
+        );
+        assert_eq!(rename_type_refs("serde_json::Value", &renames), "serde_json::Value");
+        assert_eq!(rename_type_refs("ValueItem", &renames), "ValueItem");
+    }
+
+    #[test]
+    fn test_common_word_suffix() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(common_word_suffix(&names(&["BillingAddress", "ShippingAddress"])).as_deref(), Some("Address"));
+        assert_eq!(common_word_suffix(&names(&["HomeGeoPoint", "WorkGeoPoint"])).as_deref(), Some("GeoPoint"));
+        assert_eq!(common_word_suffix(&names(&["Address", "Address2"])), None);
+        assert_eq!(common_word_suffix(&names(&["Item2", "Entry2"])), None);
+    }
+}
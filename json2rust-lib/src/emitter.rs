@@ -0,0 +1,140 @@
+use crate::codegen::{GeneratedTypes, RustEmitter};
+use crate::schema_export::JsonSchemaEmitter;
+use crate::types::*;
+use crate::typescript::TypeScriptEmitter;
+
+/// A backend that writes generated types as source in some target language. The types are
+/// the shared IR every backend reads: structs and enums with their fields, serde attributes
+/// and docs, with each field's type both as Rust and as a `TypeRef` for other languages. An
+/// emitter renders one type at a time, and `emit` strings them together; one whose output is a
+/// single document, like JSON Schema, overrides `emit` instead.
+pub trait Emitter {
+    /// Name the emitter is registered and chosen under, e.g. `rust` or `ts`.
+    fn name(&self) -> &str;
+
+    /// Extension of the files it writes, without the dot.
+    fn file_extension(&self) -> &str;
+
+    /// Text written before the first type, such as imports.
+    fn prelude(&self, _types: &GeneratedTypes) -> String {
+        String::new()
+    }
+
+    /// One struct; `types` holds every other type it may refer to.
+    fn emit_struct(&self, rust_struct: &RustStruct, types: &GeneratedTypes) -> Result<String, Json2RustError>;
+
+    /// One enum; `types` holds every other type it may refer to.
+    fn emit_enum(&self, rust_enum: &RustEnum, types: &GeneratedTypes) -> Result<String, Json2RustError>;
+
+    /// The prelude, then every enum and every struct, separated by blank lines. `root_name` is
+    /// the type the input was named after, for outputs that single one out.
+    fn emit(&self, types: &GeneratedTypes, _root_name: &str) -> Result<String, Json2RustError> {
+        let mut items = Vec::new();
+        for rust_enum in &types.enums {
+            items.push(self.emit_enum(rust_enum, types)?);
+        }
+        for rust_struct in &types.structs {
+            items.push(self.emit_struct(rust_struct, types)?);
+        }
+        Ok(self.prelude(types) + &items.join("\n"))
+    }
+}
+
+/// Emitters by name, so a target picked at run time can be looked up and other crates can add
+/// their own backends next to the built-in ones.
+#[derive(Default)]
+pub struct EmitterRegistry {
+    emitters: Vec<Box<dyn Emitter>>,
+}
+
+impl EmitterRegistry {
+    /// The Rust, JSON Schema and TypeScript emitters.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::default();
+        registry.register(Box::new(RustEmitter));
+        registry.register(Box::new(JsonSchemaEmitter));
+        registry.register(Box::new(TypeScriptEmitter));
+        registry
+    }
+
+    /// Adds `emitter`, replacing any registered under the same name.
+    pub fn register(&mut self, emitter: Box<dyn Emitter>) {
+        self.emitters.retain(|registered| registered.name() != emitter.name());
+        self.emitters.push(emitter);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Emitter> {
+        self.emitters.iter().find(|emitter| emitter.name() == name).map(|emitter| emitter.as_ref())
+    }
+
+    /// Registered names, in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.emitters.iter().map(|emitter| emitter.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend the library does not ship, as another crate would write it.
+    struct PythonEmitter;
+
+    impl Emitter for PythonEmitter {
+        fn name(&self) -> &str {
+            "python"
+        }
+
+        fn file_extension(&self) -> &str {
+            "py"
+        }
+
+        fn prelude(&self, _types: &GeneratedTypes) -> String {
+            "from dataclasses import dataclass\n\n".to_string()
+        }
+
+        fn emit_struct(&self, rust_struct: &RustStruct, _types: &GeneratedTypes) -> Result<String, Json2RustError> {
+            let mut code = format!("@dataclass\nclass {}:\n", rust_struct.name);
+            for field in &rust_struct.fields {
+                code.push_str(&format!("    {}: object\n", field.name));
+            }
+            Ok(code)
+        }
+
+        fn emit_enum(&self, rust_enum: &RustEnum, _types: &GeneratedTypes) -> Result<String, Json2RustError> {
+            Ok(format!("{} = object\n", rust_enum.name))
+        }
+    }
+
+    fn types() -> GeneratedTypes {
+        let schema = crate::analyzer::analyze_json(r#"{"id": 1, "name": "Ann"}"#, "User").unwrap();
+        crate::codegen::generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_emitters() {
+        let registry = EmitterRegistry::with_builtins();
+        assert_eq!(registry.names(), ["rust", "json-schema", "ts"]);
+
+        let types = types();
+        let rust = registry.get("rust").unwrap();
+        assert_eq!(rust.file_extension(), "rs");
+        assert_eq!(rust.emit(&types, "User").unwrap(), crate::codegen::generate_code_with_types(&types).unwrap());
+        let schema = registry.get("json-schema").unwrap().emit(&types, "User").unwrap();
+        assert!(schema.contains("\"title\": \"User\""), "{}", schema);
+        let typescript = registry.get("ts").unwrap().emit_struct(&types.structs[0], &types).unwrap();
+        assert_eq!(typescript, "export interface User {\n  id: number;\n  name: string;\n}\n");
+        assert!(registry.get("protobuf").is_none());
+    }
+
+    #[test]
+    fn test_register_custom_emitter() {
+        let mut registry = EmitterRegistry::with_builtins();
+        registry.register(Box::new(PythonEmitter));
+        registry.register(Box::new(PythonEmitter));
+        assert_eq!(registry.names(), ["rust", "json-schema", "ts", "python"]);
+
+        let code = registry.get("python").unwrap().emit(&types(), "User").unwrap();
+        assert_eq!(code, "from dataclasses import dataclass\n\n@dataclass\nclass User:\n    id: object\n    name: object\n");
+    }
+}
@@ -0,0 +1,199 @@
+use crate::types::*;
+use std::net::IpAddr;
+
+const MIN_BASE64_LEN: usize = 16;
+
+pub fn detect_string_format(s: &str) -> Option<StringFormat> {
+    if is_uuid(s) {
+        Some(StringFormat::Uuid)
+    } else if is_rfc3339_datetime(s) {
+        Some(StringFormat::DateTime)
+    } else if is_iso_date(s) {
+        Some(StringFormat::Date)
+    } else if s.parse::<IpAddr>().is_ok() {
+        Some(StringFormat::IpAddr)
+    } else if is_url(s) {
+        Some(StringFormat::Url)
+    } else if is_base64(s) {
+        Some(StringFormat::Base64)
+    } else {
+        None
+    }
+}
+
+pub fn string_format_type_name(format: StringFormat) -> &'static str {
+    match format {
+        StringFormat::DateTime => "chrono::DateTime<chrono::Utc>",
+        StringFormat::Date => "chrono::NaiveDate",
+        StringFormat::Uuid => "uuid::Uuid",
+        StringFormat::Url => "url::Url",
+        StringFormat::IpAddr => "std::net::IpAddr",
+        StringFormat::Base64 => "Vec<u8>",
+        StringFormat::TomlDatetime => "toml::value::Datetime",
+    }
+}
+
+/// Returns the `#[serde(with = "...")]` path needed to (de)serialize a field of this
+/// type, for formats whose Rust type has no string representation of its own and for
+/// binary blobs, which would otherwise be read as sequences of numbers.
+pub fn serde_with_for_type(json_type: &JsonType, config: &GenerationConfig) -> Option<String> {
+    match json_type {
+        JsonType::Bytes => Some("serde_bytes".to_string()),
+        JsonType::Array(element) if **element == JsonType::Bytes => Some("serde_with::As::<Vec<serde_with::Bytes>>".to_string()),
+        _ => base64_adapter(json_type, config).map(|adapter| format!("serde_with::As::<{}>", adapter)),
+    }
+}
+
+fn base64_adapter(json_type: &JsonType, config: &GenerationConfig) -> Option<String> {
+    match json_type {
+        JsonType::String(StringStats { format: Some(StringFormat::Base64), declared, .. })
+            if *declared || config.string_formats.contains(&StringFormat::Base64) =>
+        {
+            Some("serde_with::base64::Base64".to_string())
+        }
+        JsonType::Array(element) => base64_adapter(element, config).map(|inner| format!("Vec<{}>", inner)),
+        _ => None,
+    }
+}
+
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+fn is_iso_date(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if !s.is_ascii() || bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) = (parse_digits(&s[0..4]), parse_digits(&s[5..7]), parse_digits(&s[8..10])) else {
+        return false;
+    };
+    year > 0 && (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+fn is_rfc3339_datetime(s: &str) -> bool {
+    if !s.is_ascii() || s.len() < 20 || !is_iso_date(&s[..10]) {
+        return false;
+    }
+    let rest = &s[10..];
+    if !rest.starts_with(['T', 't', ' ']) {
+        return false;
+    }
+    let time = &rest[1..];
+    if time.len() < 9 || !is_time_of_day(&time[..8]) {
+        return false;
+    }
+
+    let mut offset = &time[8..];
+    if let Some(fraction) = offset.strip_prefix('.') {
+        let digits = fraction.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return false;
+        }
+        offset = &fraction[digits..];
+    }
+
+    match offset {
+        "Z" | "z" => true,
+        _ => {
+            let bytes = offset.as_bytes();
+            bytes.len() == 6
+                && (bytes[0] == b'+' || bytes[0] == b'-')
+                && bytes[3] == b':'
+                && parse_digits(&offset[1..3]).is_some_and(|h| h < 24)
+                && parse_digits(&offset[4..6]).is_some_and(|m| m < 60)
+        }
+    }
+}
+
+fn is_time_of_day(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.len() != 8 || bytes[2] != b':' || bytes[5] != b':' {
+        return false;
+    }
+    matches!(
+        (parse_digits(&s[0..2]), parse_digits(&s[3..5]), parse_digits(&s[6..8])),
+        // Allow a leap second
+        (Some(h), Some(m), Some(sec)) if h < 24 && m < 60 && sec <= 60
+    )
+}
+
+fn parse_digits(s: &str) -> Option<u32> {
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_url(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once("://") else {
+        return false;
+    };
+    let mut scheme_chars = scheme.chars();
+    scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !rest.starts_with('/')
+        && !s.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+fn is_base64(s: &str) -> bool {
+    if s.len() < MIN_BASE64_LEN || !s.len().is_multiple_of(4) {
+        return false;
+    }
+    let body = s.trim_end_matches('=');
+    if s.len() - body.len() > 2 {
+        return false;
+    }
+    // Mixed case keeps hex digests and plain identifiers from being mistaken for blobs
+    body.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+        && body.chars().any(|c| c.is_ascii_uppercase())
+        && body.chars().any(|c| c.is_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_string_format() {
+        assert_eq!(detect_string_format("2024-03-01T12:30:00Z"), Some(StringFormat::DateTime));
+        assert_eq!(detect_string_format("2024-03-01T12:30:00.123+02:00"), Some(StringFormat::DateTime));
+        assert_eq!(detect_string_format("2024-03-01"), Some(StringFormat::Date));
+        assert_eq!(detect_string_format("550e8400-e29b-41d4-a716-446655440000"), Some(StringFormat::Uuid));
+        assert_eq!(detect_string_format("https://example.com/a?b=c"), Some(StringFormat::Url));
+        assert_eq!(detect_string_format("192.168.0.1"), Some(StringFormat::IpAddr));
+        assert_eq!(detect_string_format("::1"), Some(StringFormat::IpAddr));
+        assert_eq!(detect_string_format("SGVsbG8sIFdvcmxkIQ=="), Some(StringFormat::Base64));
+    }
+
+    #[test]
+    fn test_detect_plain_strings() {
+        assert_eq!(detect_string_format("hello world"), None);
+        assert_eq!(detect_string_format("2024-13-01"), None);
+        assert_eq!(detect_string_format("2024-03-01T25:00:00Z"), None);
+        assert_eq!(detect_string_format("not a url://"), None);
+        assert_eq!(detect_string_format("d41d8cd98f00b204e9800998ecf8427e"), None);
+    }
+
+    #[test]
+    fn test_serde_with_for_type() {
+        let config = GenerationConfig::with_all_string_formats();
+        let blob = JsonType::String(StringStats { format: Some(StringFormat::Base64), ..Default::default() });
+
+        assert_eq!(
+            serde_with_for_type(&blob, &config).as_deref(),
+            Some("serde_with::As::<serde_with::base64::Base64>")
+        );
+        assert_eq!(
+            serde_with_for_type(&JsonType::Array(Box::new(blob.clone())), &config).as_deref(),
+            Some("serde_with::As::<Vec<serde_with::base64::Base64>>")
+        );
+        assert_eq!(serde_with_for_type(&blob, &GenerationConfig::default()), None);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::analyzer::{analyze_document, merge_schemas};
+use crate::case::to_pascal_case;
+use crate::identifiers::sanitize_type_name;
+use crate::types::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// Path segment standing in for a numeric or UUID id in an endpoint template
+const ID_SEGMENT: &str = "{id}";
+
+/// An endpoint seen in a HAR capture: a method and a URL path template.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HarEndpoint {
+    pub method: String,  // Upper case, as recorded
+    pub path: String,    // Path with numeric and UUID segments replaced by `{id}`
+}
+
+impl HarEndpoint {
+    /// Base name of the endpoint's types, e.g. `GetUsersById` for `GET /users/{id}`.
+    pub fn type_name(&self) -> String {
+        let mut name = to_pascal_case(&self.method);
+        let segments: Vec<&str> = self.path.split('/').filter(|segment| !segment.is_empty()).collect();
+        if segments.is_empty() {
+            name.push_str("Root");
+        }
+        for segment in segments {
+            if segment == ID_SEGMENT {
+                name.push_str("ById");
+            } else {
+                name.push_str(&to_pascal_case(segment));
+            }
+        }
+        sanitize_type_name(&name)
+    }
+}
+
+/// Imports the JSON bodies of a HAR capture, grouped by endpoint: every request body and
+/// every successful (2xx) response body of a group is a sample of its `{Endpoint}Request` or
+/// `{Endpoint}Response` root, merged like any other samples. Error responses are left out,
+/// as they rarely share the shape of the success ones, and so are bodies that do not parse,
+/// which captures truncate or leave base64-encoded. Generate the roots together with
+/// `generate_rust_types_for_schemas`.
+pub fn import_har(har_text: &str, options: &AnalysisOptions) -> Result<Vec<JsonSchema>, Json2RustError> {
+    let har: Value = serde_json::from_str(har_text)?;
+    let entries = har
+        .pointer("/log/entries")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Json2RustError::InputParsing { format: "HAR", message: "missing log.entries".to_string() })?;
+
+    // Keyed by root name, so the output does not depend on the order requests were recorded in
+    let mut groups: BTreeMap<String, JsonSchema> = BTreeMap::new();
+    for entry in entries {
+        let request = &entry["request"];
+        let (Some(method), Some(url)) = (request["method"].as_str(), request["url"].as_str()) else {
+            continue;
+        };
+        let endpoint = HarEndpoint { method: method.to_ascii_uppercase(), path: path_template(url) };
+
+        let status = entry.pointer("/response/status").and_then(Value::as_u64).unwrap_or_default();
+        let bodies = [
+            ("Request", request.get("postData")),
+            ("Response", entry.pointer("/response/content").filter(|_| (200..300).contains(&status))),
+        ];
+        for (role, body) in bodies {
+            let Some(sample) = body.and_then(json_body) else { continue };
+            let name = format!("{}{}", endpoint.type_name(), role);
+            let schema = analyze_document(&sample, &name, options)?;
+            let merged = match groups.remove(&name) {
+                Some(existing) => merge_schemas(existing, schema)?,
+                None => schema,
+            };
+            groups.insert(name, merged);
+        }
+    }
+
+    Ok(groups.into_values().collect())
+}
+
+/// The JSON document in a HAR `postData` or response `content`, if it holds one.
+fn json_body(body: &Value) -> Option<Value> {
+    let mime_type = body["mimeType"].as_str()?;
+    if !mime_type.contains("json") || body.get("encoding").is_some() {
+        return None;
+    }
+    serde_json::from_str(body["text"].as_str()?).ok()
+}
+
+/// The path of `url` with numeric and UUID segments collapsed to `{id}`, so
+/// `https://api.example.com/users/42?expand=1` becomes `/users/{id}`.
+pub fn path_template(url: &str) -> String {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |start| &rest[start..]),
+        None => url,
+    };
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| if is_id(segment) { ID_SEGMENT } else { segment })
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+fn is_id(segment: &str) -> bool {
+    let is_numeric = segment.bytes().all(|b| b.is_ascii_digit());
+    let is_uuid = segment.len() == 36
+        && segment.char_indices().all(|(i, c)| if matches!(i, 8 | 13 | 18 | 23) { c == '-' } else { c.is_ascii_hexdigit() });
+    is_numeric || is_uuid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::*;
+
+    fn entry(method: &str, url: &str, request_body: Option<&str>, status: u64, response_body: &str) -> Value {
+        let mut entry = serde_json::json!({
+            "request": {"method": method, "url": url, "headers": []},
+            "response": {"status": status, "content": {"mimeType": "application/json; charset=utf-8", "text": response_body}}
+        });
+        if let Some(body) = request_body {
+            entry["request"]["postData"] = serde_json::json!({"mimeType": "application/json", "text": body});
+        }
+        entry
+    }
+
+    #[test]
+    fn test_path_template() {
+        assert_eq!(path_template("https://api.example.com/users/42?expand=1"), "/users/{id}");
+        assert_eq!(
+            path_template("http://localhost:8080/orgs/0b6f7e3c-9a1d-4c7e-8f2a-1d2e3f4a5b6c/members/7#top"),
+            "/orgs/{id}/members/{id}"
+        );
+        assert_eq!(path_template("https://api.example.com"), "/");
+        assert_eq!(path_template("https://api.example.com/v2/me"), "/v2/me");
+
+        let endpoint = HarEndpoint { method: "GET".to_string(), path: "/users/{id}".to_string() };
+        assert_eq!(endpoint.type_name(), "GetUsersById");
+        let endpoint = HarEndpoint { method: "DELETE".to_string(), path: "/".to_string() };
+        assert_eq!(endpoint.type_name(), "DeleteRoot");
+        let endpoint = HarEndpoint { method: "POST".to_string(), path: "/api/v1/order-items".to_string() };
+        assert_eq!(endpoint.type_name(), "PostApiV1OrderItems");
+    }
+
+    #[test]
+    fn test_import_groups_entries_by_endpoint() {
+        let har = serde_json::json!({"log": {"version": "1.2", "entries": [
+            entry("GET", "https://api.example.com/users/1", None, 200, r#"{"id": 1, "name": "Ann"}"#),
+            entry("POST", "https://api.example.com/users", Some(r#"{"name": "Bob"}"#), 201, r#"{"id": 2, "name": "Bob"}"#),
+            entry("GET", "https://api.example.com/users/2", None, 200, r#"{"id": 2, "name": "Bob", "email": "bob@example.com"}"#),
+            entry("GET", "https://api.example.com/users/3", None, 404, r#"{"error": "not found"}"#),
+            entry("GET", "https://api.example.com/users/4", None, 200, r#"{"id": 4, "na"#),
+        ]}});
+        let roots = import_har(&har.to_string(), &AnalysisOptions::default()).unwrap();
+        let names: Vec<&str> = roots.iter().map(|root| root.name.as_str()).collect();
+        assert_eq!(names, ["GetUsersByIdResponse", "PostUsersRequest", "PostUsersResponse"]);
+
+        let JsonType::Object(user) = &roots[0].json_type else { panic!("Expected object") };
+        assert_eq!(user.samples, 2);
+        assert!(!user.is_optional(&user.fields["id"]));
+        assert!(user.is_optional(&user.fields["email"]));
+        assert!(!user.fields.contains_key("error"));
+
+        let types = generate_rust_types_for_schemas(&roots, &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("pub struct GetUsersByIdResponse {"), "{}", code);
+        assert!(code.contains("pub struct PostUsersRequest {"), "{}", code);
+        assert!(code.contains("pub email: Option<String>,"), "{}", code);
+    }
+
+    #[test]
+    fn test_import_rejects_non_har() {
+        let error = import_har(r#"{"entries": []}"#, &AnalysisOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("missing log.entries"), "{}", error);
+    }
+}
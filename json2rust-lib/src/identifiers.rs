@@ -0,0 +1,188 @@
+use crate::case::{to_pascal_case, to_snake_case};
+use crate::types::*;
+use std::collections::HashSet;
+
+const FALLBACK_FIELD_NAME: &str = "field";
+const FALLBACK_TYPE_NAME: &str = "Empty";
+
+/// Strict, reserved and edition-specific keywords that cannot be used as plain identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that are not allowed as raw identifiers either.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Types generated code refers to, which a type generated under the same name would shadow.
+const STD_TYPE_NAMES: &[&str] = &["String", "Vec", "Option", "Result", "Box", "HashMap", "BTreeMap", "Value"];
+
+/// Symbols that carry meaning worth keeping in a name; any other symbol is a word break.
+const SYMBOL_WORDS: &[(char, &str)] = &[('+', "plus"), ('%', "percent"), ('&', "and")];
+
+/// Turns a JSON key into a snake_case field name that compiles: symbols become word breaks,
+/// accented letters are folded to ASCII, a leading digit gets a `_` prefix and keywords are
+/// escaped per `keyword_style`. Leading underscores, as in `_id`, are kept.
+pub fn sanitize_field_name(key: &str, keyword_style: KeywordStyle) -> String {
+    let words = spell_symbols(&transliterate(key));
+    let snake = to_snake_case(&words);
+    if snake.is_empty() {
+        return FALLBACK_FIELD_NAME.to_string();
+    }
+
+    let underscores = key.len() - key.trim_start_matches('_').len();
+    let mut name = "_".repeat(underscores) + &snake;
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    escape_keyword(name, keyword_style)
+}
+
+/// Sanitizes the keys of one object together, so keys that clean up to the same name (such
+/// as `userId` and `user_id`) get distinct fields. Keys that already are their own field
+/// name keep it; the rest are numbered in key order.
+pub fn sanitize_field_names<'a>(keys: impl IntoIterator<Item = &'a String>, keyword_style: KeywordStyle) -> Vec<String> {
+    let candidates: Vec<(&String, String)> = keys
+        .into_iter()
+        .map(|key| (key, sanitize_field_name(key, keyword_style)))
+        .collect();
+    let mut used: HashSet<String> = candidates
+        .iter()
+        .filter(|(key, name)| unraw(name) == key.as_str())
+        .map(|(_, name)| name.clone())
+        .collect();
+
+    candidates
+        .into_iter()
+        .map(|(key, name)| {
+            if unraw(&name) == key.as_str() {
+                return name;
+            }
+            let base = name.strip_suffix('_').unwrap_or(unraw(&name)).to_string();
+            let mut unique = name;
+            let mut counter = 1;
+            while !used.insert(unique.clone()) {
+                counter += 1;
+                unique = format!("{}_{}", base, counter);
+            }
+            unique
+        })
+        .collect()
+}
+
+/// Turns a JSON key or value into a PascalCase type or variant name that compiles.
+pub fn sanitize_type_name(raw: &str) -> String {
+    let name = to_pascal_case(&spell_symbols(&transliterate(raw)));
+    match name.chars().next() {
+        None => FALLBACK_TYPE_NAME.to_string(),
+        Some(first) if first.is_ascii_digit() => format!("V{}", name),
+        _ if name == "Self" => "SelfValue".to_string(),
+        _ if STD_TYPE_NAMES.contains(&name.as_str()) => format!("{}Type", name),
+        _ => name,
+    }
+}
+
+/// The name serde sees for a field, without any `r#` prefix.
+pub fn unraw(name: &str) -> &str {
+    name.strip_prefix("r#").unwrap_or(name)
+}
+
+fn spell_symbols(s: &str) -> String {
+    s.chars()
+        .map(|c| match SYMBOL_WORDS.iter().find(|(symbol, _)| *symbol == c) {
+            Some((_, word)) => format!(" {} ", word),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+fn escape_keyword(name: String, keyword_style: KeywordStyle) -> String {
+    if !RUST_KEYWORDS.contains(&name.as_str()) {
+        return name;
+    }
+    match keyword_style {
+        KeywordStyle::Raw if !NON_RAW_KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        _ => format!("{}_", name),
+    }
+}
+
+/// Folds common accented Latin letters to ASCII; other non-ASCII characters are left for the
+/// callers to drop.
+fn transliterate(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        let folded = match c.to_lowercase().next().unwrap_or(c) {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+            'æ' => "ae",
+            'ç' => "c",
+            'è' | 'é' | 'ê' | 'ë' => "e",
+            'ì' | 'í' | 'î' | 'ï' => "i",
+            'ñ' => "n",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+            'œ' => "oe",
+            'ß' => "ss",
+            'ù' | 'ú' | 'û' | 'ü' => "u",
+            'ý' | 'ÿ' => "y",
+            _ => {
+                result.push(c);
+                continue;
+            }
+        };
+        if c.is_uppercase() {
+            result.push_str(&folded.to_uppercase());
+        } else {
+            result.push_str(folded);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_field_name() {
+        let raw = KeywordStyle::Raw;
+        assert_eq!(sanitize_field_name("type", raw), "r#type");
+        assert_eq!(sanitize_field_name("match", KeywordStyle::Suffix), "match_");
+        assert_eq!(sanitize_field_name("self", raw), "self_");
+        assert_eq!(sanitize_field_name("1st_place", raw), "_1st_place");
+        assert_eq!(sanitize_field_name("@id", raw), "id");
+        assert_eq!(sanitize_field_name("_id", raw), "_id");
+        assert_eq!(sanitize_field_name("$ref", raw), "r#ref");
+        assert_eq!(sanitize_field_name("content-type", raw), "content_type");
+        assert_eq!(sanitize_field_name("a.b c", raw), "a_b_c");
+        assert_eq!(sanitize_field_name("C++", raw), "c_plus_plus");
+        assert_eq!(sanitize_field_name("café", raw), "cafe");
+        assert_eq!(sanitize_field_name("名前", raw), "field");
+        assert_eq!(sanitize_field_name("_", raw), "field");
+        assert_eq!(sanitize_field_name("__typename", raw), "__typename");
+        assert_eq!(sanitize_field_name("APIKey", raw), "api_key");
+    }
+
+    #[test]
+    fn test_sanitize_field_names_disambiguates() {
+        let keys: Vec<String> = ["userId", "user_id", "user-id", "type", "@type"].iter().map(|k| k.to_string()).collect();
+        assert_eq!(
+            sanitize_field_names(&keys, KeywordStyle::Raw),
+            ["user_id_2", "user_id", "user_id_3", "r#type", "type_2"]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_type_name() {
+        assert_eq!(sanitize_type_name("content-type"), "ContentType");
+        assert_eq!(sanitize_type_name("@id"), "Id");
+        assert_eq!(sanitize_type_name("1st_place"), "V1stPlace");
+        assert_eq!(sanitize_type_name("self"), "SelfValue");
+        assert_eq!(sanitize_type_name("string"), "StringType");
+        assert_eq!(sanitize_type_name("hash_map"), "HashMapType");
+        assert_eq!(sanitize_type_name("value"), "ValueType");
+        assert_eq!(sanitize_type_name("Ünïcode"), "Unicode");
+        assert_eq!(sanitize_type_name("!!"), "Empty");
+    }
+}
@@ -0,0 +1,133 @@
+use crate::case::split_words;
+use std::collections::HashMap;
+
+/// Plurals that no suffix rule gets right.
+const IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("aliases", "alias"),
+    ("analyses", "analysis"),
+    ("axes", "axis"),
+    ("bonuses", "bonus"),
+    ("buses", "bus"),
+    ("caches", "cache"),
+    ("campuses", "campus"),
+    ("children", "child"),
+    ("cookies", "cookie"),
+    ("crises", "crisis"),
+    ("criteria", "criterion"),
+    ("feet", "foot"),
+    ("geese", "goose"),
+    ("halves", "half"),
+    ("heroes", "hero"),
+    ("indices", "index"),
+    ("knives", "knife"),
+    ("leaves", "leaf"),
+    ("lives", "life"),
+    ("matrices", "matrix"),
+    ("men", "man"),
+    ("mice", "mouse"),
+    ("movies", "movie"),
+    ("oxen", "ox"),
+    ("people", "person"),
+    ("phenomena", "phenomenon"),
+    ("potatoes", "potato"),
+    ("quizzes", "quiz"),
+    ("statuses", "status"),
+    ("teeth", "tooth"),
+    ("theses", "thesis"),
+    ("vertices", "vertex"),
+    ("viruses", "virus"),
+    ("wives", "wife"),
+    ("women", "woman"),
+];
+
+/// Words whose plural is the word itself.
+const UNCOUNTABLE: &[&str] = &[
+    "analytics", "content", "data", "equipment", "feedback", "fish", "hardware", "info",
+    "information", "media", "metadata", "money", "news", "series", "sheep", "software",
+    "species", "traffic",
+];
+
+/// Returns the singular of an English plural noun, or `None` when the word is uncountable or
+/// does not look plural. `overrides` maps lowercase plurals to singulars and wins over the
+/// built-in rules.
+pub fn singularize(word: &str, overrides: &HashMap<String, String>) -> Option<String> {
+    let lower = word.to_ascii_lowercase();
+    if let Some(singular) = overrides.get(&lower) {
+        return Some(singular.clone());
+    }
+    if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(plural, _)| *plural == lower) {
+        return Some(singular.to_string());
+    }
+    if UNCOUNTABLE.contains(&lower.as_str()) || lower.len() < 3 {
+        return None;
+    }
+
+    let singular = if let Some(stem) = lower.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "xes", "ches", "shes", "zzes"].iter().any(|suffix| lower.ends_with(suffix)) {
+        lower[..lower.len() - 2].to_string()
+    } else if lower.ends_with('s') && !["ss", "us", "is"].iter().any(|suffix| lower.ends_with(suffix)) {
+        lower[..lower.len() - 1].to_string()
+    } else {
+        return None;
+    };
+    Some(singular)
+}
+
+/// Names the element type of an array from the array's type name by singularizing its last
+/// word (`UserAddresses` → `UserAddress`), falling back to an `Item` suffix (`DataItem`).
+pub fn element_type_name(array_name: &str, overrides: &HashMap<String, String>) -> String {
+    let singular = split_words(array_name).last().and_then(|last| {
+        let prefix = array_name.strip_suffix(last)?;
+        let singular = singularize(last, overrides)?;
+        let mut chars = singular.chars();
+        let first = chars.next()?;
+        let first = if last.starts_with(|c: char| c.is_ascii_uppercase()) { first.to_ascii_uppercase() } else { first };
+        Some(format!("{}{}{}", prefix, first, chars.as_str()))
+    });
+    singular.unwrap_or_else(|| format!("{}Item", array_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_singularize() {
+        let none = HashMap::new();
+        let cases = [
+            ("users", "user"),
+            ("categories", "category"),
+            ("addresses", "address"),
+            ("boxes", "box"),
+            ("matches", "match"),
+            ("wishes", "wish"),
+            ("children", "child"),
+            ("people", "person"),
+            ("statuses", "status"),
+            ("responses", "response"),
+            ("archives", "archive"),
+            ("Leaves", "leaf"),
+        ];
+        for (plural, singular) in cases {
+            assert_eq!(singularize(plural, &none).as_deref(), Some(singular), "{}", plural);
+        }
+        for word in ["data", "news", "status", "class", "analysis", "log", "metadata"] {
+            assert_eq!(singularize(word, &none), None, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_element_type_name() {
+        let none = HashMap::new();
+        assert_eq!(element_type_name("Addresses", &none), "Address");
+        assert_eq!(element_type_name("UserAddresses", &none), "UserAddress");
+        assert_eq!(element_type_name("Children", &none), "Child");
+        assert_eq!(element_type_name("Data", &none), "DataItem");
+        assert_eq!(element_type_name("Status", &none), "StatusItem");
+
+        let overrides = HashMap::from([("data".to_string(), "record".to_string())]);
+        assert_eq!(element_type_name("Data", &overrides), "Record");
+        assert_eq!(element_type_name("SensorData", &overrides), "SensorRecord");
+    }
+}
@@ -0,0 +1,355 @@
+use crate::analyzer::{analyze_document, empty_schema, merge_schemas};
+use crate::json_schema::import_json_schema;
+use crate::ndjson::analyze_ndjson;
+use crate::streaming::{analyze_deserializer, analyze_deserializer_with_widths, analyze_json_reader, YAML_BINARY_TAG, YAML_TIMESTAMP_TAG};
+use crate::types::*;
+use serde_json::{Map, Number, Value};
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read};
+
+/// Analyzes a document in `format` from `reader` into the same schema `analyze_json` would
+/// build for the equivalent JSON. JSON, JSON Lines, YAML and MessagePack are folded in as
+/// they are parsed; TOML, JSON5 and CBOR are read into memory first because their parsers
+/// need the whole input.
+///
+/// The binary formats keep what JSON cannot express: blobs become `JsonType::Bytes`,
+/// integers keep the width they were encoded with, and integer or boolean keys make a map.
+///
+/// A JSON Schema is not a sample but a description of them: it is imported with
+/// `import_json_schema` rather than analyzed, and `options` do not apply.
+pub fn analyze_reader<R: Read>(
+    mut reader: R,
+    format: InputFormat,
+    root_name: &str,
+    options: &AnalysisOptions,
+) -> Result<JsonSchema, Json2RustError> {
+    let parse_error = |error: &dyn Display| Json2RustError::InputParsing { format: format.name(), message: error.to_string() };
+    match format {
+        InputFormat::Json => analyze_json_reader(reader, root_name, options),
+        InputFormat::JsonLines => analyze_ndjson(BufReader::new(reader), root_name, options),
+        InputFormat::Yaml => {
+            // serde_yaml reads the whole stream up front anyway
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            let text = localize_yaml_tags(&text);
+            // Every document in a multi-document stream is one more sample of the root type
+            let mut merged: Option<JsonSchema> = None;
+            for document in serde_yaml::Deserializer::from_str(&text) {
+                let schema = analyze_deserializer(document, root_name, options, |error| parse_error(&error))?;
+                merged = Some(merge_sample(merged, schema)?);
+            }
+            Ok(merged.unwrap_or_else(|| empty_schema(root_name)))
+        }
+        InputFormat::Toml => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            analyze_deserializer(toml::Deserializer::new(&text), root_name, options, |error| parse_error(&error))
+        }
+        InputFormat::Json5 => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            let mut deserializer = json5::Deserializer::from_str(&text).map_err(|error| parse_error(&error))?;
+            analyze_deserializer(&mut deserializer, root_name, options, |error| parse_error(&error))
+        }
+        InputFormat::Csv => {
+            let rows = read_csv_rows(reader).map_err(|error| parse_error(&error))?;
+            analyze_document(&Value::Array(rows), root_name, options)
+        }
+        InputFormat::MessagePack => {
+            // Concatenated values are samples of the root type, like YAML documents
+            let mut merged: Option<JsonSchema> = None;
+            let mut deserializer = rmp_serde::Deserializer::new(BufReader::new(reader));
+            while !deserializer.get_mut().fill_buf()?.is_empty() {
+                let schema = analyze_deserializer_with_widths(&mut deserializer, root_name, options, true, |error| parse_error(&error))?;
+                merged = Some(merge_sample(merged, schema)?);
+            }
+            Ok(merged.unwrap_or_else(|| empty_schema(root_name)))
+        }
+        InputFormat::Cbor => {
+            // A CBOR sequence (RFC 8742) is read the same way
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            let mut merged: Option<JsonSchema> = None;
+            let mut deserializer = serde_cbor::Deserializer::from_slice(&bytes);
+            while deserializer.byte_offset() < bytes.len() {
+                let schema = analyze_deserializer_with_widths(&mut deserializer, root_name, options, true, |error| parse_error(&error))?;
+                merged = Some(merge_sample(merged, schema)?);
+            }
+            Ok(merged.unwrap_or_else(|| empty_schema(root_name)))
+        }
+        InputFormat::JsonSchema => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            import_json_schema(&text, root_name)
+        }
+    }
+}
+
+fn merge_sample(merged: Option<JsonSchema>, schema: JsonSchema) -> Result<JsonSchema, Json2RustError> {
+    match merged {
+        Some(existing) => merge_schemas(existing, schema),
+        None => Ok(schema),
+    }
+}
+
+/// Renames the core tags serde_yaml would hand over as plain strings to local tags, which
+/// reach the analyzer with their name. `!!str`, `!!int`, `!!float`, `!!bool` and `!!null` are
+/// resolved by serde_yaml itself. A string that spells out `!!binary` is renamed too, which
+/// changes its text but never its type.
+fn localize_yaml_tags(text: &str) -> String {
+    text.replace("!!binary", &format!("!{}", YAML_BINARY_TAG))
+        .replace("!!timestamp", &format!("!{}", YAML_TIMESTAMP_TAG))
+}
+
+/// `analyze_reader` for input that is already in memory.
+pub fn analyze_text(text: &str, format: InputFormat, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    analyze_reader(text.as_bytes(), format, root_name, options)
+}
+
+/// Reads every CSV record as an object keyed by the header row. Each column gets one type
+/// across all rows, falling back to strings when its cells disagree, and short records
+/// simply lack their trailing columns, which then come out optional.
+fn read_csv_rows<R: Read>(reader: R) -> Result<Vec<Value>, csv::Error> {
+    let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers: Vec<String> = csv_reader
+        .headers()?
+        .iter()
+        .enumerate()
+        .map(|(index, header)| match header.trim() {
+            "" => format!("column_{}", index + 1),
+            header => header.to_string(),
+        })
+        .collect();
+    let records = csv_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let mut kinds = vec![CellKind::Empty; headers.len()];
+    for record in &records {
+        for (kind, cell) in kinds.iter_mut().zip(record.iter()) {
+            *kind = kind.widen(CellKind::of(cell));
+        }
+    }
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            let cells = record.iter().zip(&kinds).map(|(cell, kind)| kind.value(cell));
+            Value::Object(headers.iter().cloned().zip(cells).collect::<Map<String, Value>>())
+        })
+        .collect();
+    Ok(rows)
+}
+
+/// What a CSV cell looks like, ordered so a column can widen from one to the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CellKind {
+    Empty,
+    Bool,
+    Integer,
+    Float,
+    Text,
+}
+
+impl CellKind {
+    /// A leading zero (`007`, `02134`) marks a code rather than a number, and `inf` or
+    /// `NaN` are never meant as numbers even though Rust parses them.
+    fn of(cell: &str) -> Self {
+        let cell = cell.trim();
+        if cell.is_empty() {
+            return CellKind::Empty;
+        }
+        if matches!(cell, "true" | "True" | "TRUE" | "false" | "False" | "FALSE") {
+            return CellKind::Bool;
+        }
+        let digits = cell.strip_prefix('-').unwrap_or(cell);
+        let is_code = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+        let is_numeric = digits.starts_with(|c: char| c.is_ascii_digit())
+            && cell.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+        if !is_numeric || is_code {
+            CellKind::Text
+        } else if cell.parse::<i64>().is_ok() || cell.parse::<u64>().is_ok() {
+            CellKind::Integer
+        } else if cell.parse::<f64>().is_ok() {
+            CellKind::Float
+        } else {
+            CellKind::Text
+        }
+    }
+
+    fn widen(self, other: CellKind) -> CellKind {
+        match (self, other) {
+            (CellKind::Empty, kind) | (kind, CellKind::Empty) => kind,
+            (a, b) if a == b => a,
+            (CellKind::Integer, CellKind::Float) | (CellKind::Float, CellKind::Integer) => CellKind::Float,
+            _ => CellKind::Text,
+        }
+    }
+
+    fn value(self, cell: &str) -> Value {
+        let trimmed = cell.trim();
+        if trimmed.is_empty() {
+            return Value::Null;
+        }
+        let value = match self {
+            CellKind::Bool => Some(Value::Bool(trimmed.eq_ignore_ascii_case("true"))),
+            CellKind::Integer => trimmed.parse::<i64>().map(Value::from).or_else(|_| trimmed.parse::<u64>().map(Value::from)).ok(),
+            CellKind::Float => trimmed.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number),
+            CellKind::Empty | CellKind::Text => None,
+        };
+        value.unwrap_or_else(|| Value::String(cell.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::analyze_json;
+
+    fn object(schema: &JsonSchema) -> &ObjectStats {
+        match &schema.json_type {
+            JsonType::Object(object) => object,
+            JsonType::Array(element) => match element.as_ref() {
+                JsonType::Object(object) => object,
+                other => panic!("Expected array of objects, got {:?}", other),
+            },
+            other => panic!("Expected object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_formats_match_json() {
+        let json = analyze_json(r#"{"name": "app", "port": 8080, "debug": false, "tags": ["a", "b"], "db": {"url": "postgres://db"}}"#, "Config").unwrap();
+
+        let yaml = "name: app\nport: 8080\ndebug: false\ntags: [a, b]\ndb:\n  url: postgres://db\n";
+        let toml = "name = \"app\"\nport = 8080\ndebug = false\ntags = [\"a\", \"b\"]\n\n[db]\nurl = \"postgres://db\"\n";
+        let json5 = "// service config\n{name: 'app', port: 8080, debug: false, tags: ['a', 'b',], db: {url: \"postgres://db\"},}";
+        for (text, format) in [(yaml, InputFormat::Yaml), (toml, InputFormat::Toml), (json5, InputFormat::Json5)] {
+            let schema = analyze_text(text, format, "Config", &AnalysisOptions::default()).unwrap();
+            assert_eq!(schema, json, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_toml_datetimes_are_typed() {
+        let toml = "created = 1979-05-27T07:32:00Z\nlocal = 1979-05-27T07:32:00\nday = 1979-05-27\nat = 07:32:00\n";
+        let schema = analyze_text(toml, InputFormat::Toml, "Release", &AnalysisOptions::default()).unwrap();
+        for field in ["created", "local", "day", "at"] {
+            let Some(JsonType::String(stats)) = object(&schema).field_type(field) else { panic!("Expected string for {}", field) };
+            assert_eq!(stats.format, Some(StringFormat::TomlDatetime), "{}", field);
+        }
+    }
+
+    #[test]
+    fn test_yaml_tags_and_documents() {
+        let yaml = "id: !!str 42\ncount: !!int \"7\"\nwhen: !!timestamp 2001-12-14T21:59:43.10Z\nref: !Ref other\n---\nid: \"43\"\ncount: 8\nwhen: 2002-01-01T00:00:00Z\nref: another\nextra: true\n";
+        let schema = analyze_text(yaml, InputFormat::Yaml, "Doc", &AnalysisOptions::default()).unwrap();
+        let object = object(&schema);
+
+        assert_eq!(object.samples, 2);
+        assert!(matches!(object.field_type("id"), Some(JsonType::String(_))));
+        assert!(matches!(object.field_type("count"), Some(JsonType::Integer { min: 7, max: 8 })));
+        let Some(JsonType::String(when)) = object.field_type("when") else { panic!("Expected string") };
+        assert_eq!(when.format, Some(StringFormat::DateTime));
+        assert!(matches!(object.field_type("ref"), Some(JsonType::String(_))));
+        assert!(object.is_optional(&object.fields["extra"]));
+    }
+
+    #[test]
+    fn test_yaml_core_tags_are_typed() {
+        let yaml = "blob: !!binary aGVsbG8gd29ybGQ=\nat: !!timestamp 2001-12-14T21:59:43.10Z\nday: !!timestamp 2002-12-14\nlocal: !!timestamp 2001-12-14 21:59:43.10 -5\nzip: !!str 02134\nratio: !!float 1\ncount: !!int \"7\"\n";
+        let schema = analyze_text(yaml, InputFormat::Yaml, "Doc", &AnalysisOptions::default()).unwrap();
+        let types = crate::codegen::generate_rust_types_with_strategy(&schema, &[], &MergeStrategy::Optional).unwrap();
+        let code = crate::codegen::generate_code_with_types(&types).unwrap();
+
+        assert!(code.contains("#[serde(with = \"serde_with::As::<serde_with::base64::Base64>\")]\n    pub blob: Vec<u8>,"), "{}", code);
+        assert!(code.contains("pub at: chrono::DateTime<chrono::Utc>,"), "{}", code);
+        assert!(code.contains("pub day: chrono::NaiveDate,"), "{}", code);
+        assert!(code.contains("pub local: String,"), "{}", code);
+        assert!(code.contains("pub zip: String,"), "{}", code);
+        assert!(code.contains("pub ratio: f64,"), "{}", code);
+        assert!(code.contains("pub count: u32,"), "{}", code);
+    }
+
+    #[test]
+    fn test_non_finite_floats_are_floats() {
+        let json5 = "{low: -Infinity, high: Infinity, missing: NaN}";
+        let yaml = "low: -.inf\nhigh: .inf\nmissing: .nan\n";
+        for (text, format) in [(json5, InputFormat::Json5), (yaml, InputFormat::Yaml)] {
+            let schema = analyze_text(text, format, "Range", &AnalysisOptions::default()).unwrap();
+            for field in ["low", "high", "missing"] {
+                assert!(matches!(object(&schema).field_type(field), Some(JsonType::Float { .. })), "{:?} {}", format, field);
+            }
+        }
+    }
+
+    #[test]
+    fn test_csv_columns_are_typed_across_rows() {
+        let csv = "id,name,score,active,zip,note\n1,Ann,9.5,true,02134,\n2,Bob,7,false,10001,late\n";
+        let schema = analyze_text(csv, InputFormat::Csv, "Row", &AnalysisOptions::default()).unwrap();
+        let object = object(&schema);
+
+        assert!(matches!(object.field_type("id"), Some(JsonType::Integer { min: 1, max: 2 })));
+        assert!(matches!(object.field_type("score"), Some(JsonType::Float { .. })));
+        assert_eq!(object.field_type("active"), Some(&JsonType::Boolean));
+        assert!(matches!(object.field_type("zip"), Some(JsonType::String(_))));
+        assert_eq!(CellKind::of("1e3").widen(CellKind::of("")), CellKind::Float);
+        assert_eq!(CellKind::of("NaN"), CellKind::Text);
+        assert!(object.is_optional(&object.fields["note"]));
+    }
+
+    #[derive(serde::Serialize)]
+    struct Reading {
+        id: u32,
+        delta: i64,
+        payload: serde_bytes::ByteBuf,
+        labels: std::collections::BTreeMap<i64, String>,
+    }
+
+    fn readings() -> [Reading; 2] {
+        let reading = |id, delta| Reading {
+            id,
+            delta,
+            payload: serde_bytes::ByteBuf::from(vec![0xde, 0xad]),
+            labels: [(1, "low".to_string()), (2, "high".to_string())].into(),
+        };
+        [reading(7, -3), reading(300, 100)]
+    }
+
+    #[test]
+    fn test_binary_formats_keep_bytes_widths_and_keys() {
+        let msgpack: Vec<u8> = readings().iter().flat_map(|reading| rmp_serde::to_vec_named(reading).unwrap()).collect();
+        let cbor: Vec<u8> = readings().iter().flat_map(|reading| serde_cbor::to_vec(reading).unwrap()).collect();
+        let options = AnalysisOptions::default();
+        let schema = analyze_reader(msgpack.as_slice(), InputFormat::MessagePack, "Reading", &options).unwrap();
+        assert_eq!(analyze_reader(cbor.as_slice(), InputFormat::Cbor, "Reading", &options).unwrap(), schema);
+
+        let object = object(&schema);
+        assert_eq!(object.samples, 2);
+        // Both encoders write each integer in the narrowest width that holds it, so 7 and 300
+        // are a u8 and a u16, and -3 and 100 an i8 and a u8
+        assert_eq!(object.field_type("id"), Some(&JsonType::FixedInt { signed: false, bits: 16 }));
+        assert_eq!(object.field_type("delta"), Some(&JsonType::FixedInt { signed: true, bits: 16 }));
+        assert_eq!(object.field_type("payload"), Some(&JsonType::Bytes));
+        let Some(JsonType::Map(MapKey::Integer, value_type)) = object.field_type("labels") else { panic!("Expected integer-keyed map") };
+        assert!(matches!(value_type.as_ref(), JsonType::String(_)));
+
+        let types = crate::codegen::generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = crate::codegen::generate_code_with_types(&types).unwrap();
+        assert!(code.contains("pub id: u16,"), "{}", code);
+        assert!(code.contains("pub delta: i16,"), "{}", code);
+        assert!(code.contains("#[serde(with = \"serde_bytes\")]\n    pub payload: Vec<u8>,"), "{}", code);
+        assert!(code.contains("pub labels: std::collections::HashMap<i64, String>,"), "{}", code);
+    }
+
+    #[test]
+    fn test_format_errors_name_the_format() {
+        let error = analyze_text("a = ", InputFormat::Toml, "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("TOML parsing error"), "{}", error);
+        assert_eq!(InputFormat::from_path("config/app.YML"), Some(InputFormat::Yaml));
+        assert_eq!(InputFormat::from_path("fixture.jsonc"), Some(InputFormat::Json5));
+        assert_eq!(InputFormat::from_path("README"), None);
+        assert_eq!(InputFormat::from_path("dump.mpk"), Some(InputFormat::MessagePack));
+
+        let error = analyze_reader(&[0xc1][..], InputFormat::MessagePack, "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("MessagePack parsing error"), "{}", error);
+    }
+}
@@ -0,0 +1,594 @@
+use crate::analyzer::{add_tagged_variant, analyze_json_value, merge_types, type_kind};
+use crate::identifiers::sanitize_type_name;
+use crate::types::*;
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Imports a JSON Schema document (draft 7 or 2020-12) as the schema the analyzer would
+/// infer from samples of it, so it goes through the same code generation and existing-struct
+/// merging. Definitions reached through `$ref` keep their names, and declared formats and
+/// string enums are always typed, whatever the `GenerationConfig` says about inferred ones.
+pub fn import_json_schema(schema_text: &str, root_name: &str) -> Result<JsonSchema, Json2RustError> {
+    let document: Value = serde_json::from_str(schema_text)?;
+    import_json_schema_value(&document, root_name)
+}
+
+/// `import_json_schema` for a document that is already parsed.
+pub fn import_json_schema_value(document: &Value, root_name: &str) -> Result<JsonSchema, Json2RustError> {
+    let mut importer = SchemaImporter::new(document, root_name);
+    let imported = importer.import(document, "#")?;
+    Ok(JsonSchema {
+        name: root_name.to_string(),
+        json_type: imported.json_type,
+        optional: imported.nullable,
+    })
+}
+
+/// A schema converted to the analyzer's model, plus whether it also accepts null, which
+/// the model only records on the field holding the value.
+#[derive(Debug, Clone)]
+pub(crate) struct Imported {
+    pub json_type: JsonType,
+    pub nullable: bool,
+}
+
+impl Imported {
+    fn of(json_type: JsonType) -> Self {
+        Self { json_type, nullable: false }
+    }
+}
+
+/// Converts schemas of one document, resolving `$ref`s against it. References are
+/// converted once and shared, and a reference met again while it is being converted
+/// becomes a `Recursive` back-reference.
+pub(crate) struct SchemaImporter<'a> {
+    document: &'a Value,
+    root_name: String,                    // Type name for `#`, the document itself
+    resolving: Vec<String>,               // References being converted, innermost last
+    resolved: HashMap<String, Imported>,  // References converted so far
+}
+
+impl<'a> SchemaImporter<'a> {
+    pub(crate) fn new(document: &'a Value, root_name: &str) -> Self {
+        Self {
+            document,
+            root_name: root_name.to_string(),
+            resolving: Vec::new(),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Converts `schema`, found at `pointer` in the document, for error messages.
+    pub(crate) fn import(&mut self, schema: &Value, pointer: &str) -> Result<Imported, Json2RustError> {
+        let mut imported = self.import_keywords(schema, pointer)?;
+        // OpenAPI 3.0 spells `"type": [..., "null"]` this way
+        if schema.get("nullable") == Some(&Value::Bool(true)) {
+            imported.nullable = true;
+        }
+        Ok(imported)
+    }
+
+    fn import_keywords(&mut self, schema: &Value, pointer: &str) -> Result<Imported, Json2RustError> {
+        let object = match schema {
+            Value::Object(object) => object,
+            // `true` accepts anything and `false` nothing; neither says what to generate
+            Value::Bool(_) => return Ok(Imported::of(JsonType::Null)),
+            _ => return Err(invalid(pointer, "expected a schema object")),
+        };
+
+        if let Some(reference) = object.get("$ref") {
+            let reference = reference.as_str().ok_or_else(|| invalid(pointer, "`$ref` must be a string"))?;
+            return self.import_ref(reference, pointer);
+        }
+        if let Some(members) = object.get("allOf") {
+            return self.import_all_of(object, members, pointer);
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(members) = object.get(keyword) {
+                let schemas = members.as_array().ok_or_else(|| invalid(pointer, &format!("`{}` must be an array", keyword)))?;
+                let members = schemas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, member)| self.import(member, &format!("{}/{}/{}", pointer, keyword, index)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some(discriminator) = object.get("discriminator") {
+                    if let Some(tagged) = discriminated_union(discriminator, schemas, &members)? {
+                        let nullable = members.iter().any(|member| member.nullable);
+                        return Ok(Imported { json_type: JsonType::Tagged(tagged), nullable });
+                    }
+                }
+                return union_of(members);
+            }
+        }
+        if let Some(values) = object.get("enum") {
+            let values = values.as_array().ok_or_else(|| invalid(pointer, "`enum` must be an array"))?;
+            return import_enum(values);
+        }
+        if let Some(value) = object.get("const") {
+            return import_enum(std::slice::from_ref(value));
+        }
+
+        match object.get("type") {
+            Some(Value::String(type_name)) => self.import_type(type_name, object, pointer),
+            Some(Value::Array(type_names)) => {
+                let mut members = Vec::new();
+                for type_name in type_names {
+                    let type_name = type_name.as_str().ok_or_else(|| invalid(pointer, "`type` entries must be strings"))?;
+                    members.push(self.import_type(type_name, object, pointer)?);
+                }
+                union_of(members)
+            }
+            Some(_) => Err(invalid(pointer, "`type` must be a string or an array of strings")),
+            None if ["properties", "additionalProperties", "patternProperties"].iter().any(|key| object.contains_key(*key)) => {
+                self.import_type("object", object, pointer)
+            }
+            None if object.contains_key("items") || object.contains_key("prefixItems") => self.import_type("array", object, pointer),
+            None => Ok(Imported::of(JsonType::Null)),
+        }
+    }
+
+    fn import_type(&mut self, type_name: &str, object: &Map<String, Value>, pointer: &str) -> Result<Imported, Json2RustError> {
+        let json_type = match type_name {
+            "object" => return self.import_object(object, pointer),
+            "array" => return self.import_array(object, pointer),
+            "null" => return Ok(Imported { json_type: JsonType::Null, nullable: true }),
+            "string" => JsonType::String(StringStats { format: string_format(object), values: None, samples: 1, declared: true }),
+            "integer" => integer_type(object),
+            "number" => JsonType::Float {
+                min: object.get("minimum").and_then(Value::as_f64).unwrap_or(f64::MIN),
+                max: object.get("maximum").and_then(Value::as_f64).unwrap_or(f64::MAX),
+            },
+            "boolean" => JsonType::Boolean,
+            other => return Err(invalid(pointer, &format!("unknown type `{}`", other))),
+        };
+        Ok(Imported::of(json_type))
+    }
+
+    fn import_object(&mut self, object: &Map<String, Value>, pointer: &str) -> Result<Imported, Json2RustError> {
+        let properties = object.get("properties").and_then(Value::as_object).filter(|properties| !properties.is_empty());
+        let Some(properties) = properties else {
+            // Without declared properties the object is a dictionary
+            let value_type = if let Some(values @ Value::Object(_)) = object.get("additionalProperties") {
+                self.import(values, &format!("{}/additionalProperties", pointer))?.json_type
+            } else if let Some((pattern, values)) = object.get("patternProperties").and_then(Value::as_object).and_then(|patterns| patterns.iter().next()) {
+                self.import(values, &format!("{}/patternProperties/{}", pointer, escape_pointer(pattern)))?.json_type
+            } else {
+                JsonType::Null
+            };
+            return Ok(Imported::of(JsonType::Map(MapKey::String, Box::new(value_type))));
+        };
+
+        let required = required_keys(object);
+        let mut fields = IndexMap::new();
+        for (key, property) in properties {
+            let imported = self.import(property, &format!("{}/properties/{}", pointer, escape_pointer(key)))?;
+            let field = FieldStats {
+                json_type: imported.json_type,
+                present: usize::from(required.contains(&key.as_str())),
+                nulls: usize::from(imported.nullable),
+            };
+            fields.insert(key.clone(), field);
+        }
+        Ok(Imported::of(JsonType::Object(ObjectStats { fields, samples: 1 })))
+    }
+
+    fn import_array(&mut self, object: &Map<String, Value>, pointer: &str) -> Result<Imported, Json2RustError> {
+        // Positional items: `prefixItems` in 2020-12, an `items` array in draft 7
+        let positional = ["prefixItems", "items"].into_iter().find_map(|keyword| match object.get(keyword) {
+            Some(Value::Array(items)) => Some((keyword, items)),
+            _ => None,
+        });
+        if let Some((keyword, items)) = positional {
+            let items = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| Ok(self.import(item, &format!("{}/{}/{}", pointer, keyword, index))?.json_type))
+                .collect::<Result<Vec<_>, Json2RustError>>()?;
+            return Ok(Imported::of(JsonType::Tuple(items)));
+        }
+
+        let element_type = match object.get("items") {
+            Some(items) => self.import(items, &format!("{}/items", pointer))?.json_type,
+            None => JsonType::Null,
+        };
+        Ok(Imported::of(JsonType::Array(Box::new(element_type))))
+    }
+
+    /// Combines the parts of an `allOf` into one object, with the keywords next to it
+    /// (usually extra `properties` or `required`) as one more part.
+    fn import_all_of(&mut self, object: &Map<String, Value>, members: &Value, pointer: &str) -> Result<Imported, Json2RustError> {
+        let members = members.as_array().ok_or_else(|| invalid(pointer, "`allOf` must be an array"))?;
+        let mut parts = Vec::new();
+        for (index, member) in members.iter().enumerate() {
+            parts.push(self.import(member, &format!("{}/allOf/{}", pointer, index))?);
+        }
+        if object.contains_key("properties") {
+            let mut rest = object.clone();
+            rest.remove("allOf");
+            parts.push(self.import(&Value::Object(rest), pointer)?);
+        }
+
+        let nullable = parts.iter().all(|part| part.nullable);
+        let mut combined: Option<ObjectStats> = None;
+        let mut other = None;
+        for part in parts {
+            let json_type = match part.json_type {
+                JsonType::Named(_, inner) => *inner,
+                json_type => json_type,
+            };
+            match (json_type, &mut combined) {
+                (JsonType::Null, _) => {}
+                (JsonType::Object(object), None) => combined = Some(object),
+                (JsonType::Object(object), Some(combined)) => {
+                    for (key, field) in object.fields {
+                        match combined.fields.get_mut(&key) {
+                            Some(existing) => {
+                                let existing_type = std::mem::replace(&mut existing.json_type, JsonType::Null);
+                                existing.json_type = merge_types(existing_type, field.json_type)?;
+                                // Every part applies, so one part requiring a field is enough
+                                existing.present = existing.present.max(field.present);
+                                existing.nulls = existing.nulls.min(field.nulls);
+                            }
+                            None => {
+                                combined.fields.insert(key, field);
+                            }
+                        }
+                    }
+                }
+                (json_type, _) => other = other.or(Some(json_type)),
+            }
+        }
+
+        if let Some(combined) = &mut combined {
+            for key in required_keys(object) {
+                if let Some(field) = combined.fields.get_mut(key) {
+                    field.present = combined.samples;
+                }
+            }
+        }
+        let json_type = combined.map(JsonType::Object).or(other).unwrap_or(JsonType::Null);
+        Ok(Imported { json_type, nullable })
+    }
+
+    pub(crate) fn import_ref(&mut self, reference: &str, pointer: &str) -> Result<Imported, Json2RustError> {
+        let Some(target_pointer) = reference.strip_prefix('#') else {
+            return Err(invalid(pointer, &format!("only references within the document are supported, not `{}`", reference)));
+        };
+        let name = match target_pointer.rsplit('/').next() {
+            Some(segment) if !segment.is_empty() => sanitize_type_name(&segment.replace("~1", "/").replace("~0", "~")),
+            _ => self.root_name.clone(),
+        };
+        if self.resolving.iter().any(|resolving| resolving == reference) {
+            return Ok(Imported::of(JsonType::Recursive(name)));
+        }
+        if let Some(imported) = self.resolved.get(reference) {
+            return Ok(imported.clone());
+        }
+
+        let target = self
+            .document
+            .pointer(target_pointer)
+            .ok_or_else(|| invalid(pointer, &format!("`{}` does not resolve", reference)))?;
+        self.resolving.push(reference.to_string());
+        let imported = self.import(target, reference);
+        self.resolving.pop();
+
+        let mut imported = imported?;
+        if declares_rust_type(&imported.json_type) {
+            imported.json_type = JsonType::Named(name, Box::new(imported.json_type));
+        }
+        self.resolved.insert(reference.to_string(), imported.clone());
+        Ok(imported)
+    }
+}
+
+/// Types that generate a struct or enum of their own, and so can carry a definition's name.
+pub(crate) fn declares_rust_type(json_type: &JsonType) -> bool {
+    match json_type {
+        JsonType::Object(_) | JsonType::Tagged(_) | JsonType::Union(_) => true,
+        JsonType::String(stats) => stats.values.is_some(),
+        _ => false,
+    }
+}
+
+/// A `oneOf`, `anyOf` or list of types. Objects that all pin one property to a different
+/// constant become a tagged enum; otherwise named members stay apart, each as its own
+/// variant, while unnamed members of one kind merge as samples would.
+fn union_of(members: Vec<Imported>) -> Result<Imported, Json2RustError> {
+    let nullable = members.iter().any(|member| member.nullable);
+    let members: Vec<JsonType> = members.into_iter().map(|member| member.json_type).filter(|json_type| *json_type != JsonType::Null).collect();
+    if let Some(tagged) = tagged_union(&members)? {
+        return Ok(Imported { json_type: JsonType::Tagged(tagged), nullable });
+    }
+
+    let mut distinct: Vec<JsonType> = Vec::new();
+    for member in members {
+        let is_named = |json_type: &JsonType| matches!(json_type, JsonType::Named(..));
+        let same_kind = distinct
+            .iter()
+            .position(|existing| !is_named(existing) && !is_named(&member) && type_kind(existing) == type_kind(&member));
+        match same_kind {
+            Some(index) => {
+                let existing = std::mem::replace(&mut distinct[index], JsonType::Null);
+                distinct[index] = merge_types(existing, member)?;
+            }
+            None => distinct.push(member),
+        }
+    }
+    let json_type = match distinct.len() {
+        0 => JsonType::Null,
+        1 => distinct.remove(0),
+        _ => JsonType::Union(distinct),
+    };
+    Ok(Imported { json_type, nullable })
+}
+
+fn tagged_union(members: &[JsonType]) -> Result<Option<TaggedUnion>, Json2RustError> {
+    let objects = members
+        .iter()
+        .map(|member| match member {
+            JsonType::Object(object) => Some(object),
+            JsonType::Named(_, inner) => match inner.as_ref() {
+                JsonType::Object(object) => Some(object),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<Vec<&ObjectStats>>>();
+    let Some(objects) = objects.filter(|objects| objects.len() >= 2) else {
+        return Ok(None);
+    };
+    let Some(tag) = objects[0].fields.keys().find(|key| objects.iter().all(|object| constant_of(object, key).is_some())) else {
+        return Ok(None);
+    };
+
+    let mut variants = IndexMap::new();
+    for object in objects {
+        let tag_value = constant_of(object, tag).unwrap_or_default();
+        let mut payload = object.clone();
+        payload.fields.shift_remove(tag);
+        add_tagged_variant(&mut variants, tag_value, JsonType::Object(payload))?;
+    }
+    Ok(Some(TaggedUnion { tag: tag.clone(), content: None, variants }))
+}
+
+/// An OpenAPI `discriminator` over `$ref`'d object schemas. The tag value of each is its
+/// `mapping` key, or else the name of the schema it refers to.
+fn discriminated_union(discriminator: &Value, schemas: &[Value], members: &[Imported]) -> Result<Option<TaggedUnion>, Json2RustError> {
+    let Some(tag) = discriminator.get("propertyName").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+    let mapping = discriminator.get("mapping").and_then(Value::as_object);
+
+    let mut variants = IndexMap::new();
+    for (schema, member) in schemas.iter().zip(members) {
+        let (Some(reference), JsonType::Named(_, inner)) = (schema.get("$ref").and_then(Value::as_str), &member.json_type) else {
+            return Ok(None);
+        };
+        let JsonType::Object(object) = inner.as_ref() else {
+            return Ok(None);
+        };
+        let schema_name = reference.rsplit('/').next().unwrap_or(reference);
+        let tag_value = mapping
+            .and_then(|mapping| {
+                // Mapping targets are references or bare schema names
+                mapping.iter().find(|(_, target)| matches!(target.as_str(), Some(target) if target == reference || target == schema_name))
+            })
+            .map_or_else(|| schema_name.to_string(), |(tag_value, _)| tag_value.clone());
+        let mut payload = object.clone();
+        payload.fields.shift_remove(tag);
+        add_tagged_variant(&mut variants, tag_value, JsonType::Object(payload))?;
+    }
+    Ok(Some(TaggedUnion { tag: tag.to_string(), content: None, variants }))
+}
+
+/// The value of a property declared with a single `const` or `enum` string.
+fn constant_of(object: &ObjectStats, key: &str) -> Option<String> {
+    match &object.fields.get(key)?.json_type {
+        JsonType::String(StringStats { values: Some(values), declared: true, .. }) if values.len() == 1 => Some(values[0].clone()),
+        _ => None,
+    }
+}
+
+/// String values become a declared enum; anything else is typed like samples of it.
+fn import_enum(values: &[Value]) -> Result<Imported, Json2RustError> {
+    let nullable = values.iter().any(Value::is_null);
+    let strings: Vec<String> = values.iter().filter_map(|value| value.as_str().map(str::to_string)).collect();
+    if !strings.is_empty() && strings.len() + usize::from(nullable) == values.len() {
+        let samples = strings.len();
+        let stats = StringStats { format: None, values: Some(strings), samples, declared: true };
+        return Ok(Imported { json_type: JsonType::String(stats), nullable });
+    }
+
+    let mut json_type = JsonType::Null;
+    for value in values {
+        let sample = analyze_json_value(value, "Value", "", &AnalysisOptions::default())?;
+        json_type = merge_types(json_type, sample.json_type)?;
+    }
+    Ok(Imported { json_type, nullable })
+}
+
+fn required_keys(object: &Map<String, Value>) -> Vec<&str> {
+    object
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|keys| keys.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+fn string_format(object: &Map<String, Value>) -> Option<StringFormat> {
+    match object.get("format").and_then(Value::as_str) {
+        Some("date-time") => Some(StringFormat::DateTime),
+        Some("date") => Some(StringFormat::Date),
+        Some("uuid") => Some(StringFormat::Uuid),
+        Some("uri") => Some(StringFormat::Url),
+        Some("ipv4" | "ipv6") => Some(StringFormat::IpAddr),
+        // OpenAPI spells base64 as a format
+        Some("byte") => Some(StringFormat::Base64),
+        _ if object.get("contentEncoding").and_then(Value::as_str) == Some("base64") => Some(StringFormat::Base64),
+        _ => None,
+    }
+}
+
+/// Sized `format`s (`int32`, `uint8`, ...) fix the width; otherwise the declared bounds
+/// choose it the way sample ranges do.
+fn integer_type(object: &Map<String, Value>) -> JsonType {
+    let width = match object.get("format").and_then(Value::as_str) {
+        Some("int8") => Some((true, 8)),
+        Some("int16") => Some((true, 16)),
+        Some("int32") => Some((true, 32)),
+        Some("int64") => Some((true, 64)),
+        Some("uint8") => Some((false, 8)),
+        Some("uint16") => Some((false, 16)),
+        Some("uint32") => Some((false, 32)),
+        Some("uint64") => Some((false, 64)),
+        _ => None,
+    };
+    if let Some((signed, bits)) = width {
+        return JsonType::FixedInt { signed, bits };
+    }
+
+    let min = object.get("minimum").and_then(Value::as_i64).unwrap_or(i64::MIN);
+    match object.get("maximum").and_then(Value::as_u64) {
+        Some(max) if max > i64::MAX as u64 && min >= 0 => JsonType::Unsigned { min: min as u64, max },
+        _ => JsonType::Integer {
+            min,
+            max: object.get("maximum").and_then(Value::as_i64).unwrap_or(i64::MAX),
+        },
+    }
+}
+
+pub(crate) fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn invalid(pointer: &str, message: &str) -> Json2RustError {
+    Json2RustError::InvalidSchema { pointer: pointer.to_string(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::*;
+    use crate::parser::parse_existing_structs;
+
+    const ORDER_SCHEMA: &str = r##"{
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "required": ["id", "status", "customer", "lines"],
+        "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "status": {"enum": ["pending", "shipped", "delivered"]},
+            "placed_at": {"type": "string", "format": "date-time"},
+            "customer": {"$ref": "#/$defs/Customer"},
+            "lines": {"type": "array", "items": {"$ref": "#/$defs/Line"}},
+            "attributes": {"type": "object", "additionalProperties": {"type": "string"}},
+            "note": {"type": ["string", "null"]},
+            "payment": {
+                "oneOf": [
+                    {"type": "object", "required": ["method", "number"], "properties": {"method": {"const": "card"}, "number": {"type": "string"}}},
+                    {"type": "object", "required": ["method"], "properties": {"method": {"const": "cash"}}}
+                ]
+            },
+            "category": {"$ref": "#/$defs/Category"}
+        },
+        "$defs": {
+            "Customer": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {"name": {"type": "string"}, "billing": {"$ref": "#/$defs/Address"}, "shipping": {"$ref": "#/$defs/Address"}}
+            },
+            "Address": {"type": "object", "properties": {"city": {"type": "string"}}},
+            "Line": {"type": "object", "required": ["sku", "quantity"], "properties": {"sku": {"type": "string"}, "quantity": {"type": "integer", "minimum": 1, "maximum": 1000}}},
+            "Category": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {"name": {"type": "string"}, "children": {"type": "array", "items": {"$ref": "#/$defs/Category"}}}
+            }
+        }
+    }"##;
+
+    fn generate(schema: &JsonSchema) -> String {
+        let types = generate_rust_types_with_config(schema, &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        generate_code_with_types(&types).unwrap()
+    }
+
+    #[test]
+    fn test_import_generates_named_types() {
+        let schema = import_json_schema(ORDER_SCHEMA, "Order").unwrap();
+        let code = generate(&schema);
+
+        assert!(code.contains("pub struct Order {"), "{}", code);
+        assert!(code.contains("pub id: uuid::Uuid,"), "{}", code);
+        assert!(code.contains("pub status: Status,"), "{}", code);
+        assert!(code.contains("pub enum Status {"), "{}", code);
+        assert!(code.contains("pub placed_at: Option<chrono::DateTime<chrono::Utc>>,"), "{}", code);
+        assert!(code.contains("pub customer: Customer,"), "{}", code);
+        assert!(code.contains("pub billing: Option<Address>,"), "{}", code);
+        assert!(code.contains("pub shipping: Option<Address>,"), "{}", code);
+        assert_eq!(code.matches("pub struct Address {").count(), 1, "{}", code);
+        assert!(code.contains("pub lines: Vec<Line>,"), "{}", code);
+        assert!(code.contains("pub quantity: u32,"), "{}", code);
+        assert!(code.contains("pub attributes: Option<std::collections::HashMap<String, String>>,"), "{}", code);
+        assert!(code.contains("pub note: Option<String>,"), "{}", code);
+        assert!(code.contains("#[serde(tag = \"method\")]"), "{}", code);
+        assert!(code.contains("pub children: Option<Vec<Category>>,"), "{}", code);
+    }
+
+    #[test]
+    fn test_import_unions_and_all_of() {
+        let schema = r##"{
+            "type": "object",
+            "required": ["shape", "admin"],
+            "properties": {
+                "shape": {"anyOf": [{"$ref": "#/definitions/Circle"}, {"$ref": "#/definitions/Square"}, {"type": "null"}]},
+                "admin": {"allOf": [{"$ref": "#/definitions/User"}, {"properties": {"level": {"type": "integer", "format": "uint8"}}, "required": ["level"]}]},
+                "point": {"type": "array", "prefixItems": [{"type": "number"}, {"type": "number"}]}
+            },
+            "definitions": {
+                "Circle": {"type": "object", "properties": {"radius": {"type": "number"}}},
+                "Square": {"type": "object", "properties": {"side": {"type": "number"}}},
+                "User": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}
+            }
+        }"##;
+        let schema = import_json_schema(schema, "Drawing").unwrap();
+        let JsonType::Object(object) = &schema.json_type else { panic!("Expected object") };
+
+        let shape = &object.fields["shape"];
+        assert_eq!(shape.nulls, 1);
+        let JsonType::Union(members) = &shape.json_type else { panic!("Expected union, got {:?}", shape.json_type) };
+        assert!(matches!(&members[..], [JsonType::Named(circle, _), JsonType::Named(square, _)] if circle == "Circle" && square == "Square"));
+
+        let JsonType::Object(admin) = &object.fields["admin"].json_type else { panic!("Expected combined object") };
+        assert!(!admin.is_optional(&admin.fields["name"]));
+        assert_eq!(admin.fields["level"].json_type, JsonType::FixedInt { signed: false, bits: 8 });
+        assert!(matches!(object.field_type("point"), Some(JsonType::Tuple(items)) if items.len() == 2));
+
+        let code = generate(&schema);
+        assert!(code.contains("Circle(Circle),"), "{}", code);
+        assert!(code.contains("pub level: u8,"), "{}", code);
+    }
+
+    #[test]
+    fn test_imported_schema_extends_existing_structs() {
+        let existing_code = "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct User {\n    pub name: String,\n    pub age: i32,\n}\n";
+        let schema = r#"{"type": "object", "required": ["name", "age"], "properties": {"name": {"type": "string"}, "age": {"type": "integer"}, "email": {"type": "string"}}}"#;
+        let schema = import_json_schema(schema, "User").unwrap();
+
+        let existing_structs = parse_existing_structs(existing_code).unwrap();
+        let types = generate_rust_types_with_config(&schema, &existing_structs, &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types_and_preservation_and_schema(&types, Some(existing_code), &MergeStrategy::Optional, Some(&schema)).unwrap();
+
+        assert!(code.contains("pub age: i32,"), "{}", code);
+        assert!(code.contains("pub email: Option<String>,"), "{}", code);
+    }
+
+    #[test]
+    fn test_import_reports_unresolvable_refs() {
+        let error = import_json_schema(r#"{"properties": {"a": {"$ref": "other.json#/A"}}}"#, "Root").unwrap_err();
+        assert!(matches!(&error, Json2RustError::InvalidSchema { pointer, .. } if pointer == "#/properties/a"), "{}", error);
+        let error = import_json_schema(r##"{"items": {"$ref": "#/$defs/Missing"}}"##, "Root").unwrap_err();
+        assert!(error.to_string().contains("does not resolve"), "{}", error);
+    }
+}
@@ -1,10 +1,42 @@
 pub mod analyzer;
+pub mod case;
 pub mod codegen;
+pub mod dedupe;
+pub mod emitter;
+pub mod formats;
+pub mod har;
+pub mod identifiers;
+pub mod inflection;
+pub mod input;
+pub mod json_schema;
+pub mod ndjson;
+pub mod openapi;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod parser;
+pub mod schema_export;
+pub mod streaming;
 pub mod types;
+pub mod typescript;
 mod tests;
 
 pub use analyzer::*;
+pub use case::*;
 pub use codegen::*;
+pub use dedupe::*;
+pub use emitter::*;
+pub use formats::*;
+pub use har::*;
+pub use identifiers::*;
+pub use inflection::*;
+pub use input::*;
+pub use json_schema::*;
+pub use ndjson::*;
+pub use openapi::*;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
 pub use parser::*;
-pub use types::*;
\ No newline at end of file
+pub use schema_export::*;
+pub use streaming::*;
+pub use types::*;
+pub use typescript::*;
@@ -0,0 +1,181 @@
+use crate::analyzer::{analyze_json_value, empty_schema, merge_schemas};
+use crate::types::*;
+use serde::de::IgnoredAny;
+use serde_json::Value;
+use std::io::BufRead;
+
+// Fixed so that sampling the same file twice generates the same types
+const RESERVOIR_SEED: u64 = 0x5eed_1e55_ca75_0001;
+
+/// Analyzes JSON Lines input, where every non-blank line is one record of the root type.
+/// Records are merged into the schema as they are read, so memory stays bounded by the
+/// largest record rather than the input. With `options.max_samples` set, a uniform random
+/// sample of that many records is analyzed instead, in their original order; the others are
+/// still checked to be valid JSON.
+pub fn analyze_ndjson<R: BufRead>(reader: R, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let mut schema: Option<JsonSchema> = None;
+    match options.max_samples {
+        Some(max_samples) => {
+            for (line_number, line) in sample_records(reader, max_samples)? {
+                merge_record(&mut schema, line_number, &line, root_name, options)?;
+            }
+        }
+        None => {
+            for record in records(reader) {
+                let (line_number, line) = record?;
+                merge_record(&mut schema, line_number, &line, root_name, options)?;
+            }
+        }
+    }
+    Ok(schema.unwrap_or_else(|| empty_schema(root_name)))
+}
+
+/// The records `analyze_ndjson` analyzes, with their line numbers, read into memory.
+pub(crate) fn read_records<R: BufRead>(reader: R, options: &AnalysisOptions) -> Result<Vec<(usize, String)>, Json2RustError> {
+    match options.max_samples {
+        Some(max_samples) => sample_records(reader, max_samples),
+        None => Ok(records(reader).collect::<Result<_, _>>()?),
+    }
+}
+
+/// The schema of the record on line `line_number`.
+pub(crate) fn analyze_record(line_number: usize, line: &str, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|source| Json2RustError::InvalidRecord { line: line_number, source })?;
+    analyze_json_value(&value, root_name, "", options)
+}
+
+/// Every non-blank line with its line number.
+fn records<R: BufRead>(reader: R) -> impl Iterator<Item = std::io::Result<(usize, String)>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| line.map(|line| (index + 1, line)))
+        .filter(|record| !matches!(record, Ok((_, line)) if line.trim().is_empty()))
+}
+
+/// A uniform random sample of at most `max_samples` records, in their original order. Every
+/// record is parsed, without building a value, so a malformed one fails whether sampled or not.
+fn sample_records<R: BufRead>(reader: R, max_samples: usize) -> Result<Vec<(usize, String)>, Json2RustError> {
+    let mut reservoir = Reservoir::new(max_samples);
+    for record in records(reader) {
+        let (line_number, line) = record?;
+        serde_json::from_str::<IgnoredAny>(&line).map_err(|source| Json2RustError::InvalidRecord { line: line_number, source })?;
+        reservoir.offer(line_number, line);
+    }
+    Ok(reservoir.into_sorted())
+}
+
+fn merge_record(
+    schema: &mut Option<JsonSchema>,
+    line_number: usize,
+    line: &str,
+    root_name: &str,
+    options: &AnalysisOptions,
+) -> Result<(), Json2RustError> {
+    let record = analyze_record(line_number, line, root_name, options)?;
+    *schema = Some(match schema.take() {
+        Some(existing) => merge_schemas(existing, record)?,
+        None => record,
+    });
+    Ok(())
+}
+
+/// Keeps a uniform sample of at most `capacity` lines (Algorithm R).
+struct Reservoir {
+    capacity: usize,
+    seen: usize,
+    lines: Vec<(usize, String)>,
+    rng: SplitMix64,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, seen: 0, lines: Vec::new(), rng: SplitMix64(RESERVOIR_SEED) }
+    }
+
+    fn offer(&mut self, line_number: usize, line: String) {
+        self.seen += 1;
+        if self.lines.len() < self.capacity {
+            self.lines.push((line_number, line));
+        } else {
+            let slot = self.rng.below(self.seen as u64) as usize;
+            if slot < self.capacity {
+                self.lines[slot] = (line_number, line);
+            }
+        }
+    }
+
+    fn into_sorted(mut self) -> Vec<(usize, String)> {
+        self.lines.sort_by_key(|(line_number, _)| *line_number);
+        self.lines
+    }
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_ndjson_merges_records() {
+        let input = "{\"id\": 1, \"level\": \"info\"}\n\n{\"id\": 2, \"level\": \"warn\", \"error\": \"boom\"}\n";
+        let schema = analyze_ndjson(input.as_bytes(), "LogEntry", &AnalysisOptions::default()).unwrap();
+
+        assert_eq!(schema.name, "LogEntry");
+        let JsonType::Object(object) = schema.json_type else { panic!("Expected object type") };
+        assert_eq!(object.samples, 2);
+        let keys: Vec<&String> = object.fields.keys().collect();
+        assert_eq!(keys, ["id", "level", "error"]);
+        assert!(object.is_optional(&object.fields["error"]));
+        assert!(!object.is_optional(&object.fields["id"]));
+    }
+
+    #[test]
+    fn test_analyze_ndjson_reports_line() {
+        let input = "{\"id\": 1}\n{\"id\": \n";
+        let error = analyze_ndjson(input.as_bytes(), "Row", &AnalysisOptions::default()).unwrap_err();
+        assert!(matches!(error, Json2RustError::InvalidRecord { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_max_samples_limits_records() {
+        let input: String = (0..1000).map(|i| format!("{{\"n\": {}}}\n", i)).collect();
+        let options = AnalysisOptions { max_samples: Some(10), ..AnalysisOptions::default() };
+        let schema = analyze_ndjson(input.as_bytes(), "Row", &options).unwrap();
+
+        let JsonType::Object(object) = schema.json_type else { panic!("Expected object type") };
+        assert_eq!(object.samples, 10);
+        let again = analyze_ndjson(input.as_bytes(), "Row", &options).unwrap();
+        assert_eq!(JsonType::Object(object), again.json_type);
+
+        let input = input + "{\"n\": \n";
+        let error = analyze_ndjson(input.as_bytes(), "Row", &options).unwrap_err();
+        assert!(matches!(error, Json2RustError::InvalidRecord { line: 1001, .. }), "{}", error);
+    }
+
+    #[test]
+    fn test_reservoir_is_roughly_uniform() {
+        let mut reservoir = Reservoir::new(100);
+        for line_number in 0..10_000 {
+            reservoir.offer(line_number, String::new());
+        }
+        let late = reservoir.into_sorted().iter().filter(|(line_number, _)| *line_number >= 5_000).count();
+        assert!((30..=70).contains(&late), "{} of 100 samples from the second half", late);
+    }
+}
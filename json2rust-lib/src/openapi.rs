@@ -0,0 +1,252 @@
+use crate::case::to_pascal_case;
+use crate::json_schema::{declares_rust_type, escape_pointer, SchemaImporter};
+use crate::types::*;
+use serde_json::{Map, Value};
+
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// Imports the types an OpenAPI 3 document, in YAML or JSON, declares: one root per
+/// `components/schemas` entry that becomes a struct or enum, and with `options.operations`
+/// the JSON request and success response bodies of every operation with an `operationId`,
+/// as `{OperationId}Request` and `{OperationId}Response`. Generate them together with
+/// `generate_rust_types_for_schemas` so the types they share are generated once.
+pub fn import_openapi(spec_text: &str, options: &OpenApiOptions) -> Result<Vec<JsonSchema>, Json2RustError> {
+    // YAML is a superset of JSON, so one parser reads both spellings
+    let document: Value = serde_yaml::from_str(spec_text)
+        .map_err(|error| Json2RustError::InputParsing { format: "OpenAPI", message: error.to_string() })?;
+    import_openapi_value(&document, options)
+}
+
+/// `import_openapi` for a document that is already parsed.
+pub fn import_openapi_value(document: &Value, options: &OpenApiOptions) -> Result<Vec<JsonSchema>, Json2RustError> {
+    match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => {}
+        _ => return Err(invalid("#/openapi", "expected an OpenAPI 3 document")),
+    }
+
+    // One importer for every root, so a component referenced from several is converted once.
+    // The root name only names `$ref: "#"`, which cannot occur in a valid document.
+    let mut importer = SchemaImporter::new(document, "Root");
+    let mut roots = Vec::new();
+    for name in document.pointer("/components/schemas").and_then(Value::as_object).into_iter().flat_map(Map::keys) {
+        let reference = format!("#/components/schemas/{}", escape_pointer(name));
+        // Aliases of arrays and scalars have no type of their own and are inlined where used
+        if let JsonType::Named(name, inner) = importer.import_ref(&reference, &reference)?.json_type {
+            roots.push(JsonSchema { name: name.clone(), json_type: JsonType::Named(name, inner), optional: false });
+        }
+    }
+
+    if options.operations {
+        for (path, item) in document.get("paths").and_then(Value::as_object).into_iter().flatten() {
+            for method in METHODS {
+                let Some(operation) = item.get(method) else { continue };
+                let Some(operation_id) = operation.get("operationId").and_then(Value::as_str) else { continue };
+                let pointer = format!("#/paths/{}/{}", escape_pointer(path), method);
+                let name = to_pascal_case(operation_id);
+
+                if let Some(body) = operation.get("requestBody") {
+                    if let Some((schema, schema_pointer)) = json_body_schema(document, body, format!("{}/requestBody", pointer))? {
+                        push_operation_root(&mut roots, importer.import(schema, &schema_pointer)?.json_type, format!("{}Request", name));
+                    }
+                }
+
+                let responses = operation.get("responses").and_then(Value::as_object);
+                let success = responses.and_then(|responses| {
+                    responses.iter().find(|(status, _)| status.starts_with('2')).or_else(|| responses.get_key_value("default"))
+                });
+                if let Some((status, response)) = success {
+                    let response_pointer = format!("{}/responses/{}", pointer, status);
+                    if let Some((schema, schema_pointer)) = json_body_schema(document, response, response_pointer)? {
+                        push_operation_root(&mut roots, importer.import(schema, &schema_pointer)?.json_type, format!("{}Response", name));
+                    }
+                }
+            }
+        }
+    }
+    Ok(roots)
+}
+
+/// The schema of the JSON content of a request body or response, following a `$ref` to
+/// `components`, with its pointer.
+fn json_body_schema<'a>(document: &'a Value, body: &'a Value, pointer: String) -> Result<Option<(&'a Value, String)>, Json2RustError> {
+    let (body, pointer) = match body.get("$ref").and_then(Value::as_str) {
+        Some(reference) => {
+            let target = reference
+                .strip_prefix('#')
+                .and_then(|target| document.pointer(target))
+                .ok_or_else(|| invalid(&pointer, &format!("`{}` does not resolve", reference)))?;
+            (target, reference.to_string())
+        }
+        None => (body, pointer),
+    };
+
+    let content = body.get("content").and_then(Value::as_object);
+    let json_content = content.and_then(|content| content.iter().find(|(media_type, _)| is_json_media_type(media_type)));
+    Ok(json_content.and_then(|(media_type, media)| {
+        let schema = media.get("schema")?;
+        Some((schema, format!("{}/content/{}/schema", pointer, escape_pointer(media_type))))
+    }))
+}
+
+fn is_json_media_type(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+/// Adds the type of a body as a root named after its operation. A body that is a component
+/// reuses that component's type, and a list of inline objects names its element instead.
+fn push_operation_root(roots: &mut Vec<JsonSchema>, json_type: JsonType, name: String) {
+    match json_type {
+        JsonType::Named(..) => {}
+        JsonType::Array(element) => push_operation_root(roots, *element, format!("{}Item", name)),
+        json_type if declares_rust_type(&json_type) => roots.push(JsonSchema { name, json_type, optional: false }),
+        _ => {}
+    }
+}
+
+fn invalid(pointer: &str, message: &str) -> Json2RustError {
+    Json2RustError::InvalidSchema { pointer: pointer.to_string(), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::*;
+    use crate::parser::parse_existing_structs;
+
+    const PETSTORE: &str = r##"
+openapi: 3.0.3
+info: {title: Petstore, version: "1.0"}
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      responses:
+        "200":
+          description: All pets
+          content:
+            application/json:
+              schema: {type: array, items: {$ref: "#/components/schemas/Pet"}}
+    post:
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [name, kind]
+              properties:
+                name: {type: string}
+                kind: {$ref: "#/components/schemas/Kind"}
+      responses:
+        "201": {$ref: "#/components/responses/PetCreated"}
+  /pets/{petId}/photos:
+    get:
+      operationId: listPhotos
+      responses:
+        default:
+          description: Photos
+          content:
+            application/json; charset=utf-8:
+              schema:
+                type: array
+                items: {type: object, properties: {url: {type: string, format: uri}}}
+components:
+  responses:
+    PetCreated:
+      description: Created
+      content:
+        application/json:
+          schema: {type: object, required: [id], properties: {id: {type: integer, format: int64}}}
+  schemas:
+    Pet:
+      type: object
+      required: [id, name, owner]
+      properties:
+        id: {type: integer, format: int64}
+        name: {type: string}
+        tag: {type: string, nullable: true}
+        kind: {$ref: "#/components/schemas/Kind"}
+        owner: {$ref: "#/components/schemas/Owner"}
+    Owner:
+      type: object
+      properties:
+        email: {type: string}
+        pets: {type: array, items: {$ref: "#/components/schemas/Pet"}}
+    Kind: {type: string, enum: [cat, dog]}
+    PetList: {type: array, items: {$ref: "#/components/schemas/Pet"}}
+    Event:
+      oneOf: [{$ref: "#/components/schemas/Adopted"}, {$ref: "#/components/schemas/Returned"}]
+      discriminator:
+        propertyName: type
+        mapping: {adopted: "#/components/schemas/Adopted"}
+    Adopted:
+      type: object
+      required: [type, pet]
+      properties: {type: {type: string}, pet: {$ref: "#/components/schemas/Pet"}}
+    Returned:
+      type: object
+      required: [type]
+      properties: {type: {type: string}, reason: {type: string}}
+"##;
+
+    fn root_names(roots: &[JsonSchema]) -> Vec<&str> {
+        roots.iter().map(|root| root.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_import_components() {
+        let roots = import_openapi(PETSTORE, &OpenApiOptions::default()).unwrap();
+        assert_eq!(root_names(&roots), ["Pet", "Owner", "Kind", "Event", "Adopted", "Returned"]);
+
+        let types = generate_rust_types_for_schemas(&roots, &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        assert_eq!(code.matches("pub struct Pet {").count(), 1, "{}", code);
+        assert!(code.contains("pub id: i64,"), "{}", code);
+        assert!(code.contains("pub tag: Option<String>,"), "{}", code);
+        assert!(code.contains("pub kind: Option<Kind>,"), "{}", code);
+        assert!(code.contains("pub owner: Owner,"), "{}", code);
+        assert!(code.contains("pub pets: Option<Vec<Pet>>,"), "{}", code);
+        assert!(code.contains("pub enum Kind {"), "{}", code);
+        assert!(code.contains("#[serde(tag = \"type\")]\npub enum Event {"), "{}", code);
+        assert!(code.contains("#[serde(rename = \"adopted\")]\n    Adopted {"), "{}", code);
+        assert!(code.contains("    Returned {"), "{}", code);
+    }
+
+    #[test]
+    fn test_import_operations() {
+        let roots = import_openapi(PETSTORE, &OpenApiOptions { operations: true }).unwrap();
+        assert_eq!(
+            root_names(&roots)[6..],
+            ["CreatePetRequest", "CreatePetResponse", "ListPhotosResponseItem"]
+        );
+
+        let types = generate_rust_types_for_schemas(&roots, &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types(&types).unwrap();
+        assert!(code.contains("pub struct CreatePetRequest {"), "{}", code);
+        assert!(code.contains("pub kind: Kind,"), "{}", code);
+        assert!(code.contains("pub struct ListPhotosResponseItem {"), "{}", code);
+        assert!(code.contains("pub url: Option<url::Url>,"), "{}", code);
+    }
+
+    #[test]
+    fn test_regeneration_keeps_existing_structs() {
+        let existing_code = "use serde::{Deserialize, Serialize};\n\n#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Owner {\n    pub email: Option<String>,\n    pub nickname: Option<String>,\n}\n\nimpl Owner {\n    pub fn display_name(&self) -> Option<&str> {\n        self.nickname.as_deref()\n    }\n}\n";
+        let roots = import_openapi(PETSTORE, &OpenApiOptions::default()).unwrap();
+        let existing_structs = parse_existing_structs(existing_code).unwrap();
+        let types = generate_rust_types_for_schemas(&roots, &existing_structs, &MergeStrategy::Optional, &GenerationConfig::default()).unwrap();
+        let code = generate_code_with_types_and_preservation(&types, Some(existing_code), &MergeStrategy::Optional).unwrap();
+
+        assert!(code.contains("impl Owner {\n    pub fn display_name"), "{}", code);
+        assert!(code.contains("pub nickname: Option<String>,"), "{}", code);
+        assert!(code.contains("pub pets: Option<Vec<Pet>>,"), "{}", code);
+        assert_eq!(code.matches("pub struct Owner {").count(), 1, "{}", code);
+        assert!(code.contains("pub struct Pet {"), "{}", code);
+    }
+
+    #[test]
+    fn test_rejects_other_documents() {
+        let error = import_openapi("swagger: \"2.0\"\n", &OpenApiOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("expected an OpenAPI 3 document"), "{}", error);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::analyzer::*;
+use crate::input::analyze_reader;
+use crate::ndjson::{analyze_record, read_records};
+use crate::types::*;
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read};
+
+// Samples analyzed per round; bounds the partial schemas held at once for huge arrays
+const BATCH_SIZE: usize = 4096;
+
+// `merge_types` is not strictly associative (tag filing, union member order and map
+// folding all depend on what has been merged so far), so only analysis runs on the pool
+// and partial schemas are always merged in input order. That keeps every result here
+// identical to its sequential counterpart.
+
+/// Same result as `analyze_samples`, with the samples parsed and analyzed on the rayon
+/// thread pool. Set `RAYON_NUM_THREADS` to limit the number of threads.
+pub fn analyze_samples_parallel<S: AsRef<str> + Sync>(
+    samples: &[S],
+    root_name: &str,
+    options: &AnalysisOptions,
+) -> Result<JsonSchema, Json2RustError> {
+    merge_parallel(samples, root_name, |sample| analyze_json_parallel(sample.as_ref(), root_name, options))
+}
+
+/// Runs `analyze` over `inputs` on the thread pool and merges the schemas in input order,
+/// for inputs that are not plain JSON strings (files, JSON Lines streams, ...).
+pub fn merge_parallel<T, F>(inputs: &[T], root_name: &str, analyze: F) -> Result<JsonSchema, Json2RustError>
+where
+    T: Sync,
+    F: Fn(&T) -> Result<JsonSchema, Json2RustError> + Sync,
+{
+    let mut merged: Option<JsonSchema> = None;
+    for_each_in_order(inputs, analyze, |schema| {
+        merged = Some(match merged.take() {
+            Some(existing) => merge_schemas(existing, schema)?,
+            None => schema,
+        });
+        Ok(())
+    })?;
+    Ok(merged.unwrap_or_else(|| empty_schema(root_name)))
+}
+
+/// Same result as `analyze_json_with_options`, with the elements of a top-level array
+/// analyzed on the thread pool.
+pub fn analyze_json_parallel(json_str: &str, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let value: Value = serde_json::from_str(json_str)?;
+    let arr = match &value {
+        Value::Array(arr) if !arr.is_empty() => arr,
+        _ => return analyze_json_with_options(json_str, root_name, options),
+    };
+
+    let objects = arr.iter().map(Value::as_object).collect::<Option<Vec<_>>>();
+    let tagging = objects.as_deref().and_then(find_tag_field);
+    let element_type = match (objects, tagging) {
+        (Some(objects), Some((tag, content))) => {
+            let mut variants: IndexMap<String, JsonType> = IndexMap::new();
+            for_each_in_order(
+                &objects,
+                |obj| analyze_tagged_variant(obj, &tag, content.as_deref(), "[]", options),
+                |(tag_value, payload)| add_tagged_variant(&mut variants, tag_value, payload),
+            )?;
+            JsonType::Tagged(TaggedUnion { tag, content, variants })
+        }
+        _ => {
+            let item_name = format!("{}Item", root_name);
+            let mut item_types = Vec::new();
+            for_each_in_order(
+                arr,
+                |item| analyze_json_value(item, &item_name, "[]", options),
+                |item_schema| {
+                    item_types.push(item_schema.json_type);
+                    Ok(())
+                },
+            )?;
+            merge_element_types(item_types)?
+        }
+    };
+
+    Ok(JsonSchema {
+        name: root_name.to_string(),
+        json_type: JsonType::Array(Box::new(element_type)),
+        optional: false,
+    })
+}
+
+/// Same result as `analyze_ndjson`, with the records analyzed on the thread pool. The records,
+/// or the sample of them `options.max_samples` asks for, are read into memory first.
+pub fn analyze_ndjson_parallel<R: BufRead>(reader: R, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let records = read_records(reader, options)?;
+    merge_parallel(&records, root_name, |(line_number, line)| analyze_record(*line_number, line, root_name, options))
+}
+
+/// Same result as `analyze_reader`, with the elements of a top-level JSON array or the records
+/// of JSON Lines input analyzed on the thread pool. Those are read into memory rather than
+/// streamed; other formats are analyzed as `analyze_reader` does.
+pub fn analyze_reader_parallel<R: Read>(
+    mut reader: R,
+    format: InputFormat,
+    root_name: &str,
+    options: &AnalysisOptions,
+) -> Result<JsonSchema, Json2RustError> {
+    match format {
+        InputFormat::Json => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            analyze_json_parallel(&text, root_name, options)
+        }
+        InputFormat::JsonLines => analyze_ndjson_parallel(BufReader::new(reader), root_name, options),
+        _ => analyze_reader(reader, format, root_name, options),
+    }
+}
+
+/// Analyzes `items` on the thread pool one batch at a time, handing the results to `fold`
+/// in input order. Stops at the first error in input order.
+fn for_each_in_order<T, R, A, F>(items: &[T], analyze: A, mut fold: F) -> Result<(), Json2RustError>
+where
+    T: Sync,
+    R: Send,
+    A: Fn(&T) -> Result<R, Json2RustError> + Sync,
+    F: FnMut(R) -> Result<(), Json2RustError>,
+{
+    for batch in items.chunks(BATCH_SIZE) {
+        let results: Vec<Result<R, Json2RustError>> = batch.par_iter().map(&analyze).collect();
+        for result in results {
+            fold(result?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<String> {
+        (0..200)
+            .map(|i| match i % 4 {
+                0 => format!(r#"{{"id": {}, "name": "user{}", "tags": ["a"]}}"#, i, i),
+                1 => format!(r#"{{"id": -{}, "email": "u{}@example.com", "score": {}.5}}"#, i, i, i),
+                2 => format!(r#"{{"name": null, "meta": {{"kind": "k{}", "n": {}}}, "extra": [1, "x"]}}"#, i % 3, i),
+                _ => format!(r#"[{{"type": "click", "x": {}}}, {{"type": "key", "code": "c{}"}}]"#, i, i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_samples_match_sequential() {
+        let samples = samples();
+        let options = AnalysisOptions::default();
+        let sequential = analyze_samples(&samples, "Root", &options).unwrap();
+        let parallel = analyze_samples_parallel(&samples, "Root", &options).unwrap();
+        assert_eq!(parallel, sequential);
+
+        let JsonType::Union(members) = &parallel.json_type else { panic!("Expected union of object and array") };
+        let JsonType::Object(object) = &members[0] else { panic!("Expected object first") };
+        let keys: Vec<&String> = object.fields.keys().collect();
+        assert_eq!(keys, ["id", "name", "tags", "email", "score", "meta", "extra"]);
+    }
+
+    #[test]
+    fn test_parallel_array_matches_sequential() {
+        let records: Vec<String> = samples().into_iter().filter(|sample| sample.starts_with('{')).collect();
+        let json = format!("[{}]", records.join(","));
+        let events: String = (0..BATCH_SIZE + 10)
+            .map(|i| if i % 2 == 0 { format!(r#"{{"type": "a", "n": {}}}"#, i) } else { r#"{"type": "b", "s": "x"}"#.to_string() })
+            .collect::<Vec<_>>()
+            .join(",");
+        let tagged = format!("[{}]", events);
+
+        let options = AnalysisOptions::default();
+        for json in [json.as_str(), tagged.as_str(), "[]", r#"{"a": [1, 2]}"#] {
+            let sequential = analyze_json_with_options(json, "Root", &options).unwrap();
+            assert_eq!(analyze_json_parallel(json, "Root", &options).unwrap(), sequential);
+        }
+    }
+
+    #[test]
+    fn test_parallel_reports_first_error() {
+        let samples = ["{}", "{", "[1"];
+        let error = analyze_samples_parallel(&samples, "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("EOF while parsing an object"), "{}", error);
+        assert_eq!(analyze_samples_parallel::<&str>(&[], "Root", &AnalysisOptions::default()).unwrap().name, "Root");
+    }
+
+    #[test]
+    fn test_parallel_reader_matches_sequential() {
+        let lines: String = samples().iter().map(|sample| sample.clone() + "\n\n").collect();
+        let array = format!("[{}]", samples().join(","));
+        let sampled = AnalysisOptions { max_samples: Some(20), ..AnalysisOptions::default() };
+        for (text, format, options) in [
+            (lines.as_str(), InputFormat::JsonLines, AnalysisOptions::default()),
+            (lines.as_str(), InputFormat::JsonLines, sampled),
+            (array.as_str(), InputFormat::Json, AnalysisOptions::default()),
+            ("a: 1\n", InputFormat::Yaml, AnalysisOptions::default()),
+        ] {
+            let sequential = analyze_reader(text.as_bytes(), format, "Root", &options).unwrap();
+            assert_eq!(analyze_reader_parallel(text.as_bytes(), format, "Root", &options).unwrap(), sequential, "{:?}", format);
+        }
+
+        let error = analyze_ndjson_parallel("{}\n\n{\"a\": \n".as_bytes(), "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(matches!(error, Json2RustError::InvalidRecord { line: 3, .. }), "{}", error);
+    }
+}
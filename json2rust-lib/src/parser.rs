@@ -1,4 +1,5 @@
 use crate::types::*;
+use indexmap::IndexMap;
 use std::collections::HashMap;
 use syn::{File, Item, ItemStruct, Type, TypePath, Fields, FieldsNamed};
 
@@ -20,7 +21,7 @@ pub fn parse_existing_structs(rust_code: &str) -> Result<Vec<ExistingStruct>, Js
 
 fn parse_struct_item(item_struct: &ItemStruct) -> Result<ExistingStruct, Json2RustError> {
     let name = item_struct.ident.to_string();
-    let mut fields = HashMap::new();
+    let mut fields = IndexMap::new();
     
     if let Fields::Named(FieldsNamed { named, .. }) = &item_struct.fields {
         for field in named {
@@ -72,6 +73,88 @@ fn extract_type_string(ty: &Type) -> Result<String, Json2RustError> {
     }
 }
 
+/// Reads a type as written in Rust code, e.g. `Option<Vec<User>>`, as a `TypeRef`. Only the
+/// last path segment counts, so `chrono::DateTime<Utc>` reads like `DateTime<Utc>`; a plain
+/// name that is not a std or well-known library type is taken for a generated type.
+pub fn type_ref_from_rust(type_name: &str) -> TypeRef {
+    syn::parse_str::<Type>(type_name).map_or(TypeRef::Any, |ty| syn_type_ref(&ty))
+}
+
+/// `type_ref_from_rust` for a field, whose serde adapter may write its bytes as base64.
+pub fn field_type_ref(type_name: &str, serde_with: Option<&str>) -> TypeRef {
+    let ty = type_ref_from_rust(type_name);
+    match serde_with {
+        Some(with) if with.contains("Base64") => base64_bytes(ty),
+        _ => ty,
+    }
+}
+
+pub(crate) fn syn_type_ref(ty: &Type) -> TypeRef {
+    match ty {
+        Type::Tuple(tuple) => TypeRef::Tuple(tuple.elems.iter().map(syn_type_ref).collect()),
+        Type::Reference(reference) => syn_type_ref(&reference.elem),
+        Type::Path(TypePath { path, .. }) => match path.segments.last() {
+            Some(segment) => {
+                let args: Vec<&Type> = match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                path_type_ref(&segment.ident.to_string(), &args)
+            }
+            None => TypeRef::Any,
+        },
+        _ => TypeRef::Any,
+    }
+}
+
+fn path_type_ref(ident: &str, args: &[&Type]) -> TypeRef {
+    match (ident, args) {
+        ("Option", [inner]) => TypeRef::Optional(Box::new(syn_type_ref(inner))),
+        ("Box" | "Rc" | "Arc" | "Cow", [inner]) => syn_type_ref(inner),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => TypeRef::List(Box::new(syn_type_ref(inner))),
+        ("HashMap" | "BTreeMap" | "IndexMap", [key, value]) => {
+            let key = match syn_type_ref(key) {
+                TypeRef::Boolean => MapKey::Boolean,
+                TypeRef::Integer { .. } => MapKey::Integer,
+                _ => MapKey::String,
+            };
+            TypeRef::Map(key, Box::new(syn_type_ref(value)))
+        }
+        ("String" | "str", _) => TypeRef::String,
+        ("char", _) => TypeRef::Char,
+        ("bool", _) => TypeRef::Boolean,
+        ("f32" | "f64", _) => TypeRef::Float,
+        ("DateTime", _) => TypeRef::Format(StringFormat::DateTime),
+        ("NaiveDate", _) => TypeRef::Format(StringFormat::Date),
+        ("Uuid", _) => TypeRef::Format(StringFormat::Uuid),
+        ("Url", _) => TypeRef::Format(StringFormat::Url),
+        ("IpAddr", _) => TypeRef::Format(StringFormat::IpAddr),
+        ("Datetime", _) => TypeRef::Format(StringFormat::TomlDatetime),
+        ("Value", _) => TypeRef::Any,
+        (ident, []) => match numeric_kind(ident) {
+            Some((kind, bits)) => TypeRef::Integer { signed: kind == 'i', bits: bits as u8 },
+            None => TypeRef::Named(ident.to_string()),
+        },
+        _ => TypeRef::Any,
+    }
+}
+
+fn base64_bytes(ty: TypeRef) -> TypeRef {
+    match ty {
+        TypeRef::List(element) if *element == (TypeRef::Integer { signed: false, bits: 8 }) => TypeRef::Format(StringFormat::Base64),
+        TypeRef::List(element) => TypeRef::List(Box::new(base64_bytes(*element))),
+        TypeRef::Optional(inner) => TypeRef::Optional(Box::new(base64_bytes(*inner))),
+        ty => ty,
+    }
+}
+
 pub fn calculate_struct_similarity(existing: &ExistingStruct, new_fields: &HashMap<String, String>) -> f64 {
     if existing.fields.is_empty() && new_fields.is_empty() {
         return 1.0;
@@ -124,21 +207,80 @@ fn are_types_compatible(existing_type: &str, new_type: &str) -> bool {
         return are_types_compatible(existing_type, inner_new);
     }
     
-    // Check numeric type compatibility
-    if is_numeric_type(existing_type) && new_type == "f64" {
+    // Numbers are compatible because merging widens the existing type where it must
+    if widen_numeric_type(existing_type, new_type).is_some() {
         return true;
     }
     
-    if is_numeric_type(new_type) && existing_type == "f64" {
-        return true;
+    matches!(
+        (existing_type, new_type),
+        ("String", "i64") | ("i64", "String") | ("String", "f64") | ("f64", "String")
+    )
+}
+
+/// The type a field gets when an `existing` numeric type meets the `new` one inferred from
+/// samples: the existing type while it can hold the samples, otherwise both widened along
+/// u32 → i64 → f64. `None` unless both types are numeric.
+pub(crate) fn widen_numeric_type(existing: &str, new: &str) -> Option<String> {
+    let (existing_kind, existing_bits) = numeric_kind(existing)?;
+    let (new_kind, new_bits) = numeric_kind(new)?;
+    let fits = match (existing_kind, new_kind) {
+        // Any JSON number deserializes into a float, if not always exactly
+        ('f', _) => true,
+        (_, 'f') => false,
+        // Inference picks u32 for small non-negative numbers and i64 for negative ones, so
+        // only the sign says something about the samples that a declared width can't hold
+        _ if new == "u32" => true,
+        ('i', 'i') => true,
+        ('i', 'u') => existing_bits > new_bits,
+        ('u', 'i') => false,
+        _ => existing_bits >= new_bits,
+    };
+    if fits {
+        return Some(existing.to_string());
     }
-    
-    match (existing_type, new_type) {
-        ("String", "i64") | ("i64", "String") => true,
-        ("String", "f64") | ("f64", "String") => true,
-        ("i64", "f64") | ("f64", "i64") => true,
-        _ => false,
+
+    let widened = match (existing_kind, new_kind) {
+        ('f', _) | (_, 'f') => "f64",
+        (kind, other) if kind == other => numeric_name(kind, existing_bits.max(new_bits))?,
+        _ => {
+            let (signed_bits, unsigned_bits) = if existing_kind == 'i' { (existing_bits, new_bits) } else { (new_bits, existing_bits) };
+            match unsigned_bits < 64 {
+                true => numeric_name('i', signed_bits.max(64))?,
+                false => "f64",
+            }
+        }
+    };
+    Some(widened.to_string())
+}
+
+/// `('i', 32)` for `i32`; pointer-sized integers count as 64 bits.
+fn numeric_kind(type_name: &str) -> Option<(char, u32)> {
+    if !is_numeric_type(type_name) {
+        return None;
     }
+    let kind = type_name.chars().next()?;
+    let bits = match &type_name[1..] {
+        "size" => 64,
+        bits => bits.parse().ok()?,
+    };
+    Some((kind, bits))
+}
+
+fn numeric_name(kind: char, bits: u32) -> Option<&'static str> {
+    Some(match (kind, bits) {
+        ('u', 8) => "u8",
+        ('u', 16) => "u16",
+        ('u', 32) => "u32",
+        ('u', 64) => "u64",
+        ('u', 128) => "u128",
+        ('i', 8) => "i8",
+        ('i', 16) => "i16",
+        ('i', 32) => "i32",
+        ('i', 64) => "i64",
+        ('i', 128) => "i128",
+        _ => return None,
+    })
 }
 
 fn is_numeric_type(type_name: &str) -> bool {
@@ -180,7 +322,7 @@ mod tests {
         let existing = ExistingStruct {
             name: "Person".to_string(),
             fields: {
-                let mut fields = HashMap::new();
+                let mut fields = IndexMap::new();
                 fields.insert("name".to_string(), "String".to_string());
                 fields.insert("age".to_string(), "i32".to_string());
                 fields
@@ -201,6 +343,26 @@ mod tests {
         assert!(are_types_compatible("Option<String>", "String"));
         assert!(are_types_compatible("String", "Option<String>"));
         assert!(are_types_compatible("String", "i64"));
+        assert!(are_types_compatible("i32", "u32"));
+        assert!(are_types_compatible("Option<f32>", "f64"));
+    }
+
+    #[test]
+    fn test_widen_numeric_type() {
+        // Kept while the existing type holds the samples
+        assert_eq!(widen_numeric_type("i32", "u32").as_deref(), Some("i32"));
+        assert_eq!(widen_numeric_type("f32", "f64").as_deref(), Some("f32"));
+        assert_eq!(widen_numeric_type("i64", "i64").as_deref(), Some("i64"));
+        assert_eq!(widen_numeric_type("i32", "i64").as_deref(), Some("i32"));
+        // Negative samples, then fractional ones
+        assert_eq!(widen_numeric_type("u32", "i64").as_deref(), Some("i64"));
+        assert_eq!(widen_numeric_type("u32", "f64").as_deref(), Some("f64"));
+        assert_eq!(widen_numeric_type("i64", "f64").as_deref(), Some("f64"));
+        // Values above i64::MAX
+        assert_eq!(widen_numeric_type("u32", "u64").as_deref(), Some("u64"));
+        assert_eq!(widen_numeric_type("i64", "u64").as_deref(), Some("f64"));
+        assert_eq!(widen_numeric_type("u64", "i64").as_deref(), Some("f64"));
+        assert_eq!(widen_numeric_type("String", "i64"), None);
         assert!(!are_types_compatible("bool", "String"));
     }
 }
\ No newline at end of file
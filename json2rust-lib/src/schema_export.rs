@@ -0,0 +1,400 @@
+use crate::codegen::GeneratedTypes;
+use crate::emitter::Emitter;
+use crate::identifiers::unraw;
+use crate::types::*;
+use serde_json::{json, Map, Value};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Writes generated types as a JSON Schema (draft 2020-12) document describing the JSON they
+/// (de)serialize. The type named `root_name` is the document itself and every other type is
+/// a `$defs` entry, so the schema matches exactly what `generate_code_with_types` emits:
+/// fields that are not `Option` or `#[serde(default)]` are `required`, unit enums become
+/// `enum`s, untagged enums `oneOf`s, and serde renames, tags and adapters are applied.
+pub fn generate_json_schema_with_types(types: &GeneratedTypes, root_name: &str) -> Result<String, Json2RustError> {
+    JsonSchemaEmitter.emit(types, root_name)
+}
+
+/// `generate_json_schema_with_types` as a `serde_json::Value`. Without a type named
+/// `root_name`, as for the many roots of an API description, the document only has `$defs`.
+pub fn json_schema_document(types: &GeneratedTypes, root_name: &str) -> Result<Value, Json2RustError> {
+    let exporter = SchemaExporter { types, root_name };
+    let mut definitions = Vec::new();
+    for rust_struct in &types.structs {
+        definitions.push((&rust_struct.name, exporter.struct_definition(rust_struct)));
+    }
+    for rust_enum in &types.enums {
+        definitions.push((&rust_enum.name, exporter.enum_definition(rust_enum)));
+    }
+
+    let mut root = None;
+    let mut defs = Map::new();
+    for (name, schema) in definitions {
+        if name == root_name {
+            root = Some(schema);
+        } else {
+            defs.insert(name.clone(), schema);
+        }
+    }
+
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DIALECT));
+    document.insert("title".to_string(), json!(root_name));
+    if let Some(Value::Object(root)) = root {
+        document.extend(root);
+    }
+    if !defs.is_empty() {
+        document.insert("$defs".to_string(), Value::Object(defs));
+    }
+    Ok(Value::Object(document))
+}
+
+/// The JSON Schema backend: one document for all the types, rooted at the type `emit` is
+/// given. Emitting a single type gives its `$defs` entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonSchemaEmitter;
+
+impl Emitter for JsonSchemaEmitter {
+    fn name(&self) -> &str {
+        "json-schema"
+    }
+
+    fn file_extension(&self) -> &str {
+        "json"
+    }
+
+    fn emit_struct(&self, rust_struct: &RustStruct, types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        Ok(serde_json::to_string_pretty(&SchemaExporter { types, root_name: "" }.struct_definition(rust_struct))?)
+    }
+
+    fn emit_enum(&self, rust_enum: &RustEnum, types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        Ok(serde_json::to_string_pretty(&SchemaExporter { types, root_name: "" }.enum_definition(rust_enum))?)
+    }
+
+    fn emit(&self, types: &GeneratedTypes, root_name: &str) -> Result<String, Json2RustError> {
+        Ok(serde_json::to_string_pretty(&json_schema_document(types, root_name)?)? + "\n")
+    }
+}
+
+struct SchemaExporter<'a> {
+    types: &'a GeneratedTypes,
+    root_name: &'a str,
+}
+
+impl SchemaExporter<'_> {
+    fn struct_definition(&self, rust_struct: &RustStruct) -> Value {
+        described(self.object_schema(&rust_struct.fields), &rust_struct.docs)
+    }
+
+    fn enum_definition(&self, rust_enum: &RustEnum) -> Value {
+        described(self.enum_schema(rust_enum), &rust_enum.docs)
+    }
+
+    fn object_schema(&self, fields: &[RustField]) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut additional_properties = None;
+        let mut all_of = Vec::new();
+        for field in fields {
+            let ty = match &field.ty {
+                ty if field.is_optional && !matches!(ty, TypeRef::Optional(_)) => TypeRef::Optional(Box::new(ty.clone())),
+                ty => ty.clone(),
+            };
+            let schema = self.type_schema(&ty);
+
+            if field.serde_flatten {
+                // A flattened map takes the keys no other field does; anything else adds fields
+                match schema.get("additionalProperties") {
+                    Some(values) if schema.get("properties").is_none() => additional_properties = Some(values.clone()),
+                    _ => all_of.push(schema),
+                }
+                continue;
+            }
+            let wire_name = field.serde_rename.clone().unwrap_or_else(|| unraw(&field.name).to_string());
+            if !matches!(ty, TypeRef::Optional(_)) && !field.serde_default {
+                required.push(json!(wire_name));
+            }
+            properties.insert(wire_name, schema);
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), json!("object"));
+        if !properties.is_empty() {
+            schema.insert("properties".to_string(), Value::Object(properties));
+        }
+        if !required.is_empty() {
+            schema.insert("required".to_string(), Value::Array(required));
+        }
+        if let Some(values) = additional_properties {
+            schema.insert("additionalProperties".to_string(), values);
+        }
+        if !all_of.is_empty() {
+            schema.insert("allOf".to_string(), Value::Array(all_of));
+        }
+        Value::Object(schema)
+    }
+
+    fn enum_schema(&self, rust_enum: &RustEnum) -> Value {
+        let wire_name = |variant: &RustEnumVariant| variant.serde_rename.clone().unwrap_or_else(|| variant.name.clone());
+        let is_unit = |variant: &RustEnumVariant| variant.newtype.is_none() && variant.fields.is_empty();
+
+        if rust_enum.representation == EnumRepresentation::External && rust_enum.variants.iter().all(is_unit) {
+            let values: Vec<String> = rust_enum.variants.iter().filter(|variant| !variant.serde_other).map(wire_name).collect();
+            // A `#[serde(other)]` catch-all accepts any string, so the known ones are only examples
+            return match rust_enum.variants.iter().any(|variant| variant.serde_other) {
+                true => json!({"type": "string", "examples": values}),
+                false => json!({"type": "string", "enum": values}),
+            };
+        }
+
+        let mut one_of = Vec::new();
+        for variant in &rust_enum.variants {
+            let payload = match &variant.newtype_ty {
+                Some(ty) => Some(self.type_schema(ty)),
+                None if !variant.fields.is_empty() => Some(self.object_schema(&variant.fields)),
+                None => None,
+            };
+            let wire_name = wire_name(variant);
+            one_of.push(match &rust_enum.representation {
+                EnumRepresentation::Untagged => payload.unwrap_or_else(|| json!({"type": "null"})),
+                EnumRepresentation::External => match payload {
+                    Some(payload) => json!({
+                        "type": "object",
+                        "properties": {wire_name.clone(): payload},
+                        "required": [wire_name],
+                        "additionalProperties": false,
+                    }),
+                    None => json!({"const": wire_name}),
+                },
+                EnumRepresentation::Internal { tag } => tagged_object(tag, &wire_name, payload),
+                EnumRepresentation::Adjacent { tag, content } => match payload {
+                    Some(payload) => json!({
+                        "type": "object",
+                        "properties": {tag.clone(): {"const": wire_name}, content.clone(): payload},
+                        "required": [tag, content],
+                    }),
+                    None => tagged_object(tag, &wire_name, None),
+                },
+            });
+        }
+        json!({"oneOf": one_of})
+    }
+
+    fn type_schema(&self, ty: &TypeRef) -> Value {
+        match ty {
+            // Generated types win over library types that happen to share a name
+            TypeRef::Named(name) if self.types.structs.iter().any(|s| s.name == *name) || self.types.enums.iter().any(|e| e.name == *name) => {
+                match name == self.root_name {
+                    true => json!({"$ref": "#"}),
+                    false => json!({"$ref": format!("#/$defs/{}", name)}),
+                }
+            }
+            TypeRef::Optional(inner) => nullable(self.type_schema(inner)),
+            TypeRef::List(element) => json!({"type": "array", "items": self.type_schema(element)}),
+            TypeRef::Bytes => json!({"type": "array", "items": integer_schema(false, 8)}),
+            TypeRef::Tuple(items) if items.is_empty() => json!({"type": "null"}),
+            TypeRef::Tuple(items) => {
+                let items: Vec<Value> = items.iter().map(|item| self.type_schema(item)).collect();
+                json!({"type": "array", "prefixItems": items, "items": false, "minItems": items.len()})
+            }
+            TypeRef::Map(key, value) => {
+                let mut schema = json!({"type": "object", "additionalProperties": self.type_schema(value)});
+                // serde_json writes non-string keys as their string form
+                match key {
+                    MapKey::Boolean => schema["propertyNames"] = json!({"enum": ["true", "false"]}),
+                    MapKey::Integer => schema["propertyNames"] = json!({"pattern": "^-?[0-9]+$"}),
+                    MapKey::String => {}
+                }
+                schema
+            }
+            TypeRef::String => json!({"type": "string"}),
+            TypeRef::Char => json!({"type": "string", "minLength": 1, "maxLength": 1}),
+            TypeRef::Boolean => json!({"type": "boolean"}),
+            TypeRef::Integer { signed, bits } => integer_schema(*signed, *bits),
+            TypeRef::Float => json!({"type": "number"}),
+            TypeRef::Format(StringFormat::DateTime) => json!({"type": "string", "format": "date-time"}),
+            TypeRef::Format(StringFormat::Date) => json!({"type": "string", "format": "date"}),
+            TypeRef::Format(StringFormat::Uuid) => json!({"type": "string", "format": "uuid"}),
+            TypeRef::Format(StringFormat::Url) => json!({"type": "string", "format": "uri"}),
+            TypeRef::Format(StringFormat::IpAddr) => json!({"type": "string", "anyOf": [{"format": "ipv4"}, {"format": "ipv6"}]}),
+            TypeRef::Format(StringFormat::Base64) => json!({"type": "string", "contentEncoding": "base64"}),
+            // serde_json::Value, TOML datetimes outside TOML and anything unknown accept any JSON
+            TypeRef::Format(StringFormat::TomlDatetime) | TypeRef::Named(_) | TypeRef::Any => json!({}),
+        }
+    }
+}
+
+/// `schema` with the type's docs as its `description`.
+fn described(mut schema: Value, docs: &[String]) -> Value {
+    if let (Value::Object(map), false) = (&mut schema, docs.is_empty()) {
+        map.insert("description".to_string(), json!(docs.join("\n")));
+    }
+    schema
+}
+
+/// An internally tagged variant: its fields, or its payload, plus the tag.
+fn tagged_object(tag: &str, value: &str, payload: Option<Value>) -> Value {
+    let tag_only = json!({"type": "object", "properties": {tag: {"const": value}}, "required": [tag]});
+    let Some(payload) = payload else { return tag_only };
+    match (&payload["properties"], &payload["required"]) {
+        (Value::Object(fields), required) if payload.get("allOf").is_none() && payload.get("additionalProperties").is_none() => {
+            let mut properties = Map::new();
+            properties.insert(tag.to_string(), json!({"const": value}));
+            properties.extend(fields.clone());
+            let mut required_fields = vec![json!(tag)];
+            required_fields.extend(required.as_array().cloned().unwrap_or_default());
+            json!({"type": "object", "properties": properties, "required": required_fields})
+        }
+        _ => json!({"allOf": [tag_only, payload]}),
+    }
+}
+
+fn nullable(schema: Value) -> Value {
+    let allows_null = match &schema {
+        Value::Object(object) if object.is_empty() => true,
+        _ => match (&schema["type"], &schema["anyOf"]) {
+            (Value::String(kind), _) => kind == "null",
+            (Value::Array(kinds), _) => kinds.contains(&json!("null")),
+            (_, Value::Array(members)) => members.contains(&json!({"type": "null"})),
+            _ => false,
+        },
+    };
+    if allows_null {
+        return schema;
+    }
+    match &schema["type"] {
+        // Restrictions such as `enum` or `format` would apply to null as well
+        Value::String(kind) if schema.as_object().is_some_and(|object| object.len() == 1) => json!({"type": [kind, "null"]}),
+        _ => json!({"anyOf": [schema, {"type": "null"}]}),
+    }
+}
+
+fn integer_schema(signed: bool, bits: u8) -> Value {
+    let mut schema = json!({"type": "integer"});
+    // 64-bit and wider integers only keep their sign
+    if bits <= 32 {
+        let max = (1i64 << (bits - signed as u8)) - 1;
+        schema["minimum"] = json!(if signed { -max - 1 } else { 0 });
+        schema["maximum"] = json!(max);
+    } else if !signed {
+        schema["minimum"] = json!(0);
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::analyze_json;
+    use crate::codegen::generate_rust_types_with_config;
+
+    fn export(json: &str, root_name: &str, config: &GenerationConfig) -> Value {
+        let schema = analyze_json(json, root_name).unwrap();
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, config).unwrap();
+        json_schema_document(&types, root_name).unwrap()
+    }
+
+    #[test]
+    fn test_export_structs_and_required() {
+        let json = r#"[
+            {"id": 1, "userName": "ann", "address": {"city": "Oslo"}, "tags": ["a"], "role": "admin"},
+            {"id": 2, "userName": "bo", "address": {"city": "Rome"}, "tags": [], "role": "user", "nickname": null},
+            {"id": 3, "userName": "cy", "address": {"city": "Lima"}, "tags": []},
+            {"id": 4, "userName": "di", "address": {"city": "Kyiv"}, "tags": ["b"], "role": "admin"},
+            {"id": 5, "userName": "ed", "address": {"city": "Bern"}, "tags": ["c"], "role": "user"}
+        ]"#;
+        let config = GenerationConfig { string_enums: true, ..GenerationConfig::default() };
+        let document = export(json, "Users", &config);
+
+        assert_eq!(document["$schema"], DIALECT);
+        assert_eq!(document["properties"]["items"]["items"], json!({"$ref": "#/$defs/User"}));
+        let user = &document["$defs"]["User"];
+        assert_eq!(user["required"], json!(["id", "userName", "address", "tags"]), "{:#}", document);
+        assert_eq!(user["properties"]["id"], json!({"type": "integer", "minimum": 0, "maximum": u32::MAX}));
+        assert_eq!(user["properties"]["address"], json!({"$ref": "#/$defs/Address"}));
+        assert_eq!(user["properties"]["nickname"], json!({}));
+        assert_eq!(user["properties"]["role"], json!({"anyOf": [{"$ref": "#/$defs/Role"}, {"type": "null"}]}));
+        assert_eq!(document["$defs"]["Role"], json!({"type": "string", "enum": ["admin", "user"]}));
+        assert_eq!(document["$defs"]["Address"]["required"], json!(["city"]));
+    }
+
+    #[test]
+    fn test_export_field_attributes() {
+        let field = |name: &str, type_name: &str| RustField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            ty: crate::parser::type_ref_from_rust(type_name),
+            is_optional: false,
+            serde_rename: None,
+            serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
+        };
+        let fields = vec![
+            RustField { serde_rename: Some("type".to_string()), ..field("r#type", "String") },
+            RustField {
+                serde_with: Some("serde_with::As::<serde_with::base64::Base64>".to_string()),
+                ty: TypeRef::Format(StringFormat::Base64),
+                ..field("payload", "Vec<u8>")
+            },
+            RustField { is_optional: true, ..field("labels", "std::collections::BTreeMap<i64, String>") },
+            RustField { serde_default: true, ..field("point", "(f64, f64)") },
+            RustField { serde_flatten: true, ..field("entries", "std::collections::HashMap<String, u8>") },
+        ];
+        let types = GeneratedTypes {
+            structs: vec![RustStruct { name: "Packet".to_string(), fields, derives: Vec::new(), is_optional: false, docs: vec!["A network packet.".to_string()] }],
+            enums: Vec::new(),
+        };
+        let document = json_schema_document(&types, "Packet").unwrap();
+
+        assert_eq!(document["description"], "A network packet.");
+        assert_eq!(document["required"], json!(["type", "payload"]));
+        assert_eq!(document["properties"]["type"], json!({"type": "string"}));
+        assert_eq!(document["properties"]["payload"], json!({"type": "string", "contentEncoding": "base64"}));
+        assert_eq!(
+            document["properties"]["labels"],
+            json!({"anyOf": [{"type": "object", "additionalProperties": {"type": "string"}, "propertyNames": {"pattern": "^-?[0-9]+$"}}, {"type": "null"}]})
+        );
+        assert_eq!(
+            document["properties"]["point"],
+            json!({"type": "array", "prefixItems": [{"type": "number"}, {"type": "number"}], "items": false, "minItems": 2})
+        );
+        assert_eq!(document["additionalProperties"], json!({"type": "integer", "minimum": 0, "maximum": 255}));
+        assert!(document.get("$defs").is_none());
+    }
+
+    #[test]
+    fn test_export_enums() {
+        let json = r#"{
+            "readings": [{"value": 1}, {"value": "two"}],
+            "events": [{"type": "click", "x": 1}, {"type": "key", "code": "a"}, {"type": "click", "x": 2}],
+            "parent": {"name": "a", "parent": {"name": "b"}}
+        }"#;
+        let document = export(json, "Root", &GenerationConfig::default());
+
+        let reading = &document["$defs"]["Reading"];
+        assert_eq!(reading["properties"]["value"], json!({"$ref": "#/$defs/ValueType"}), "{:#}", document);
+        let union = &document["$defs"]["ValueType"];
+        assert_eq!(union["oneOf"], json!([{"type": "integer", "minimum": 0, "maximum": u32::MAX}, {"type": "string"}]), "{:#}", document);
+
+        let event = &document["$defs"]["Event"];
+        assert_eq!(event["oneOf"][0]["properties"]["type"], json!({"const": "click"}), "{:#}", document);
+        assert_eq!(event["oneOf"][0]["required"], json!(["type", "x"]));
+        assert_eq!(event["oneOf"][1]["properties"]["code"], json!({"type": "string"}));
+
+        let text = generate_json_schema_with_types(
+            &generate_rust_types_with_config(&analyze_json(json, "Root").unwrap(), &[], &MergeStrategy::Optional, &GenerationConfig::default()).unwrap(),
+            "Root",
+        )
+        .unwrap();
+        assert!(text.starts_with("{\n  \"$schema\""), "{}", text);
+    }
+
+    #[test]
+    fn test_nullable() {
+        assert_eq!(nullable(json!({"type": "string"})), json!({"type": ["string", "null"]}));
+        assert_eq!(nullable(json!({"$ref": "#/$defs/A"})), json!({"anyOf": [{"$ref": "#/$defs/A"}, {"type": "null"}]}));
+        assert_eq!(nullable(nullable(json!({"type": "integer", "minimum": 0}))), json!({"anyOf": [{"type": "integer", "minimum": 0}, {"type": "null"}]}));
+        assert_eq!(nullable(json!({})), json!({}));
+    }
+}
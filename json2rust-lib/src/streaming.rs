@@ -0,0 +1,599 @@
+use crate::analyzer::*;
+use crate::formats::detect_string_format;
+use crate::types::*;
+use indexmap::IndexMap;
+use serde::de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{BufReader, Read};
+
+// The toml crate hands datetimes to visitors as a map holding just this key
+const TOML_DATETIME_KEY: &str = "$__toml_private_datetime";
+// YAML core tags serde_yaml reads as plain strings, renamed to local tags by `input.rs`
+pub(crate) const YAML_BINARY_TAG: &str = "json2rust:binary";
+pub(crate) const YAML_TIMESTAMP_TAG: &str = "json2rust:timestamp";
+
+/// Analyzes a JSON document straight from `reader`, folding tokens into the schema as they
+/// are parsed instead of building a `serde_json::Value` first. The result is the same as
+/// `analyze_json_with_options` on the whole text, but memory stays bounded by the size of
+/// the inferred schema rather than the document.
+pub fn analyze_json_reader<R: Read>(reader: R, root_name: &str, options: &AnalysisOptions) -> Result<JsonSchema, Json2RustError> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let schema = analyze_deserializer(&mut deserializer, root_name, options, Json2RustError::JsonParsing)?;
+    deserializer.end()?;
+    Ok(schema)
+}
+
+/// Runs the same analysis over any self-describing serde format. Deserializer errors go
+/// through `parse_error`; analysis errors are returned as they are.
+pub(crate) fn analyze_deserializer<'de, D, F>(
+    deserializer: D,
+    root_name: &str,
+    options: &AnalysisOptions,
+    parse_error: F,
+) -> Result<JsonSchema, Json2RustError>
+where
+    D: de::Deserializer<'de>,
+    F: FnOnce(D::Error) -> Json2RustError,
+{
+    analyze_deserializer_with_widths(deserializer, root_name, options, false, parse_error)
+}
+
+/// `analyze_deserializer` for formats that encode the width of every integer, such as
+/// MessagePack and CBOR, where even 64-bit integers keep their width instead of a range.
+pub(crate) fn analyze_deserializer_with_widths<'de, D, F>(
+    deserializer: D,
+    root_name: &str,
+    options: &AnalysisOptions,
+    integer_widths: bool,
+    parse_error: F,
+) -> Result<JsonSchema, Json2RustError>
+where
+    D: de::Deserializer<'de>,
+    F: FnOnce(D::Error) -> Json2RustError,
+{
+    let context = Context { options, integer_widths, error: RefCell::new(None) };
+    let seed = ValueSeed { context: &context, path: String::new(), is_root: true };
+    let analyzed = match seed.deserialize(deserializer) {
+        Ok(analyzed) => analyzed,
+        Err(error) => return Err(context.error.into_inner().unwrap_or_else(|| parse_error(error))),
+    };
+    Ok(JsonSchema {
+        name: root_name.to_string(),
+        json_type: analyzed.json_type,
+        optional: false,
+    })
+}
+
+struct Context<'a> {
+    options: &'a AnalysisOptions,
+    integer_widths: bool,  // 64-bit integers are fixed-width too, not just the narrower ones
+    error: RefCell<Option<Json2RustError>>,  // Analysis error that aborted parsing
+}
+
+impl Context<'_> {
+    /// Passes analysis errors through the deserializer, keeping the original for the caller.
+    fn check<T, E: de::Error>(&self, result: Result<T, Json2RustError>) -> Result<T, E> {
+        result.map_err(|error| {
+            let message = error.to_string();
+            *self.error.borrow_mut() = Some(error);
+            E::custom(message)
+        })
+    }
+
+    /// Whether a map path points at or below `prefix`, so values there analyze differently
+    /// depending on the path they are reached by.
+    fn has_map_path_under(&self, prefix: &str) -> bool {
+        self.options.map_paths.iter().any(|map_path| {
+            let map_path = map_path.strip_prefix('$').unwrap_or(map_path);
+            map_path.strip_prefix('.').unwrap_or(map_path).starts_with(prefix)
+        })
+    }
+}
+
+/// A value folded into its type, plus what the enclosing object or array still needs to
+/// decide between a struct, a map and a tagged enum.
+struct Analyzed {
+    json_type: JsonType,
+    kind: ValueKind,
+    text: Option<String>,          // Contents of a string
+    object: Option<ObjectFacts>,   // Entries of an object
+}
+
+struct ObjectFacts {
+    entries: IndexMap<String, EntryFacts>,
+    fields: Option<ObjectStats>,  // Fields at their own paths, kept when the object became a map
+}
+
+struct EntryFacts {
+    kind: ValueKind,
+    text: Option<String>,
+    keys: Option<Vec<String>>,  // Keys of an object value
+}
+
+impl Analyzed {
+    fn scalar(json_type: JsonType, kind: ValueKind) -> Self {
+        Self { json_type, kind, text: None, object: None }
+    }
+
+    /// The fields of an object as a struct would see them, whatever it was inferred as.
+    fn field_stats(&self) -> Option<&ObjectStats> {
+        match &self.json_type {
+            JsonType::Object(stats) => Some(stats),
+            _ => self.object.as_ref()?.fields.as_ref(),
+        }
+    }
+
+    fn entry_facts(&self) -> EntryFacts {
+        EntryFacts {
+            kind: self.kind,
+            text: self.text.clone(),
+            keys: self.object.as_ref().map(|object| object.entries.keys().cloned().collect()),
+        }
+    }
+}
+
+struct ValueSeed<'c, 'a> {
+    context: &'c Context<'a>,
+    path: String,  // Location in `AnalysisOptions::map_paths` syntax
+    is_root: bool,
+}
+
+impl ValueSeed<'_, '_> {
+    fn child(&self, path: String) -> Self {
+        ValueSeed { context: self.context, path, is_root: false }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_, '_> {
+    type Value = Analyzed;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Analyzed, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ValueSeed<'_, '_> {
+    type Value = Analyzed;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Analyzed, E> {
+        Ok(Analyzed::scalar(JsonType::Null, ValueKind::Null))
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Analyzed, E> {
+        Ok(Analyzed::scalar(JsonType::Boolean, ValueKind::Bool))
+    }
+
+    // Only binary formats report the narrow widths; text formats go through i64 and u64
+    fn visit_i8<E>(self, _: i8) -> Result<Analyzed, E> {
+        Ok(fixed_int(true, 8))
+    }
+
+    fn visit_i16<E>(self, _: i16) -> Result<Analyzed, E> {
+        Ok(fixed_int(true, 16))
+    }
+
+    fn visit_i32<E>(self, _: i32) -> Result<Analyzed, E> {
+        Ok(fixed_int(true, 32))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<Analyzed, E> {
+        if self.context.integer_widths {
+            return Ok(fixed_int(true, 64));
+        }
+        Ok(Analyzed::scalar(number_type(&n.into()), ValueKind::Number))
+    }
+
+    fn visit_u8<E>(self, _: u8) -> Result<Analyzed, E> {
+        Ok(fixed_int(false, 8))
+    }
+
+    fn visit_u16<E>(self, _: u16) -> Result<Analyzed, E> {
+        Ok(fixed_int(false, 16))
+    }
+
+    fn visit_u32<E>(self, _: u32) -> Result<Analyzed, E> {
+        Ok(fixed_int(false, 32))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> Result<Analyzed, E> {
+        if self.context.integer_widths {
+            return Ok(fixed_int(false, 64));
+        }
+        Ok(Analyzed::scalar(number_type(&n.into()), ValueKind::Number))
+    }
+
+    fn visit_i128<E>(self, n: i128) -> Result<Analyzed, E> {
+        // Beyond 64 bits, like the JSON parser does
+        Ok(Analyzed::scalar(JsonType::Float { min: n as f64, max: n as f64 }, ValueKind::Number))
+    }
+
+    fn visit_u128<E>(self, n: u128) -> Result<Analyzed, E> {
+        Ok(Analyzed::scalar(JsonType::Float { min: n as f64, max: n as f64 }, ValueKind::Number))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<Analyzed, E> {
+        // JSON5 `Infinity` / `NaN` and YAML `.inf` / `.nan` still need an `f64` to land in
+        Ok(match serde_json::Number::from_f64(n) {
+            Some(n) => Analyzed::scalar(number_type(&n), ValueKind::Number),
+            None => Analyzed::scalar(JsonType::Float { min: n, max: n }, ValueKind::Number),
+        })
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Analyzed, E> {
+        Ok(Analyzed {
+            json_type: string_type(s),
+            kind: ValueKind::String,
+            text: Some(s.to_string()),
+            object: None,
+        })
+    }
+
+    fn visit_bytes<E>(self, _: &[u8]) -> Result<Analyzed, E> {
+        Ok(Analyzed::scalar(JsonType::Bytes, ValueKind::Bytes))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Analyzed, D::Error> {
+        // A MessagePack extension, read as its type code and payload
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Analyzed, A::Error> {
+        // A YAML local tag such as `!Ref name`, read like the untagged value. The core tags
+        // `!!binary` and `!!timestamp` arrive here too, as local tags (see `input.rs`)
+        let (tag, variant) = data.variant::<String>()?;
+        match tag.as_str() {
+            YAML_BINARY_TAG => {
+                variant.newtype_variant::<de::IgnoredAny>()?;
+                Ok(declared_string(StringFormat::Base64))
+            }
+            YAML_TIMESTAMP_TAG => {
+                let analyzed = variant.newtype_variant_seed(self)?;
+                // Space-separated YAML timestamps do not fit a `chrono` type and stay strings
+                Ok(match analyzed.text.as_deref().and_then(detect_string_format) {
+                    Some(format @ (StringFormat::DateTime | StringFormat::Date)) => declared_string(format),
+                    _ => analyzed,
+                })
+            }
+            _ => variant.newtype_variant_seed(self),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Analyzed, A::Error> {
+        let item_path = format!("{}[]", self.path);
+        let mut array = ArrayAccumulator::new();
+        while let Some(item) = seq.next_element_seed(self.child(item_path.clone()))? {
+            self.context.check(array.push(item))?;
+        }
+        Ok(Analyzed::scalar(array.finish(self.is_root), ValueKind::Array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Analyzed, A::Error> {
+        let options = self.context.options;
+        let value_path = join_path(&self.path, "*");
+        let mut entries: IndexMap<String, (EntryFacts, JsonType, Option<JsonType>)> = IndexMap::new();
+        let mut key_kind = None;
+
+        let mut next_key = map.next_key_seed(KeySeed)?;
+        if next_key.as_ref().is_some_and(|(key, kind)| *kind == MapKey::String && key == TOML_DATETIME_KEY) {
+            let text: String = map.next_value()?;
+            return Ok(Analyzed {
+                json_type: JsonType::String(StringStats { format: Some(StringFormat::TomlDatetime), values: None, samples: 1, declared: false }),
+                kind: ValueKind::String,
+                text: Some(text),
+                object: None,
+            });
+        }
+        while let Some((key, kind)) = next_key {
+            key_kind = Some(match key_kind {
+                Some(seen) if seen != kind => MapKey::String,
+                _ => kind,
+            });
+            let field_path = join_path(&self.path, &key);
+            let entry = if self.context.has_map_path_under(&value_path) || self.context.has_map_path_under(&field_path) {
+                // The value is typed differently as a field and as a map value, so keep it
+                // around long enough to analyze it both ways
+                let value: Value = map.next_value()?;
+                let field_type = self.context.check(analyze_json_value(&value, "", &field_path, options))?.json_type;
+                let map_type = self.context.check(analyze_json_value(&value, "", &value_path, options))?.json_type;
+                let facts = EntryFacts {
+                    kind: value_kind(&value),
+                    text: value.as_str().map(str::to_string),
+                    keys: value.as_object().map(|object| object.keys().cloned().collect()),
+                };
+                (facts, field_type, Some(map_type))
+            } else {
+                let analyzed = map.next_value_seed(self.child(field_path))?;
+                (analyzed.entry_facts(), analyzed.json_type, None)
+            };
+            entries.insert(key, entry);
+            next_key = map.next_key_seed(KeySeed)?;
+        }
+
+        // Only a map can have keys that are not strings
+        let key_kind = key_kind.unwrap_or(MapKey::String);
+        let is_map = key_kind != MapKey::String || is_map_path(&self.path, options) || (options.detect_maps && {
+            let shapes: Vec<EntryShape> = entries
+                .iter()
+                .map(|(key, (facts, _, _))| EntryShape {
+                    key,
+                    kind: facts.kind,
+                    keys: facts.keys.as_ref().map(|keys| keys.iter().map(String::as_str).collect()),
+                })
+                .collect();
+            is_map_like(&shapes)
+        });
+
+        let (json_type, fields) = if is_map {
+            let value_types = entries.values().map(|(_, field_type, map_type)| map_type.as_ref().unwrap_or(field_type).clone());
+            let value_type = self.context.check(merge_element_types(value_types))?;
+            let fields = entries.iter().map(|(key, (_, field_type, _))| (key.clone(), field_type.clone())).collect();
+            (JsonType::Map(key_kind, Box::new(value_type)), Some(ObjectStats::from_fields(fields)))
+        } else {
+            let fields = entries.iter_mut().map(|(key, (_, field_type, _))| (key.clone(), std::mem::replace(field_type, JsonType::Null))).collect();
+            (JsonType::Object(ObjectStats::from_fields(fields)), None)
+        };
+
+        Ok(Analyzed {
+            json_type,
+            kind: ValueKind::Object,
+            text: None,
+            object: Some(ObjectFacts {
+                entries: entries.into_iter().map(|(key, (facts, _, _))| (key, facts)).collect(),
+                fields,
+            }),
+        })
+    }
+}
+
+fn fixed_int(signed: bool, bits: u8) -> Analyzed {
+    Analyzed::scalar(JsonType::FixedInt { signed, bits }, ValueKind::Number)
+}
+
+fn declared_string(format: StringFormat) -> Analyzed {
+    let stats = StringStats { format: Some(format), values: None, samples: 1, declared: true };
+    Analyzed::scalar(JsonType::String(stats), ValueKind::String)
+}
+
+/// Reads a map key of any scalar kind, along with its text for paths and field names.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = (String, MapKey);
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(String, MapKey), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed {
+    type Value = (String, MapKey);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, integer or boolean map key")
+    }
+
+    fn visit_str<E>(self, key: &str) -> Result<(String, MapKey), E> {
+        Ok((key.to_string(), MapKey::String))
+    }
+
+    fn visit_i64<E>(self, key: i64) -> Result<(String, MapKey), E> {
+        Ok((key.to_string(), MapKey::Integer))
+    }
+
+    fn visit_u64<E>(self, key: u64) -> Result<(String, MapKey), E> {
+        // Keys beyond i64 are left as strings rather than typed too narrowly
+        let kind = if i64::try_from(key).is_ok() { MapKey::Integer } else { MapKey::String };
+        Ok((key.to_string(), kind))
+    }
+
+    fn visit_bool<E>(self, key: bool) -> Result<(String, MapKey), E> {
+        Ok((key.to_string(), MapKey::Boolean))
+    }
+}
+
+/// Folds array elements one at a time into everything the batch analyzer would decide on:
+/// the merged element type, the leading elements for tuple detection, and every string field
+/// that could still turn out to be the tag of a tagged enum.
+struct ArrayAccumulator {
+    count: usize,
+    element_type: JsonType,
+    has_null: bool,                // Some element was null, so the elements are `Nullable`
+    leading_types: Vec<JsonType>,  // Types of the first MAX_TUPLE_LEN + 1 elements
+    tags: Vec<TagCandidate>,       // Emptied for good by any element that is not an object
+}
+
+impl ArrayAccumulator {
+    fn new() -> Self {
+        Self { count: 0, element_type: JsonType::Null, has_null: false, leading_types: Vec::new(), tags: Vec::new() }
+    }
+
+    fn push(&mut self, item: Analyzed) -> Result<(), Json2RustError> {
+        match (&item.object, item.field_stats()) {
+            (Some(object), Some(stats)) => {
+                if self.count == 0 {
+                    self.tags = TagCandidate::seed(object);
+                }
+                let mut tags = Vec::with_capacity(self.tags.len());
+                for mut candidate in std::mem::take(&mut self.tags) {
+                    if candidate.add(object, stats)? {
+                        tags.push(candidate);
+                    }
+                }
+                self.tags = tags;
+            }
+            _ => self.tags.clear(),
+        }
+
+        self.count += 1;
+        if self.leading_types.len() <= MAX_TUPLE_LEN {
+            self.leading_types.push(item.json_type.clone());
+        }
+        self.has_null |= item.json_type == JsonType::Null;
+        let element_type = std::mem::replace(&mut self.element_type, JsonType::Null);
+        self.element_type = merge_types(element_type, item.json_type)?;
+        Ok(())
+    }
+
+    fn finish(self, is_root: bool) -> JsonType {
+        if self.count == 0 {
+            // A top-level `[]` is most likely an empty list of records
+            let element_type = if is_root { JsonType::Object(ObjectStats::default()) } else { JsonType::Null };
+            return JsonType::Array(Box::new(element_type));
+        }
+        let count = self.count;
+        if let Some(tagged) = self.tags.into_iter().find_map(|candidate| candidate.finish(count)) {
+            return JsonType::Array(Box::new(JsonType::Tagged(tagged)));
+        }
+        if !is_root && is_mixed(&self.element_type) && count <= MAX_TUPLE_LEN {
+            JsonType::Tuple(self.leading_types)
+        } else if self.has_null {
+            JsonType::Array(Box::new(nullable(self.element_type)))
+        } else {
+            JsonType::Array(Box::new(self.element_type))
+        }
+    }
+}
+
+/// One string field of the first element, tracked as both an internal and an adjacent tag
+/// until the elements rule either reading out.
+struct TagCandidate {
+    tag: String,
+    content: Option<String>,  // Payload key while every element is a two-key envelope
+    groups: IndexMap<String, HashSet<String>>,
+    variants: IndexMap<String, JsonType>,
+    adjacent_groups: IndexMap<String, HashSet<String>>,
+    adjacent_variants: IndexMap<String, JsonType>,
+}
+
+impl TagCandidate {
+    /// Conventional discriminator names go first, as in the batch analyzer.
+    fn seed(first: &ObjectFacts) -> Vec<TagCandidate> {
+        let mut tags: Vec<&String> = first
+            .entries
+            .iter()
+            .filter(|(_, facts)| facts.kind == ValueKind::String)
+            .map(|(key, _)| key)
+            .collect();
+        tags.sort_by_key(|key| !TAG_FIELD_NAMES.contains(&key.as_str()));
+        tags.into_iter()
+            .map(|tag| TagCandidate {
+                tag: tag.clone(),
+                content: first.entries.keys().find(|key| *key != tag).cloned(),
+                groups: IndexMap::new(),
+                variants: IndexMap::new(),
+                adjacent_groups: IndexMap::new(),
+                adjacent_variants: IndexMap::new(),
+            })
+            .collect()
+    }
+
+    /// Records one element, returning whether the field can still be the tag.
+    fn add(&mut self, object: &ObjectFacts, stats: &ObjectStats) -> Result<bool, Json2RustError> {
+        let Some(tag_value) = object.entries.get(&self.tag).and_then(|facts| facts.text.clone()) else {
+            return Ok(false);
+        };
+
+        self.groups
+            .entry(tag_value.clone())
+            .or_default()
+            .extend(object.entries.keys().filter(|key| **key != self.tag).cloned());
+        if self.groups.len() > MAX_TAGGED_VARIANTS {
+            return Ok(false);
+        }
+        let mut payload = stats.clone();
+        payload.fields.shift_remove(&self.tag);
+        add_tagged_variant(&mut self.variants, tag_value.clone(), JsonType::Object(payload))?;
+
+        let envelope = self.content.as_ref().and_then(|content| {
+            let keys = object.entries.get(content)?.keys.as_ref()?;
+            (object.entries.len() == 2).then_some((content, keys))
+        });
+        match envelope {
+            Some((content, keys)) => {
+                self.adjacent_groups.entry(tag_value.clone()).or_default().extend(keys.iter().cloned());
+                let payload = stats.field_type(content).cloned().unwrap_or(JsonType::Null);
+                add_tagged_variant(&mut self.adjacent_variants, tag_value, payload)?;
+            }
+            None => {
+                self.content = None;
+                self.adjacent_groups.clear();
+                self.adjacent_variants.clear();
+            }
+        }
+        Ok(true)
+    }
+
+    fn finish(self, count: usize) -> Option<TaggedUnion> {
+        let (groups, variants) = match self.content {
+            Some(_) => (self.adjacent_groups, self.adjacent_variants),
+            None => (self.groups, self.variants),
+        };
+        let group_keys: Vec<&HashSet<String>> = groups.values().collect();
+        is_tagged_grouping(&self.tag, &group_keys, count).then_some(TaggedUnion {
+            tag: self.tag,
+            content: self.content,
+            variants,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_schema(json: &str, options: &AnalysisOptions) {
+        let expected = analyze_json_with_options(json, "Root", options).unwrap();
+        let streamed = analyze_json_reader(json.as_bytes(), "Root", options).unwrap();
+        assert_eq!(streamed.json_type, expected.json_type, "{}", json);
+    }
+
+    #[test]
+    fn test_streaming_matches_batch_analysis() {
+        let documents = [
+            r#"{"id": 1, "name": "Alice", "tags": ["a", "b"], "address": {"city": "Paris", "zip": null}}"#,
+            r#"[{"id": 1, "score": 2.5}, {"id": -3, "extra": true}]"#,
+            r#"[]"#,
+            r#"{"empty": [], "pair": [1, "one"], "long": [1, "a", 2, "b", 3, "c", 4, "d", 5]}"#,
+            r#"[{"type": "click", "x": 1, "y": 2}, {"type": "key", "code": "A"}, {"type": "click", "x": 3, "y": 4}]"#,
+            r#"{"events": [{"kind": "a", "data": {"n": 1}}, {"kind": "b", "data": {"s": "x"}}]}"#,
+            r#"{"users": {"u1": {"name": "a"}, "u2": {"name": "b"}, "u3": {"name": "c"}, "u4": {"name": "d", "age": 4}}}"#,
+            r#"[1, "two", null, 3.5]"#,
+            r#"{"values": [1, null, 2], "rows": [[1, "a", true], [null, "b", false]], "scores": {"a": 1, "b": null}, "nested_nulls": [[null], [1]]}"#,
+            r#""just a string""#,
+            r#"{"big": 18446744073709551615, "neg": -9223372036854775808, "when": "2024-01-15T10:30:00Z"}"#,
+        ];
+        for json in documents {
+            assert_same_schema(json, &AnalysisOptions::default());
+        }
+    }
+
+    #[test]
+    fn test_streaming_respects_map_paths() {
+        let options = AnalysisOptions {
+            map_paths: vec!["$.scores".to_string(), "scores.*.history".to_string()],
+            ..AnalysisOptions::default()
+        };
+        assert_same_schema(
+            r#"{"scores": {"alice": {"best": 3, "history": {"a": 1}}, "bob": {"best": 5, "history": {"b": 2}}}}"#,
+            &options,
+        );
+
+        let options = AnalysisOptions { detect_maps: false, ..AnalysisOptions::default() };
+        assert_same_schema(r#"{"1": "a", "2": "b", "3": "c", "4": "d"}"#, &options);
+    }
+
+    #[test]
+    fn test_streaming_reports_invalid_json() {
+        let error = analyze_json_reader(r#"{"id": 1"#.as_bytes(), "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(matches!(error, Json2RustError::JsonParsing(_)));
+        let error = analyze_json_reader(r#"{"id": 1} {}"#.as_bytes(), "Root", &AnalysisOptions::default()).unwrap_err();
+        assert!(matches!(error, Json2RustError::JsonParsing(_)));
+    }
+}
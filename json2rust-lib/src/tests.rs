@@ -90,6 +90,35 @@ mod existing_struct_tests {
         assert_eq!(total_field.type_name, "i64");
     }
     
+    #[test]
+    fn test_numeric_types_widen_for_new_samples() {
+        let existing_code = r#"
+            struct Reading {
+                id: u32,
+                delta: u32,
+                value: i64,
+                note: String,
+            }
+        "#;
+        
+        let json = r#"[{"id": 1, "delta": -4, "value": 2.5, "note": "a"}, {"id": 2, "delta": 3, "value": 1, "note": "b"}]"#;
+        
+        let existing_structs = parse_existing_structs(existing_code).unwrap();
+        let schema = analyze_json(json, "Readings").unwrap();
+        let rust_structs = generate_rust_structs(&schema, &existing_structs).unwrap();
+        
+        let reading = rust_structs.iter().find(|s| s.name == "Reading").unwrap();
+        let field_type = |name: &str| reading.fields.iter().find(|f| f.name == name).unwrap().type_name.clone();
+        assert_eq!(field_type("id"), "u32");
+        assert_eq!(field_type("delta"), "i64");
+        assert_eq!(field_type("value"), "f64");
+        
+        // The widened types accept every sample
+        let code = generate_code(&rust_structs).unwrap();
+        assert!(code.contains("pub delta: i64,"), "{}", code);
+        assert!(code.contains("pub value: f64,"), "{}", code);
+    }
+    
     #[test]
     fn test_option_type_handling() {
         let existing_code = r#"
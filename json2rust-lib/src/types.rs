@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 use thiserror::Error;
 
@@ -20,12 +21,242 @@ impl From<&str> for MergeStrategy {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StringFormat {
+    DateTime,  // RFC 3339 timestamp -> chrono::DateTime<chrono::Utc>
+    Date,      // ISO 8601 calendar date -> chrono::NaiveDate
+    Uuid,      // Hyphenated UUID -> uuid::Uuid
+    Url,       // Absolute URL with scheme and authority -> url::Url
+    IpAddr,    // IPv4 or IPv6 address -> std::net::IpAddr
+    Base64,    // Standard base64 blob -> Vec<u8> via serde_with
+    TomlDatetime,  // Native TOML date and/or time -> toml::value::Datetime; never detected in strings
+}
+
+impl StringFormat {
+    pub const ALL: [StringFormat; 6] = [
+        StringFormat::DateTime,
+        StringFormat::Date,
+        StringFormat::Uuid,
+        StringFormat::Url,
+        StringFormat::IpAddr,
+        StringFormat::Base64,
+    ];
+}
+
+impl std::str::FromStr for StringFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "datetime" => Ok(StringFormat::DateTime),
+            "date" => Ok(StringFormat::Date),
+            "uuid" => Ok(StringFormat::Uuid),
+            "url" => Ok(StringFormat::Url),
+            "ip" => Ok(StringFormat::IpAddr),
+            "base64" => Ok(StringFormat::Base64),
+            _ => Err(format!("Unknown string format: {}", s)),
+        }
+    }
+}
+
+/// Syntax of an input document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputFormat {
+    Json,
+    JsonLines,  // One JSON record per line
+    Json5,      // JSON5, which also covers JSONC comments and trailing commas
+    Yaml,       // One or more YAML documents, merged like samples
+    Toml,
+    Csv,        // Header row, then one record per row
+    MessagePack,  // One or more concatenated MessagePack values, merged like samples
+    Cbor,         // One or more concatenated CBOR items, merged like samples
+    JsonSchema,   // A JSON Schema document describing the data rather than a sample of it
+}
+
+impl InputFormat {
+    /// Guesses the format from a file extension, or `.schema.json` for JSON Schema.
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.to_ascii_lowercase().ends_with(".schema.json") {
+            return Some(InputFormat::JsonSchema);
+        }
+        let extension = std::path::Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(InputFormat::Json),
+            "ndjson" | "jsonl" => Some(InputFormat::JsonLines),
+            "json5" | "jsonc" => Some(InputFormat::Json5),
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            "toml" => Some(InputFormat::Toml),
+            "csv" => Some(InputFormat::Csv),
+            "msgpack" | "mpk" => Some(InputFormat::MessagePack),
+            "cbor" => Some(InputFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::JsonLines => "JSON Lines",
+            InputFormat::Json5 => "JSON5",
+            InputFormat::Yaml => "YAML",
+            InputFormat::Toml => "TOML",
+            InputFormat::Csv => "CSV",
+            InputFormat::MessagePack => "MessagePack",
+            InputFormat::Cbor => "CBOR",
+            InputFormat::JsonSchema => "JSON Schema",
+        }
+    }
+}
+
+impl From<&str> for InputFormat {
+    fn from(s: &str) -> Self {
+        match s {
+            "jsonl" | "ndjson" => InputFormat::JsonLines,
+            "json5" | "jsonc" => InputFormat::Json5,
+            "yaml" | "yml" => InputFormat::Yaml,
+            "toml" => InputFormat::Toml,
+            "csv" => InputFormat::Csv,
+            "msgpack" | "mpk" => InputFormat::MessagePack,
+            "cbor" => InputFormat::Cbor,
+            "jsonschema" => InputFormat::JsonSchema,
+            _ => InputFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MapType {
+    HashMap,
+    BTreeMap,
+}
+
+impl From<&str> for MapType {
+    fn from(s: &str) -> Self {
+        match s {
+            "btreemap" => MapType::BTreeMap,
+            _ => MapType::HashMap,
+        }
+    }
+}
+
+/// A `#[serde(rename_all = "...")]` case convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RenameRule {
+    PascalCase,          // PascalCase
+    CamelCase,           // camelCase
+    ScreamingSnakeCase,  // SCREAMING_SNAKE_CASE
+    KebabCase,           // kebab-case
+    ScreamingKebabCase,  // SCREAMING-KEBAB-CASE
+}
+
+/// How a JSON key that is a Rust keyword becomes a field name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeywordStyle {
+    Raw,     // r#type; `self`, `super` and `crate` fall back to a suffix
+    Suffix,  // type_
+}
+
+impl From<&str> for KeywordStyle {
+    fn from(s: &str) -> Self {
+        match s {
+            "suffix" => KeywordStyle::Suffix,
+            _ => KeywordStyle::Raw,
+        }
+    }
+}
+
+/// How a group of structurally identical structs is named once merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StructNaming {
+    FirstSeen,     // Name of the first struct emitted in the group
+    Shortest,      // Shortest name in the group
+    CommonSuffix,  // Shared trailing words, e.g. `Address` for `BillingAddress` and `ShippingAddress`
+}
+
+impl From<&str> for StructNaming {
+    fn from(s: &str) -> Self {
+        match s {
+            "shortest" => StructNaming::Shortest,
+            "common-suffix" => StructNaming::CommonSuffix,
+            _ => StructNaming::FirstSeen,
+        }
+    }
+}
+
+/// Options that control how JSON input is analyzed into a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisOptions {
+    pub detect_maps: bool,       // Treat objects with id-like keys and uniform values as maps
+    pub map_paths: Vec<String>,  // Paths such as `users` or `teams[].members` that are always maps
+    pub max_samples: Option<usize>,  // Cap on NDJSON records analyzed, chosen by reservoir sampling
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            detect_maps: true,
+            map_paths: Vec::new(),
+            max_samples: None,
+        }
+    }
+}
+
+/// Options that control which types are imported from an OpenAPI document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenApiOptions {
+    pub operations: bool,  // Also import request and response bodies, named after each operationId
+}
+
+/// Options that control how an analyzed schema is turned into Rust types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationConfig {
+    pub string_formats: Vec<StringFormat>,  // Formats allowed to produce typed fields instead of String
+    pub string_enums: bool,                 // Turn low-cardinality string fields into unit enums
+    pub enum_max_variants: usize,           // Upper bound on distinct values for a string enum
+    pub enum_unknown_variant: bool,         // Add a `#[serde(other)] Unknown` catch-all to string enums
+    pub map_type: MapType,                  // Collection emitted for map-like objects
+    pub serde_default: bool,                // Add `#[serde(default)]` to fields missing from some samples
+    pub double_option: bool,                // Emit optional fields as Option<Option<T>> to keep absent apart from null
+    pub dedupe_structs: bool,               // Merge structurally identical structs under one name
+    pub struct_naming: StructNaming,        // Name chosen for a group of merged structs
+    pub keyword_style: KeywordStyle,        // Escaping for keys that are Rust keywords
+    pub singular_overrides: HashMap<String, String>,  // Lowercase plural to singular, for naming array elements
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            string_formats: Vec::new(),
+            string_enums: false,
+            enum_max_variants: 16,
+            enum_unknown_variant: false,
+            map_type: MapType::HashMap,
+            serde_default: false,
+            double_option: false,
+            dedupe_structs: true,
+            struct_naming: StructNaming::FirstSeen,
+            keyword_style: KeywordStyle::Raw,
+            singular_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl GenerationConfig {
+    pub fn with_all_string_formats() -> Self {
+        Self {
+            string_formats: StringFormat::ALL.to_vec(),
+            ..Self::default()
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RustStruct {
     pub name: String,
     pub fields: Vec<RustField>,
     pub derives: Vec<String>,
     pub is_optional: bool,
+    pub docs: Vec<String>,  // Doc comment lines, written in each emitter's comment syntax
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -33,12 +264,26 @@ pub struct RustEnum {
     pub name: String,
     pub variants: Vec<RustEnumVariant>,
     pub derives: Vec<String>,
+    pub representation: EnumRepresentation,
+    pub docs: Vec<String>,  // Doc comment lines, written in each emitter's comment syntax
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EnumRepresentation {
+    Untagged,  // #[serde(untagged)], variants told apart by their fields
+    External,  // serde's default; unit variants (de)serialize as plain strings
+    Internal { tag: String },                   // #[serde(tag = "type")]
+    Adjacent { tag: String, content: String },  // #[serde(tag = "type", content = "data")]
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RustEnumVariant {
     pub name: String,
     pub fields: Vec<RustField>,
+    pub serde_rename: Option<String>,
+    pub serde_other: bool,
+    pub newtype: Option<String>,  // Payload of a tuple variant such as Bool(bool)
+    pub newtype_ty: Option<TypeRef>,  // The same payload for emitters of other languages
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -50,22 +295,127 @@ pub enum RustType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RustField {
     pub name: String,
-    pub type_name: String,
+    pub type_name: String,  // As written in Rust, e.g. `Option<Vec<User>>`
+    pub ty: TypeRef,        // The same type for emitters of other languages
     pub is_optional: bool,
     pub serde_rename: Option<String>,
     pub serde_flatten: bool,
+    pub serde_with: Option<String>,
+    pub serde_default: bool,
+}
+
+impl RustField {
+    /// Wraps the type in `Option`, unless it already is one, and marks the field optional.
+    pub fn make_optional(&mut self) {
+        if !self.type_name.starts_with("Option<") {
+            self.type_name = format!("Option<{}>", self.type_name);
+            self.ty = TypeRef::Optional(Box::new(self.ty.clone()));
+            self.is_optional = true;
+        }
+    }
+}
+
+/// A field or payload type by the shape of the data it holds, without Rust's paths, boxes or
+/// map flavors, so emitters for other languages never have to read Rust.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypeRef {
+    String,
+    Format(StringFormat),                // String in a known format; `Base64` holds bytes
+    Char,                                // String of exactly one character
+    Integer { signed: bool, bits: u8 },  // `usize` and `isize` count as 64 bits
+    Float,
+    Boolean,
+    Bytes,                               // Blob from a binary input format
+    Named(String),                       // A generated struct or enum
+    Optional(Box<TypeRef>),
+    List(Box<TypeRef>),
+    Tuple(Vec<TypeRef>),                 // The empty tuple is `()`, written as null
+    Map(MapKey, Box<TypeRef>),
+    Any,                                 // Any JSON value, or a type nothing is known about
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonType {
-    String,
-    Number,
+    String(StringStats),
+    Integer { min: i64, max: i64 },   // Every sample fits in i64
+    Unsigned { min: u64, max: u64 },  // Non-negative, at least one sample above i64::MAX
+    Float { min: f64, max: f64 },     // At least one sample has a fractional part or exponent
+    FixedInt { signed: bool, bits: u8 },  // Integer whose width the input encoded, e.g. a MessagePack uint16
     Boolean,
+    Bytes,                            // Binary blob from a binary input format
     Array(Box<JsonType>),
-    Object(HashMap<String, JsonType>),
+    Tuple(Vec<JsonType>),             // Short array whose positions hold different kinds, e.g. [lat, lng, "label"]
+    Union(Vec<JsonType>),             // Values of different kinds, at most one member per kind
+    Object(ObjectStats),
+    Map(MapKey, Box<JsonType>),       // Object keyed by ids/dates/hashes; holds the key kind and merged value type
+    Tagged(TaggedUnion),              // Objects discriminated by the value of a tag field
+    Recursive(String),                // Back-reference to the enclosing struct of this name (set by codegen)
+    Named(String, Box<JsonType>),     // Type a schema declares under a name, e.g. a `$defs` entry
+    Nullable(Box<JsonType>),          // Array element, map value or tuple slot that is null in some samples; `Nullable(Null)` if in all
     Null,
 }
 
+/// Kind of the keys of a map. JSON keys are always strings; binary formats may use others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MapKey {
+    String,
+    Integer,  // Every key fits in i64
+    Boolean,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectStats {
+    pub fields: IndexMap<String, FieldStats>,  // Fields in first-seen order
+    pub samples: usize,                        // Objects merged into this one
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldStats {
+    pub json_type: JsonType,
+    pub present: usize,  // Samples that had the key
+    pub nulls: usize,    // Samples where the value was null
+}
+
+impl ObjectStats {
+    /// Builds the stats of a single sample holding exactly these fields.
+    pub fn from_fields(fields: IndexMap<String, JsonType>) -> Self {
+        Self {
+            fields: fields
+                .into_iter()
+                .map(|(name, json_type)| {
+                    let nulls = usize::from(json_type == JsonType::Null);
+                    (name, FieldStats { json_type, present: 1, nulls })
+                })
+                .collect(),
+            samples: 1,
+        }
+    }
+    
+    pub fn field_type(&self, name: &str) -> Option<&JsonType> {
+        self.fields.get(name).map(|field| &field.json_type)
+    }
+    
+    /// A field is optional if some sample lacked it or held null.
+    pub fn is_optional(&self, field: &FieldStats) -> bool {
+        field.present < self.samples || field.nulls > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedUnion {
+    pub tag: String,                         // Discriminator key, e.g. "type"
+    pub content: Option<String>,             // Payload key of adjacently tagged envelopes, e.g. "data"
+    pub variants: IndexMap<String, JsonType>, // Tag value -> payload, in first-seen order
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StringStats {
+    pub format: Option<StringFormat>,  // Set only when every sample matched the format
+    pub values: Option<Vec<String>>,   // Distinct values in first-seen order; None once too many to track
+    pub samples: usize,
+    pub declared: bool,                // Format and values come from a schema, so they are used as given
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct JsonSchema {
     pub name: String,
@@ -76,7 +426,7 @@ pub struct JsonSchema {
 #[derive(Debug, Clone)]
 pub struct ExistingStruct {
     pub name: String,
-    pub fields: HashMap<String, String>,
+    pub fields: IndexMap<String, String>,
 }
 
 #[derive(Debug, Error)]
@@ -92,4 +442,13 @@ pub enum Json2RustError {
     
     #[error("Code generation error: {0}")]
     CodeGeneration(String),
+    
+    #[error("Invalid JSON on line {line}: {source}")]
+    InvalidRecord { line: usize, source: serde_json::Error },
+    
+    #[error("{format} parsing error: {message}")]
+    InputParsing { format: &'static str, message: String },
+    
+    #[error("Invalid schema at {pointer}: {message}")]
+    InvalidSchema { pointer: String, message: String },
 }
\ No newline at end of file
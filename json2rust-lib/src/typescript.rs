@@ -0,0 +1,305 @@
+use crate::codegen::GeneratedTypes;
+use crate::emitter::Emitter;
+use crate::identifiers::unraw;
+use crate::types::*;
+
+/// Renders generated types as TypeScript declarations (a `.d.ts` module) for the same JSON:
+/// structs become interfaces keyed by their wire names, enums become union types in the shape
+/// their serde representation gives them, and optional fields become `name?: T | null`.
+pub fn generate_typescript_with_types(types: &GeneratedTypes) -> Result<String, Json2RustError> {
+    TypeScriptEmitter.emit(types, "")
+}
+
+/// The TypeScript backend behind `generate_typescript_with_types`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypeScriptEmitter;
+
+impl Emitter for TypeScriptEmitter {
+    fn name(&self) -> &str {
+        "ts"
+    }
+
+    fn file_extension(&self) -> &str {
+        "d.ts"
+    }
+
+    fn emit_struct(&self, rust_struct: &RustStruct, types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        Ok(doc_comment(&rust_struct.docs) + &Declarations { types }.struct_declaration(rust_struct))
+    }
+
+    fn emit_enum(&self, rust_enum: &RustEnum, types: &GeneratedTypes) -> Result<String, Json2RustError> {
+        Ok(doc_comment(&rust_enum.docs) + &Declarations { types }.enum_declaration(rust_enum))
+    }
+}
+
+struct Declarations<'a> {
+    types: &'a GeneratedTypes,
+}
+
+impl Declarations<'_> {
+    fn struct_declaration(&self, rust_struct: &RustStruct) -> String {
+        let (keyed, flattened): (Vec<&RustField>, Vec<&RustField>) = rust_struct.fields.iter().partition(|field| !field.serde_flatten);
+        let flattened: Vec<String> = flattened.into_iter().map(|field| self.field_type(field)).collect();
+
+        // A struct that only flattens a map, like a root-level map wrapper, is that map
+        if keyed.is_empty() && !flattened.is_empty() {
+            return format!("export type {} = {};\n", rust_struct.name, flattened.join(" & "));
+        }
+
+        let mut code = format!("export interface {} {{\n", rust_struct.name);
+        for field in keyed {
+            code.push_str(&format!("  {};\n", self.property(field)));
+        }
+        if !flattened.is_empty() {
+            // Flattened fields add keys the interface cannot list
+            code.push_str("  [key: string]: unknown;\n");
+        }
+        code.push_str("}\n");
+        code
+    }
+
+    fn enum_declaration(&self, rust_enum: &RustEnum) -> String {
+        let mut members = Vec::new();
+        for variant in &rust_enum.variants {
+            let wire_name = string_literal(variant.serde_rename.as_deref().unwrap_or(&variant.name));
+            let payload = match &variant.newtype_ty {
+                Some(ty) => Some(self.type_expression(ty)),
+                None if !variant.fields.is_empty() => Some(self.object_literal(&variant.fields, None)),
+                None => None,
+            };
+            members.push(match (&rust_enum.representation, payload) {
+                // A `#[serde(other)]` catch-all takes any other string
+                (_, None) if variant.serde_other => "string".to_string(),
+                (EnumRepresentation::Untagged, payload) => payload.unwrap_or_else(|| "null".to_string()),
+                (EnumRepresentation::External, Some(payload)) => format!("{{ {}: {} }}", wire_name, payload),
+                (EnumRepresentation::External, None) => wire_name,
+                (EnumRepresentation::Internal { tag }, None) => format!("{{ {}: {} }}", property_name(tag), wire_name),
+                (EnumRepresentation::Internal { tag }, Some(_)) if variant.newtype_ty.is_none() => {
+                    self.object_literal(&variant.fields, Some((tag, &wire_name)))
+                }
+                (EnumRepresentation::Internal { tag }, Some(payload)) => {
+                    format!("({{ {}: {} }} & {})", property_name(tag), wire_name, payload)
+                }
+                (EnumRepresentation::Adjacent { tag, .. }, None) => format!("{{ {}: {} }}", property_name(tag), wire_name),
+                (EnumRepresentation::Adjacent { tag, content }, Some(payload)) => {
+                    format!("{{ {}: {}; {}: {} }}", property_name(tag), wire_name, property_name(content), payload)
+                }
+            });
+        }
+
+        let mut code = format!("export type {} =\n", rust_enum.name);
+        for member in &members {
+            code.push_str(&format!("  | {}\n", member));
+        }
+        code.pop();
+        code.push_str(";\n");
+        code
+    }
+
+    /// `{ a: string; b?: number | null }`, led by a tag property for internally tagged variants
+    /// and joined by the types of flattened fields, as in `{ a: string } & Base`.
+    fn object_literal(&self, fields: &[RustField], tag: Option<(&String, &String)>) -> String {
+        let mut properties = Vec::new();
+        let mut flattened = Vec::new();
+        if let Some((tag, value)) = tag {
+            properties.push(format!("{}: {}", property_name(tag), value));
+        }
+        for field in fields {
+            match field.serde_flatten {
+                true => flattened.push(self.field_type(field)),
+                false => properties.push(self.property(field)),
+            }
+        }
+        let mut literal = format!("{{ {} }}", properties.join("; "));
+        for field_type in flattened {
+            literal.push_str(&format!(" & {}", field_type));
+        }
+        literal
+    }
+
+    fn property(&self, field: &RustField) -> String {
+        let wire_name = field.serde_rename.clone().unwrap_or_else(|| unraw(&field.name).to_string());
+        let optional = field.is_optional || field.serde_default || matches!(field.ty, TypeRef::Optional(_));
+        format!("{}{}: {}", property_name(&wire_name), if optional { "?" } else { "" }, self.field_type(field))
+    }
+
+    fn field_type(&self, field: &RustField) -> String {
+        match &field.ty {
+            ty if field.is_optional && !matches!(ty, TypeRef::Optional(_)) => self.type_expression(&TypeRef::Optional(Box::new(ty.clone()))),
+            ty => self.type_expression(ty),
+        }
+    }
+
+    fn type_expression(&self, ty: &TypeRef) -> String {
+        match ty {
+            // Generated types win over library types that happen to share a name
+            TypeRef::Named(name) if self.types.structs.iter().any(|s| s.name == *name) || self.types.enums.iter().any(|e| e.name == *name) => {
+                name.clone()
+            }
+            TypeRef::Optional(inner) => {
+                let inner = self.type_expression(inner);
+                match inner.ends_with("| null") || inner == "null" || inner == "unknown" {
+                    true => inner,
+                    false => format!("{} | null", inner),
+                }
+            }
+            TypeRef::List(element) => {
+                let element = self.type_expression(element);
+                match element.contains(" | ") || element.contains(" & ") {
+                    true => format!("({})[]", element),
+                    false => format!("{}[]", element),
+                }
+            }
+            TypeRef::Bytes => "number[]".to_string(),
+            TypeRef::Tuple(items) if items.is_empty() => "null".to_string(),
+            TypeRef::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| self.type_expression(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            // JSON object keys are strings whatever the Rust key type
+            TypeRef::Map(_, value) => format!("Record<string, {}>", self.type_expression(value)),
+            TypeRef::String | TypeRef::Char => "string".to_string(),
+            // TOML datetimes are written as an object when not written as TOML
+            TypeRef::Format(StringFormat::TomlDatetime) => "unknown".to_string(),
+            TypeRef::Format(_) => "string".to_string(),
+            TypeRef::Boolean => "boolean".to_string(),
+            TypeRef::Integer { .. } | TypeRef::Float => "number".to_string(),
+            TypeRef::Named(_) | TypeRef::Any => "unknown".to_string(),
+        }
+    }
+}
+
+/// A JSDoc comment, kept on one line when the docs are.
+fn doc_comment(docs: &[String]) -> String {
+    match docs {
+        [] => String::new(),
+        [line] => format!("/** {} */\n", line),
+        lines => {
+            let body: String = lines.iter().map(|line| format!(" *{}{}\n", if line.is_empty() { "" } else { " " }, line)).collect();
+            format!("/**\n{} */\n", body)
+        }
+    }
+}
+
+/// A property key, quoted when it is not a valid identifier.
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    match is_identifier {
+        true => name.to_string(),
+        false => string_literal(name),
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::analyze_json;
+    use crate::codegen::generate_rust_types_with_config;
+
+    fn generate(json: &str, root_name: &str, config: &GenerationConfig) -> String {
+        let schema = analyze_json(json, root_name).unwrap();
+        let types = generate_rust_types_with_config(&schema, &[], &MergeStrategy::Optional, config).unwrap();
+        generate_typescript_with_types(&types).unwrap()
+    }
+
+    #[test]
+    fn test_interfaces() {
+        let json = r#"[
+            {"id": 1, "first-name": "Ann", "userName": "ann", "address": {"city": "Oslo"}, "tags": ["a"], "role": "admin"},
+            {"id": 2, "first-name": "Bo", "userName": "bo", "address": {"city": "Rome"}, "tags": [], "score": 1.5, "role": "admin"},
+            {"id": 3, "first-name": "Cy", "userName": "cy", "address": {"city": "Lima"}, "tags": [], "role": "user", "readings": [1, "x"]},
+            {"id": 4, "first-name": "Di", "userName": "di", "address": {"city": "Kyiv"}, "tags": [], "role": "user"},
+            {"id": 5, "first-name": "Ed", "userName": "ed", "address": {"city": "Bern"}, "tags": ["b"]}
+        ]"#;
+        let config = GenerationConfig { string_enums: true, ..GenerationConfig::default() };
+        let code = generate(json, "Users", &config);
+
+        assert!(code.contains("export interface Users {\n  items: User[];\n}"), "{}", code);
+        assert!(code.contains("export interface User {\n  id: number;\n  \"first-name\": string;\n  userName: string;\n  address: Address;\n  tags: string[];\n"), "{}", code);
+        assert!(code.contains("  score?: number | null;\n"), "{}", code);
+        assert!(code.contains("  readings?: [number, string] | null;\n"), "{}", code);
+        assert!(code.contains("  role?: Role | null;\n"), "{}", code);
+        assert!(code.contains("export type Role =\n  | \"admin\"\n  | \"user\";\n"), "{}", code);
+        assert!(code.contains("export interface Address {\n  city: string;\n}"), "{}", code);
+    }
+
+    #[test]
+    fn test_tagged_and_untagged_unions() {
+        let json = r#"{
+            "events": [{"type": "click", "x": 1}, {"type": "key", "code": "a"}, {"type": "click", "x": 2}],
+            "values": [{"v": 1}, {"v": "two"}],
+            "labels": {"1": "a", "2": "b", "3": "c", "4": "d", "5": "e"}
+        }"#;
+        let code = generate(json, "Root", &GenerationConfig::default());
+
+        assert!(code.contains("export type Event =\n  | { type: \"click\"; x: number }\n  | { type: \"key\"; code: string };\n"), "{}", code);
+        assert!(code.contains("export type V =\n  | number\n  | string;\n"), "{}", code);
+        assert!(code.contains("  labels: Record<string, string>;\n"), "{}", code);
+    }
+
+    #[test]
+    fn test_enum_representations() {
+        let variant = |name: &str, newtype: Option<&str>| RustEnumVariant {
+            name: name.to_string(),
+            fields: Vec::new(),
+            serde_rename: None,
+            serde_other: false,
+            newtype: newtype.map(str::to_string),
+            newtype_ty: newtype.map(crate::parser::type_ref_from_rust),
+        };
+        let rust_enum = |representation| RustEnum {
+            name: "Message".to_string(),
+            variants: vec![variant("Ping", None), variant("Text", Some("String"))],
+            derives: Vec::new(),
+            representation,
+            docs: Vec::new(),
+        };
+        let types = GeneratedTypes {
+            structs: Vec::new(),
+            enums: vec![rust_enum(EnumRepresentation::External)],
+        };
+
+        let mut external = rust_enum(EnumRepresentation::External);
+        external.docs = vec!["A chat message.".to_string()];
+        let external = TypeScriptEmitter.emit_enum(&external, &types).unwrap();
+        assert_eq!(external, "/** A chat message. */\nexport type Message =\n  | \"Ping\"\n  | { \"Text\": string };\n");
+        let adjacent = TypeScriptEmitter
+            .emit_enum(&rust_enum(EnumRepresentation::Adjacent { tag: "t".to_string(), content: "c".to_string() }), &types)
+            .unwrap();
+        assert_eq!(adjacent, "export type Message =\n  | { t: \"Ping\" }\n  | { t: \"Text\"; c: string };\n");
+        assert_eq!(property_name("$ref"), "$ref");
+        assert_eq!(property_name("2fa"), "\"2fa\"");
+    }
+
+    #[test]
+    fn test_types_come_from_type_ref() {
+        let field = |name: &str, type_name: &str, ty: TypeRef| RustField {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            ty,
+            is_optional: false,
+            serde_rename: None,
+            serde_flatten: false,
+            serde_with: None,
+            serde_default: false,
+        };
+        let fields = vec![
+            field("id", "crate::ids::UserId", TypeRef::Integer { signed: false, bits: 64 }),
+            field("avatar", "Vec<u8>", TypeRef::Format(StringFormat::Base64)),
+            RustField { is_optional: true, ..field("friends", "Vec<Friend>", TypeRef::List(Box::new(TypeRef::Named("User".to_string())))) },
+        ];
+        let types = GeneratedTypes {
+            structs: vec![RustStruct { name: "User".to_string(), fields, derives: Vec::new(), is_optional: false, docs: Vec::new() }],
+            enums: Vec::new(),
+        };
+
+        let code = TypeScriptEmitter.emit_struct(&types.structs[0], &types).unwrap();
+        assert_eq!(code, "export interface User {\n  id: number;\n  avatar: string;\n  friends?: User[] | null;\n}\n");
+    }
+}
@@ -47,14 +47,12 @@ fn convert_json_to_rust_internal(
                 &existing_structs,
                 &merge_strategy,
             )?;
-            let rust_structs = generate_rust_structs(&json_schema, &existing_structs)?;
-            let generated_code = generate_code_with_types_and_preservation_and_schema(
+            generate_code_with_types_and_preservation_and_schema(
                 &generated_types,
-                Some(&existing_rust_code),
+                Some(existing_rust_code),
                 &merge_strategy,
                 Some(&json_schema),
-            )?;
-            generated_code
+            )?
         }
         _ => evolve_rust_types(existing_rust_code, json_input, struct_name, true)?,
     };
@@ -62,14 +60,27 @@ fn convert_json_to_rust_internal(
     Ok(generated_code)
 }
 
+/// TypeScript declarations for the same types `convert_json_to_rust` would generate from
+/// `json_input` alone, so a frontend can share them.
 #[wasm_bindgen]
-pub fn validate_json(json_input: &str) -> bool {
-    match serde_json::from_str::<serde_json::Value>(json_input) {
-        Ok(_) => true,
-        Err(_) => false,
+pub fn convert_json_to_typescript(json_input: &str, struct_name: &str) -> String {
+    match convert_json_to_typescript_internal(json_input, struct_name) {
+        Ok(result) => result,
+        Err(e) => format!("Error: {}", e),
     }
 }
 
+fn convert_json_to_typescript_internal(json_input: &str, struct_name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let json_schema = analyze_json(json_input, struct_name)?;
+    let generated_types = generate_rust_types_with_strategy(&json_schema, &[], &MergeStrategy::Optional)?;
+    Ok(generate_typescript_with_types(&generated_types)?)
+}
+
+#[wasm_bindgen]
+pub fn validate_json(json_input: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(json_input).is_ok()
+}
+
 #[wasm_bindgen]
 pub fn get_error_message(
     json_input: &str,